@@ -0,0 +1,187 @@
+## Built into the game as the fallback for every language, so every message
+## the game asks for must be here.
+
+## Prompts, per input device. Touch has no prompts for keys it doesn't have.
+
+prompt-start-keyboard = Press Space
+prompt-start-gamepad = Press A
+prompt-start-touch = Tap to start
+prompt-modes-keyboard = 2: co-op  3: versus
+prompt-screens-keyboard =
+    H: history  K: skins
+    B: shop  D: daily
+    P: profiles
+prompt-continue-keyboard = C: continue saved run
+prompt-save-replay-keyboard = Press S to save replay
+prompt-save-replay-gamepad = Press Y to save replay
+prompt-save-ghost-keyboard = Press G to save ghost
+prompt-save-ghost-gamepad = Press X to save ghost
+
+## Heads-up display and game over.
+
+hud-score = Score:{" "}
+summary-score = Score:{" "}
+summary-co-op-score = Co-op score:{" "}
+summary-unranked = Unranked
+summary-unranked-because = Unranked: { $reasons }
+
+## Versus rounds. Times are in seconds.
+
+versus-first-round =
+    P1 survived { NUMBER($seconds, minimumFractionDigits: 1) } { $seconds ->
+        [one] second
+       *[other] seconds
+    }
+    Press 3 for round 2, P2 dodges
+versus-p1-wins = P1 wins
+versus-p2-wins = P2 wins
+versus-draw = Draw
+versus-result =
+    P1 { NUMBER($first, minimumFractionDigits: 1) }s, P2 { NUMBER($second, minimumFractionDigits: 1) }s: { $winner }
+    Press 3 for a rematch
+
+## Pause menu. $value is the option's current value.
+
+pause-title = Paused
+setting-on = On
+setting-off = Off
+pause-resume = Resume
+pause-save-quit = Save & Quit
+pause-sound = Sound: { $value }
+pause-screen-shake = Screen Shake: { $value }
+pause-reduced-motion = Reduced Motion: { $value }
+pause-game-speed = Game Speed: { $value }%
+pause-assist-hitbox = Small Hitbox: { $value }
+pause-assist-lives = Extra Lives: { $value }
+pause-controls = Controls: { $value }
+pause-submit-scores = Online Scores: { $value }
+pause-telemetry = Share Anonymous Stats: { $value }
+pause-crt = CRT Filter: { $value }
+pause-palette = Palette: { $value }
+pause-language = Language: { $value }
+pause-fullscreen = Fullscreen: { $value }
+pause-monitor = Monitor: { $value }
+pause-vsync = VSync: { $value }
+pause-fps-cap = FPS Cap: { $value }
+
+## Read out by the narrator.
+
+narration-title = Dodger. { $prompts }
+narration-go = Go
+narration-game-over = Game over. Score { $score }. { $prompt }
+
+## Pause menu values.
+
+controls-standard = Standard
+controls-left-hand = Left Hand
+controls-right-hand = Right Hand
+controls-mouse = Mouse
+palette-classic = Classic
+palette-sunset = Sunset
+palette-mono = Mono
+palette-neon = Neon
+palette-deuteranopia = Deuteranopia
+palette-protanopia = Protanopia
+palette-tritanopia = Tritanopia
+fullscreen-borderless = Borderless
+fullscreen-exclusive = Exclusive
+
+## Why a run is unranked, listed after summary-unranked-because.
+
+unranked-small-hitbox = small hitbox
+unranked-extra-lives = extra lives
+unranked-game-speed = { $speed }% speed
+unranked-resumed = Resumed
+
+## Title screen.
+
+load-errors = Failed to load: { $files }
+profile-current = Profile: { $name }
+leaderboards-hint = L: online scores
+
+## Saving from the game over screen. $key is where it was saved.
+
+replay-saved = Saved { $key }
+replay-save-failed = Failed to save replay
+ghost-saved = Saved { $key }
+ghost-save-failed = Failed to save ghost
+
+## Versus and online matches.
+
+versus-dropper =
+    P{ $player } drops: { $kind } ({ $cost })
+    Budget: { NUMBER($budget, minimumFractionDigits: 1) }
+online-scores = P1 { $first }  P2 { $second }
+online-you-win = You win
+online-you-lose = You lose
+online-waiting = Opponent still dodging...
+
+## Recent runs.
+
+history-title = Recent Runs
+history-empty = No runs yet
+run-mode-solo = Solo
+run-mode-co-op = Co-op
+run-mode-versus = Versus
+history-seed = seed { $seed }
+history-replay = [replay]
+history-help = Up/Down: select  Enter: watch replay  Esc: back
+
+## Profiles.
+
+profiles-title = Profiles
+profiles-playing = (playing)
+profiles-new = New: { $name }_
+profiles-note =
+    Scores, unlocks, coins and settings
+    are kept per profile
+profiles-help = Up/Down: select  Enter: play as  Esc: back
+
+## Daily challenge.
+
+daily-title = Daily Challenge  { $day }
+daily-about = Everyone faces the same enemies today.
+daily-no-clock = The daily challenge needs a clock, which this build doesn't have
+daily-score = Today's score: { $score }
+daily-not-counted = Today's attempt didn't count
+daily-not-eligible = Turn off assists and upgrades, and play at full speed, to take part
+daily-start = One attempt. Enter: start
+daily-past-days = Past days
+daily-none-yet = None yet
+daily-help = Esc: back
+
+## Shop.
+
+upgrade-shield = Shield
+upgrade-speed = +5% Speed
+upgrade-extra-life = Extra Life
+shop-title = Shop  Coins: { $coins }
+shop-price = { $price } coins
+shop-note = Runs with upgrades on are unranked
+shop-help = Up/Down: select  Enter: buy / toggle  Esc: back
+shop-coins-earned = +{ $coins } { $coins ->
+        [one] coin
+       *[other] coins
+    } ({ $total })
+
+## Skins.
+
+skin-robot = Robot
+skin-gold-robot = Gold Robot
+skin-knight = Knight
+skin-ember-knight = Ember Knight
+skin-king = King
+skins-title = Skins
+skins-unlocked = Unlocked: { $skins }
+skins-locked = Score { $score } in a ranked run to unlock
+skins-equipped = Equipped
+skins-wear = Enter: wear
+skins-help = Left/Right: browse  Esc: back
+
+## Online leaderboard.
+
+leaderboards-title = Online Scores
+leaderboards-loading = Loading...
+leaderboards-unreachable = Couldn't reach the leaderboard
+leaderboards-empty = No scores yet
+leaderboards-help = Up/Down: scroll  Esc: back
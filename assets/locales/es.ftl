@@ -0,0 +1,186 @@
+## Anything missing here falls back to en-US.ftl.
+
+## Prompts, per input device.
+
+prompt-start-keyboard = Pulsa Espacio
+prompt-start-gamepad = Pulsa A
+prompt-start-touch = Toca para empezar
+prompt-modes-keyboard = 2: cooperativo  3: versus
+prompt-screens-keyboard =
+    H: historial  K: aspectos
+    B: tienda  D: diario
+    P: perfiles
+prompt-continue-keyboard = C: continuar partida guardada
+prompt-save-replay-keyboard = Pulsa S para guardar la repetición
+prompt-save-replay-gamepad = Pulsa Y para guardar la repetición
+prompt-save-ghost-keyboard = Pulsa G para guardar el fantasma
+prompt-save-ghost-gamepad = Pulsa X para guardar el fantasma
+
+## Heads-up display and game over.
+
+hud-score = Puntos:{" "}
+summary-score = Puntos:{" "}
+summary-co-op-score = Puntos en equipo:{" "}
+summary-unranked = Sin clasificar
+summary-unranked-because = Sin clasificar: { $reasons }
+
+## Versus rounds. Times are in seconds.
+
+versus-first-round =
+    J1 aguantó { NUMBER($seconds, minimumFractionDigits: 1) } { $seconds ->
+        [one] segundo
+       *[other] segundos
+    }
+    Pulsa 3 para la ronda 2, J2 esquiva
+versus-p1-wins = Gana J1
+versus-p2-wins = Gana J2
+versus-draw = Empate
+versus-result =
+    J1 { NUMBER($first, minimumFractionDigits: 1) }s, J2 { NUMBER($second, minimumFractionDigits: 1) }s: { $winner }
+    Pulsa 3 para la revancha
+
+## Pause menu. $value is the option's current value.
+
+pause-title = Pausa
+setting-on = Sí
+setting-off = No
+pause-resume = Continuar
+pause-save-quit = Guardar y salir
+pause-sound = Sonido: { $value }
+pause-screen-shake = Temblor de pantalla: { $value }
+pause-reduced-motion = Menos movimiento: { $value }
+pause-game-speed = Velocidad: { $value }%
+pause-assist-hitbox = Hitbox pequeña: { $value }
+pause-assist-lives = Vidas extra: { $value }
+pause-controls = Controles: { $value }
+pause-submit-scores = Puntos en línea: { $value }
+pause-telemetry = Compartir estadísticas anónimas: { $value }
+pause-crt = Filtro CRT: { $value }
+pause-palette = Paleta: { $value }
+pause-language = Idioma: { $value }
+pause-fullscreen = Pantalla completa: { $value }
+pause-monitor = Monitor: { $value }
+pause-vsync = VSync: { $value }
+pause-fps-cap = Límite de FPS: { $value }
+
+## Read out by the narrator.
+
+narration-title = Dodger. { $prompts }
+narration-go = Ya
+narration-game-over = Fin de la partida. { $score } puntos. { $prompt }
+
+## Pause menu values.
+
+controls-standard = Estándar
+controls-left-hand = Mano izquierda
+controls-right-hand = Mano derecha
+controls-mouse = Ratón
+palette-classic = Clásica
+palette-sunset = Atardecer
+palette-mono = Mono
+palette-neon = Neón
+palette-deuteranopia = Deuteranopía
+palette-protanopia = Protanopía
+palette-tritanopia = Tritanopía
+fullscreen-borderless = Sin bordes
+fullscreen-exclusive = Exclusiva
+
+## Why a run is unranked, listed after summary-unranked-because.
+
+unranked-small-hitbox = hitbox pequeña
+unranked-extra-lives = vidas extra
+unranked-game-speed = velocidad al { $speed }%
+unranked-resumed = Reanudada
+
+## Title screen.
+
+load-errors = No se pudo cargar: { $files }
+profile-current = Perfil: { $name }
+leaderboards-hint = L: puntos en línea
+
+## Saving from the game over screen. $key is where it was saved.
+
+replay-saved = Guardada en { $key }
+replay-save-failed = No se pudo guardar la repetición
+ghost-saved = Guardado en { $key }
+ghost-save-failed = No se pudo guardar el fantasma
+
+## Versus and online matches.
+
+versus-dropper =
+    J{ $player } lanza: { $kind } ({ $cost })
+    Presupuesto: { NUMBER($budget, minimumFractionDigits: 1) }
+online-scores = J1 { $first }  J2 { $second }
+online-you-win = Has ganado
+online-you-lose = Has perdido
+online-waiting = El rival sigue esquivando...
+
+## Recent runs.
+
+history-title = Partidas recientes
+history-empty = Aún no hay partidas
+run-mode-solo = Solo
+run-mode-co-op = Equipo
+run-mode-versus = Versus
+history-seed = semilla { $seed }
+history-replay = [repetición]
+history-help = Arriba/Abajo: elegir  Intro: ver repetición  Esc: volver
+
+## Profiles.
+
+profiles-title = Perfiles
+profiles-playing = (jugando)
+profiles-new = Nuevo: { $name }_
+profiles-note =
+    Puntos, desbloqueos, monedas y ajustes
+    se guardan por perfil
+profiles-help = Arriba/Abajo: elegir  Intro: jugar  Esc: volver
+
+## Daily challenge.
+
+daily-title = Reto diario  { $day }
+daily-about = Hoy todos se enfrentan a los mismos enemigos.
+daily-no-clock = El reto diario necesita un reloj, y esta versión no lo tiene
+daily-score = Puntos de hoy: { $score }
+daily-not-counted = El intento de hoy no contó
+daily-not-eligible = Quita las ayudas y mejoras, y juega a velocidad normal, para participar
+daily-start = Un intento. Intro: empezar
+daily-past-days = Días anteriores
+daily-none-yet = Aún ninguno
+daily-help = Esc: volver
+
+## Shop.
+
+upgrade-shield = Escudo
+upgrade-speed = +5% velocidad
+upgrade-extra-life = Vida extra
+shop-title = Tienda  Monedas: { $coins }
+shop-price = { $price } monedas
+shop-note = Las partidas con mejoras no se clasifican
+shop-help = Arriba/Abajo: elegir  Intro: comprar / activar  Esc: volver
+shop-coins-earned = +{ $coins } { $coins ->
+        [one] moneda
+       *[other] monedas
+    } ({ $total })
+
+## Skins.
+
+skin-robot = Robot
+skin-gold-robot = Robot dorado
+skin-knight = Caballero
+skin-ember-knight = Caballero ascua
+skin-king = Rey
+skins-title = Aspectos
+skins-unlocked = Desbloqueado: { $skins }
+skins-locked = Consigue { $score } puntos en una partida clasificada para desbloquearlo
+skins-equipped = Equipado
+skins-wear = Intro: equipar
+skins-help = Izquierda/Derecha: ver  Esc: volver
+
+## Online leaderboard.
+
+leaderboards-title = Puntos en línea
+leaderboards-loading = Cargando...
+leaderboards-unreachable = No se pudo conectar con la clasificación
+leaderboards-empty = Aún no hay puntos
+leaderboards-help = Arriba/Abajo: desplazar  Esc: volver
@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use dodger::{collision::Aabb, spatial::SpatialGrid};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const ENEMY_COUNTS: [usize; 4] = [100, 500, 1000, 2000];
+const CELL_SIZE: f32 = 64.0;
+
+struct Body {
+    entity: Entity,
+    center: Vec2,
+    size: Vec2,
+}
+
+fn bodies(count: usize) -> Vec<Body> {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    (0..count)
+        .map(|i| Body {
+            entity: Entity::from_raw(i as u32),
+            center: Vec2::new(rng.gen_range(-320.0..320.0), rng.gen_range(-220.0..220.0)),
+            size: Vec2::splat(rng.gen_range(0.5..5.0) * 16.0),
+        })
+        .collect()
+}
+
+fn overlaps(a: &Body, b: &Body) -> bool {
+    Aabb::new(a.center.into(), a.size.into()).overlaps(&Aabb::new(b.center.into(), b.size.into()))
+}
+
+fn brute_force_pairs(bodies: &[Body]) -> usize {
+    let mut hits = 0;
+    for (i, a) in bodies.iter().enumerate() {
+        for b in &bodies[i + 1..] {
+            if overlaps(a, b) {
+                hits += 1;
+            }
+        }
+    }
+    hits
+}
+
+fn grid_pairs(grid: &mut SpatialGrid, bodies: &[Body]) -> usize {
+    grid.clear();
+    for body in bodies {
+        grid.insert(body.entity, body.center, body.size);
+    }
+
+    let mut hits = 0;
+    for a in bodies {
+        for entity in grid.query(a.center, a.size) {
+            let b = &bodies[entity.index() as usize];
+            if a.entity < b.entity && overlaps(a, b) {
+                hits += 1;
+            }
+        }
+    }
+    hits
+}
+
+fn brute_force_player(player: &Body, bodies: &[Body]) -> usize {
+    bodies.iter().filter(|body| overlaps(player, body)).count()
+}
+
+fn grid_player(grid: &mut SpatialGrid, player: &Body, bodies: &[Body]) -> usize {
+    grid.clear();
+    for body in bodies {
+        grid.insert(body.entity, body.center, body.size);
+    }
+
+    grid.query(player.center, player.size)
+        .into_iter()
+        .filter(|entity| overlaps(player, &bodies[entity.index() as usize]))
+        .count()
+}
+
+fn player_vs_enemies(c: &mut Criterion) {
+    let mut group = c.benchmark_group("player_vs_enemies");
+    let player = Body {
+        entity: Entity::from_raw(u32::MAX),
+        center: Vec2::new(0.0, -220.0),
+        size: Vec2::splat(16.0),
+    };
+
+    for count in ENEMY_COUNTS {
+        let bodies = bodies(count);
+        let mut grid = SpatialGrid::new(CELL_SIZE);
+
+        group.bench_with_input(
+            BenchmarkId::new("brute_force", count),
+            &bodies,
+            |b, bodies| b.iter(|| brute_force_player(black_box(&player), black_box(bodies))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("spatial_grid", count),
+            &bodies,
+            |b, bodies| b.iter(|| grid_player(&mut grid, black_box(&player), black_box(bodies))),
+        );
+    }
+
+    group.finish();
+}
+
+fn enemy_vs_enemy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("enemy_vs_enemy");
+
+    for count in ENEMY_COUNTS {
+        let bodies = bodies(count);
+        let mut grid = SpatialGrid::new(CELL_SIZE);
+
+        group.bench_with_input(
+            BenchmarkId::new("brute_force", count),
+            &bodies,
+            |b, bodies| b.iter(|| brute_force_pairs(black_box(bodies))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("spatial_grid", count),
+            &bodies,
+            |b, bodies| b.iter(|| grid_pairs(&mut grid, black_box(bodies))),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, player_vs_enemies, enemy_vs_enemy);
+criterion_main!(benches);
@@ -0,0 +1,103 @@
+use bevy::{ecs::event::Events, prelude::*};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use dodger::{
+    enemy::Collider,
+    movement::{self, Interpolated, Velocity},
+    player::{CollisionEvent, Player},
+    settings::Settings,
+    spatial::SpatialGrid,
+    time_scale::TimeScale,
+    SPRITE_SIZE,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const ENEMY_COUNTS: [usize; 2] = [1_000, 10_000];
+const CELL_SIZE: f32 = 64.0;
+
+/// A world holding the player and `count` falling enemies, with the spatial
+/// grid already filled the way `rebuild_spatial_grid` would.
+fn world_with_enemies(count: usize) -> World {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut world = World::new();
+    let mut grid = SpatialGrid::new(CELL_SIZE);
+
+    world.spawn((Player { index: 0 }, Transform::from_xyz(0.0, -200.0, 1.0)));
+
+    for _ in 0..count {
+        let translation = Vec3::new(
+            rng.gen_range(-320.0..320.0),
+            rng.gen_range(-220.0..220.0),
+            1.0,
+        );
+        let scale = rng.gen_range(0.5..5.0);
+        let entity = world
+            .spawn((
+                Transform {
+                    translation,
+                    scale: Vec3::splat(scale),
+                    ..default()
+                },
+                Interpolated::new(translation),
+                Velocity(Vec3::new(0.0, -rng.gen_range(50.0..250.0), 0.0)),
+                Collider,
+            ))
+            .id();
+
+        grid.insert(
+            entity,
+            translation.truncate(),
+            Vec2::splat(scale * SPRITE_SIZE),
+        );
+    }
+
+    world.insert_resource(grid);
+    world.insert_resource(TimeScale(1.0));
+    world.insert_resource(Settings::default());
+    world.insert_resource(Events::<CollisionEvent>::default());
+    world
+}
+
+fn apply_velocity(c: &mut Criterion) {
+    let mut group = c.benchmark_group("apply_velocity");
+
+    for count in ENEMY_COUNTS {
+        let mut world = world_with_enemies(count);
+        let mut schedule = Schedule::default();
+        schedule.add_systems(movement::apply_velocity);
+
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| schedule.run(&mut world))
+        });
+    }
+
+    group.finish();
+}
+
+#[cfg(not(feature = "rapier"))]
+fn check_collisions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("check_collisions");
+
+    for count in ENEMY_COUNTS {
+        let mut world = world_with_enemies(count);
+        let mut schedule = Schedule::default();
+        schedule.add_systems(dodger::player::check_collisions);
+
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| {
+                schedule.run(&mut world);
+                world
+                    .get_resource_mut::<Events<CollisionEvent>>()
+                    .unwrap()
+                    .clear();
+            })
+        });
+    }
+
+    group.finish();
+}
+
+#[cfg(not(feature = "rapier"))]
+criterion_group!(benches, apply_velocity, check_collisions);
+#[cfg(feature = "rapier")]
+criterion_group!(benches, apply_velocity);
+criterion_main!(benches);
@@ -0,0 +1,205 @@
+use bevy::prelude::*;
+
+use crate::{
+    states::{RunActive, RunState},
+    time_scale::TimeScale,
+};
+
+const SQUASH_STIFFNESS: f32 = 300.0;
+const SQUASH_DAMPING: f32 = 12.0;
+const SPAWN_ANIM_SECONDS: f32 = 0.2;
+const SPAWN_ANIM_START_SCALE: f32 = 0.6;
+const DESPAWN_ANIM_SECONDS: f32 = 0.15;
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Squash>().add_systems(
+            Update,
+            (animate_sprites, spring_squash, play_spawn_anims).run_if(in_state(RunState::Running)),
+        );
+
+        app.add_systems(Update, play_despawn_anims.run_if(in_state(RunActive)));
+    }
+}
+
+/// Cycles a sprite through a set of atlas indices on a fixed frame timer.
+#[derive(Component)]
+pub struct FrameAnimation {
+    frames: &'static [usize],
+    flip_x: bool,
+    frame: usize,
+    timer: Timer,
+}
+
+impl FrameAnimation {
+    pub fn new(frames: &'static [usize], frame_seconds: f32) -> Self {
+        Self {
+            frames,
+            flip_x: false,
+            frame: 0,
+            timer: Timer::from_seconds(frame_seconds, TimerMode::Repeating),
+        }
+    }
+
+    /// Switches to another set of frames, restarting only if it differs from the current one.
+    pub fn play(&mut self, frames: &'static [usize], flip_x: bool) {
+        if self.frames == frames && self.flip_x == flip_x {
+            return;
+        }
+
+        self.frames = frames;
+        self.flip_x = flip_x;
+        self.frame = 0;
+        self.timer.reset();
+    }
+}
+
+/// Deforms a unit-scale transform by a damped spring that settles back to 1.0.
+/// The player prefab can change how springy it is.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Squash {
+    pub stiffness: f32,
+    pub damping: f32,
+    #[reflect(ignore)]
+    offset: Vec2,
+    #[reflect(ignore)]
+    velocity: Vec2,
+}
+
+impl Default for Squash {
+    fn default() -> Self {
+        Self {
+            stiffness: SQUASH_STIFFNESS,
+            damping: SQUASH_DAMPING,
+            offset: Vec2::ZERO,
+            velocity: Vec2::ZERO,
+        }
+    }
+}
+
+impl Squash {
+    pub fn deform(&mut self, offset: Vec2) {
+        self.offset = offset;
+        self.velocity = Vec2::ZERO;
+    }
+}
+
+/// Fades a freshly spawned sprite in from transparent while growing it to
+/// `scale`. It stays on the entity once finished so it can be restarted.
+#[derive(Component)]
+pub struct SpawnAnim {
+    scale: f32,
+    timer: Timer,
+}
+
+impl SpawnAnim {
+    pub fn new(scale: f32) -> Self {
+        Self {
+            scale,
+            timer: Timer::from_seconds(SPAWN_ANIM_SECONDS, TimerMode::Once),
+        }
+    }
+
+    pub fn restart(&mut self, scale: f32) {
+        self.scale = scale;
+        self.timer.reset();
+    }
+
+    pub fn start_scale(&self) -> f32 {
+        self.scale * SPAWN_ANIM_START_SCALE
+    }
+
+    /// The scale the sprite grows to.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+}
+
+/// Shrinks and fades a sprite out from its current `scale`, then despawns it.
+/// Add it in place of despawning directly to give removed entities a quick pop.
+#[derive(Component)]
+pub struct DespawnAnim {
+    scale: Vec3,
+    timer: Timer,
+}
+
+impl DespawnAnim {
+    pub fn new(scale: Vec3) -> Self {
+        Self {
+            scale,
+            timer: Timer::from_seconds(DESPAWN_ANIM_SECONDS, TimerMode::Once),
+        }
+    }
+}
+
+fn animate_sprites(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut query: Query<(&mut FrameAnimation, &mut TextureAtlas, &mut Sprite)>,
+) {
+    let delta = time_scale.delta(&time);
+    for (mut animation, mut atlas, mut sprite) in query.iter_mut() {
+        if animation.timer.tick(delta).just_finished() {
+            animation.frame = (animation.frame + 1) % animation.frames.len();
+        }
+
+        atlas.index = animation.frames[animation.frame];
+        sprite.flip_x = animation.flip_x;
+    }
+}
+
+fn spring_squash(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut query: Query<(&mut Squash, &mut Transform)>,
+) {
+    let delta_time = time_scale.delta_seconds(&time);
+
+    for (mut squash, mut transform) in query.iter_mut() {
+        let acceleration = -squash.stiffness * squash.offset - squash.damping * squash.velocity;
+        squash.velocity += acceleration * delta_time;
+        let velocity = squash.velocity;
+        squash.offset += velocity * delta_time;
+
+        transform.scale = Vec3::new(1.0 + squash.offset.x, 1.0 + squash.offset.y, 1.0);
+    }
+}
+
+fn play_spawn_anims(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut query: Query<(&mut SpawnAnim, &mut Transform, &mut Sprite)>,
+) {
+    for (mut anim, mut transform, mut sprite) in query.iter_mut() {
+        if anim.timer.finished() {
+            continue;
+        }
+
+        let t = anim.timer.tick(time_scale.delta(&time)).fraction();
+        let scale = anim.start_scale() + (anim.scale - anim.start_scale()) * t;
+
+        transform.scale = Vec3::new(scale, scale, 1.0);
+        sprite.color.set_alpha(t);
+    }
+}
+
+fn play_despawn_anims(
+    mut commands: Commands,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut query: Query<(Entity, &mut DespawnAnim, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut anim, mut transform, mut sprite) in query.iter_mut() {
+        if anim.timer.tick(time_scale.delta(&time)).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let remaining = 1.0 - anim.timer.fraction();
+        transform.scale = anim.scale * Vec3::new(remaining, remaining, 1.0);
+        sprite.color.set_alpha(remaining);
+    }
+}
@@ -0,0 +1,286 @@
+use std::ops::Range;
+
+use bevy::prelude::*;
+use bevy_kira_audio::{AudioControl, AudioSource, DynamicAudioChannels};
+use rand::Rng;
+
+use crate::{
+    bounds::PlayArea,
+    enemy::EnemySpawnEvent,
+    loading::LoadingAssets,
+    mobile::SafeArea,
+    player::PlayerDeathEvent,
+    settings::Settings,
+    states::{GameState, MenuSelectEvent},
+};
+
+const PITCH_VARIATION: f32 = 0.1;
+const CROSSFADE_SECONDS: f32 = 1.5;
+const DUCK_SECONDS: f32 = 1.2;
+const DUCK_DEPTH: f32 = 0.7;
+const MUTE_ICON_SIZE: f32 = 32.0;
+const MUTE_ICON_PADDING: f32 = 16.0;
+
+pub struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        let buses = AudioBuses {
+            music: ["music_a", "music_b"],
+            sfx: "sfx",
+            panned_sfx: "panned_sfx",
+        };
+        app.add_plugins(bevy_kira_audio::AudioPlugin);
+        let mut channels = app.world_mut().resource_mut::<DynamicAudioChannels>();
+        for key in buses.music.iter().chain([&buses.sfx, &buses.panned_sfx]) {
+            channels.create_channel(key);
+        }
+
+        app.insert_resource(buses)
+            .insert_resource(MusicMixer {
+                active: 0,
+                fade: 1.0,
+                duck: 0.0,
+            })
+            .add_systems(Startup, load_sounds)
+            .add_systems(Update, toggle_mute)
+            .add_systems(Update, update_mute_icon)
+            .add_systems(Update, apply_bus_volumes)
+            .add_systems(Update, play_state_music)
+            .add_systems(Update, mix_music)
+            .add_systems(Update, play_sound_effects);
+    }
+}
+
+#[derive(Component)]
+struct MuteIcon;
+
+/// The keys of kira's channels, created when the plugin is built.
+#[derive(Resource)]
+struct AudioBuses {
+    music: [&'static str; 2],
+    sfx: &'static str,
+    panned_sfx: &'static str,
+}
+
+/// Crossfades between the two music channels and ducks them under the death sting.
+/// `fade` and `duck` run from 0.0 to 1.0 and are advanced by `mix_music`.
+#[derive(Resource)]
+struct MusicMixer {
+    active: usize,
+    fade: f32,
+    duck: f32,
+}
+
+#[derive(Resource)]
+struct MusicTracks {
+    title: Handle<AudioSource>,
+    playing: Handle<AudioSource>,
+    game_over: Handle<AudioSource>,
+}
+
+#[derive(Resource)]
+struct SoundEffects {
+    spawn: Handle<AudioSource>,
+    death: Handle<AudioSource>,
+    menu: Handle<AudioSource>,
+}
+
+fn load_sounds(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    let tracks = MusicTracks {
+        title: asset_server.load("music/title.wav"),
+        playing: asset_server.load("music/playing.wav"),
+        game_over: asset_server.load("music/game_over.wav"),
+    };
+    let effects = SoundEffects {
+        spawn: asset_server.load("sounds/spawn.wav"),
+        death: asset_server.load("sounds/death.wav"),
+        menu: asset_server.load("sounds/menu.wav"),
+    };
+
+    for handle in [
+        &tracks.title,
+        &tracks.playing,
+        &tracks.game_over,
+        &effects.spawn,
+        &effects.death,
+        &effects.menu,
+    ] {
+        loading.add(handle);
+    }
+
+    commands.insert_resource(tracks);
+    commands.insert_resource(effects);
+}
+
+fn toggle_mute(keyboard_input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    if keyboard_input.just_pressed(KeyCode::KeyM) {
+        settings.muted = !settings.muted;
+    }
+}
+
+/// The icon sits at the top in the middle, the one edge of the screen no
+/// menu or HUD puts anything in: the score and screen titles take the top
+/// left, the leaderboard and co-op status the top right, and prompts and
+/// the other modes' HUDs the bottom corners.
+fn update_mute_icon(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    safe_area: Res<SafeArea>,
+    query: Query<Entity, With<MuteIcon>>,
+) {
+    match (settings.muted, query.iter().next()) {
+        (true, None) => {
+            commands
+                .spawn(ImageBundle {
+                    style: Style {
+                        width: Val::Px(MUTE_ICON_SIZE),
+                        height: Val::Px(MUTE_ICON_SIZE),
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(MUTE_ICON_PADDING + safe_area.top),
+                        left: Val::Percent(50.0),
+                        margin: UiRect::left(Val::Px(-MUTE_ICON_SIZE / 2.0)),
+                        ..default()
+                    },
+                    image: UiImage::new(asset_server.load("muted.png")),
+                    ..default()
+                })
+                .insert(MuteIcon);
+        }
+        (false, Some(icon)) => commands.entity(icon).despawn(),
+        _ => {}
+    }
+}
+
+fn apply_bus_volumes(
+    channels: Res<DynamicAudioChannels>,
+    buses: Res<AudioBuses>,
+    settings: Res<Settings>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let gain = if settings.muted { 0.0 } else { 1.0 };
+    let volume = (settings.sfx_volume * gain) as f64;
+    channels.channel(buses.sfx).set_volume(volume);
+    channels.channel(buses.panned_sfx).set_volume(volume);
+}
+
+fn play_state_music(
+    channels: Res<DynamicAudioChannels>,
+    buses: Res<AudioBuses>,
+    tracks: Res<MusicTracks>,
+    state: Res<State<GameState>>,
+    mut mixer: ResMut<MusicMixer>,
+    mut playing: Local<Option<GameState>>,
+) {
+    let current = state.get();
+    if playing.as_ref() == Some(current) {
+        return;
+    }
+
+    let track = match current {
+        GameState::Title => &tracks.title,
+        GameState::Playing => &tracks.playing,
+        GameState::GameOver => &tracks.game_over,
+        GameState::Loading
+        | GameState::Dying
+        | GameState::Leaderboards
+        | GameState::History
+        | GameState::Skins
+        | GameState::Shop
+        | GameState::Daily
+        | GameState::Profiles => return,
+    };
+
+    mixer.active = 1 - mixer.active;
+    mixer.fade = 0.0;
+
+    let channel = channels.channel(buses.music[mixer.active]);
+    channel.stop();
+    channel.set_volume(0.0);
+    channel.play(track.clone()).looped();
+    *playing = Some(current.clone());
+}
+
+fn mix_music(
+    time: Res<Time>,
+    channels: Res<DynamicAudioChannels>,
+    buses: Res<AudioBuses>,
+    settings: Res<Settings>,
+    mut mixer: ResMut<MusicMixer>,
+    mut ev_death: EventReader<PlayerDeathEvent>,
+) {
+    if ev_death.read().last().is_some() {
+        mixer.duck = 1.0;
+    }
+
+    if mixer.fade >= 1.0 && mixer.duck <= 0.0 && !settings.is_changed() {
+        return;
+    }
+
+    let delta_time = time.delta_seconds();
+    let active = mixer.active;
+
+    if mixer.fade < 1.0 {
+        mixer.fade = (mixer.fade + delta_time / CROSSFADE_SECONDS).min(1.0);
+
+        if mixer.fade >= 1.0 {
+            channels.channel(buses.music[1 - active]).stop();
+        }
+    }
+
+    mixer.duck = (mixer.duck - delta_time / DUCK_SECONDS).max(0.0);
+
+    let gain = if settings.muted { 0.0 } else { 1.0 };
+    let volume = settings.music_volume * gain * (1.0 - DUCK_DEPTH * mixer.duck);
+    channels
+        .channel(buses.music[active])
+        .set_volume((volume * mixer.fade) as f64);
+    channels
+        .channel(buses.music[1 - active])
+        .set_volume((volume * (1.0 - mixer.fade)) as f64);
+}
+
+fn play_sound_effects(
+    channels: Res<DynamicAudioChannels>,
+    buses: Res<AudioBuses>,
+    sounds: Res<SoundEffects>,
+    play_area: Res<PlayArea>,
+    mut ev_spawn: EventReader<EnemySpawnEvent>,
+    mut ev_death: EventReader<PlayerDeathEvent>,
+    mut ev_menu: EventReader<MenuSelectEvent>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for spawn in ev_spawn.read() {
+        let pitch = rng.gen_range(1.0 - PITCH_VARIATION..1.0 + PITCH_VARIATION);
+        channels
+            .channel(buses.panned_sfx)
+            .play(sounds.spawn.clone())
+            .with_playback_rate(pitch as f64)
+            .with_panning(panning(spawn.0.x, &play_area.x) as f64);
+    }
+
+    for _death in ev_death.read() {
+        channels.channel(buses.sfx).play(sounds.death.clone());
+    }
+
+    for _select in ev_menu.read() {
+        channels.channel(buses.sfx).play(sounds.menu.clone());
+    }
+}
+
+/// Maps a world X position onto kira's panning range (0.0 left, 1.0 right),
+/// stopping short of the extremes so edge spawns stay audible in both ears.
+fn panning(x: f32, screen_x: &Range<f32>) -> f32 {
+    let width = screen_x.end - screen_x.start;
+    let offset = ((x - screen_x.start) / width).clamp(0.0, 1.0) - 0.5;
+    0.5 + offset * 0.8
+}
@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    bounds::PlayArea,
+    score::Scoreboard,
+    settings::Settings,
+    states::{GameState, InRun, RunState},
+    time_scale::TimeScale,
+    SpriteSheet, SPRITE_SIZE,
+};
+
+const LAYERS: [BackgroundLayer; 3] = [
+    BackgroundLayer {
+        sprite: 879,
+        count: 40,
+        speed: 10.0,
+        scale: 1.0,
+        alpha: 0.3,
+        z: 0.1,
+    },
+    BackgroundLayer {
+        sprite: 618,
+        count: 12,
+        speed: 25.0,
+        scale: 1.0,
+        alpha: 0.35,
+        z: 0.2,
+    },
+    BackgroundLayer {
+        sprite: 575,
+        count: 6,
+        speed: 45.0,
+        scale: 1.5,
+        alpha: 0.4,
+        z: 0.3,
+    },
+];
+
+/// Seconds survived at which the sky reaches each color of the palette's
+/// gradient, from midday to deep night.
+const SKY_TIMES: [f32; 4] = [0.0, 45.0, 90.0, 150.0];
+const DAY_STAR_VISIBILITY: f32 = 0.3;
+
+pub struct BackgroundPlugin;
+
+impl Plugin for BackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_background)
+            .add_systems(
+                Update,
+                (scroll_background, update_sky).run_if(in_state(RunState::Running)),
+            )
+            .add_systems(OnExit(InRun), reset_sky);
+    }
+}
+
+struct BackgroundLayer {
+    sprite: usize,
+    count: usize,
+    speed: f32,
+    scale: f32,
+    alpha: f32,
+    z: f32,
+}
+
+#[derive(Component)]
+struct Scroll {
+    speed: f32,
+    alpha: f32,
+}
+
+fn spawn_background(
+    mut commands: Commands,
+    sprite_sheet: Res<SpriteSheet>,
+    play_area: Res<PlayArea>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for layer in LAYERS.iter() {
+        for _ in 0..layer.count {
+            let x = rng.gen_range(play_area.x.clone());
+            let y = rng.gen_range(play_area.y.clone());
+
+            commands
+                .spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::srgba(1.0, 1.0, 1.0, layer.alpha),
+                            ..default()
+                        },
+                        texture: sprite_sheet.texture.clone(),
+                        transform: Transform {
+                            translation: Vec3::new(x, y, layer.z),
+                            scale: Vec3::new(layer.scale, layer.scale, 1.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    sprite_sheet.atlas(layer.sprite),
+                ))
+                .insert(Scroll {
+                    speed: layer.speed,
+                    alpha: layer.alpha,
+                })
+                .insert(StateScoped(InRun));
+        }
+    }
+}
+
+fn scroll_background(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    play_area: Res<PlayArea>,
+    settings: Res<Settings>,
+    mut query: Query<(&Scroll, &mut Transform)>,
+) {
+    if settings.reduced_motion {
+        return;
+    }
+
+    let delta_time = time_scale.delta_seconds(&time);
+    let mut rng = rand::thread_rng();
+
+    for (scroll, mut transform) in query.iter_mut() {
+        transform.translation.y -= scroll.speed * delta_time;
+
+        if transform.translation.y < play_area.y.start - SPRITE_SIZE {
+            transform.translation.y = play_area.y.end + SPRITE_SIZE;
+            transform.translation.x = rng.gen_range(play_area.x.clone());
+        }
+    }
+}
+
+fn update_sky(
+    scoreboard: Res<Scoreboard>,
+    settings: Res<Settings>,
+    mut clear_color: ResMut<ClearColor>,
+    mut query: Query<(&Scroll, &mut Sprite)>,
+) {
+    let (sky, night) = sky_at(scoreboard.score, &settings.palette.colors().sky);
+    clear_color.0 = sky;
+
+    let visibility = DAY_STAR_VISIBILITY + (1.0 - DAY_STAR_VISIBILITY) * night;
+    for (scroll, mut sprite) in query.iter_mut() {
+        sprite.color.set_alpha(scroll.alpha * visibility);
+    }
+}
+
+fn reset_sky(settings: Res<Settings>, mut clear_color: ResMut<ClearColor>) {
+    clear_color.0 = settings.palette.colors().background;
+}
+
+/// Returns the sky color after `seconds` of survival along with how far
+/// through the whole gradient that is, from 0.0 (day) to 1.0 (night).
+fn sky_at(seconds: f32, sky: &[Color; 4]) -> (Color, f32) {
+    let last = SKY_TIMES.len() - 1;
+    let night = (seconds / SKY_TIMES[last]).clamp(0.0, 1.0);
+
+    for i in 0..last {
+        let (start, end) = (SKY_TIMES[i], SKY_TIMES[i + 1]);
+        if seconds < end {
+            let t = ((seconds - start) / (end - start)).clamp(0.0, 1.0);
+            return (lerp_color(sky[i], sky[i + 1], t), night);
+        }
+    }
+
+    (sky[last], night)
+}
+
+fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let (from, to) = (from.to_srgba(), to.to_srgba());
+    Color::srgb(
+        from.red + (to.red - from.red) * t,
+        from.green + (to.green - from.green) * t,
+        from.blue + (to.blue - from.blue) * t,
+    )
+}
@@ -0,0 +1,122 @@
+//! Plays a run without a window and prints its score, so runs can be checked
+//! in CI or on a server.
+//!
+//! Usage: `headless <seed> [script]`. Each line of the script is
+//! `<frame> <press|release> <key>`, where the key is `left`, `right`, `space`
+//! or `escape`. Frames count from the end of loading, and the run is started
+//! on frame 0 automatically.
+//!
+//! `headless --replay <file>` plays back a replay saved from the game over
+//! screen instead, and prints the score the run ends with.
+
+use std::{env, fs, process};
+
+use bevy::prelude::*;
+use dodger::{
+    headless::{self, InputScript, ScriptedInput},
+    replay::Replay,
+    score::Scoreboard,
+    states::GameState,
+};
+
+/// Ten minutes of frames, after which the run is cut short.
+const MAX_FRAMES: u32 = 60 * 60 * 10;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.get(1).map(String::as_str) == Some("--replay") {
+        let path = args
+            .get(2)
+            .unwrap_or_else(|| exit("usage: headless --replay <file>"));
+        let replay: Replay = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| ron::from_str(&contents).map_err(|err| err.to_string()))
+            .unwrap_or_else(|err| exit(&format!("failed to read {}: {}", path, err)));
+
+        let seed = replay.seed;
+        run(headless::build_replay_app(replay), seed);
+        return;
+    }
+
+    let seed = match args.get(1).map(|seed| seed.parse()) {
+        Some(Ok(seed)) => seed,
+        _ => exit("usage: headless <seed> [script] | headless --replay <file>"),
+    };
+
+    let mut steps = vec![
+        ScriptedInput {
+            frame: 0,
+            key: KeyCode::Space,
+            pressed: true,
+        },
+        ScriptedInput {
+            frame: 1,
+            key: KeyCode::Space,
+            pressed: false,
+        },
+    ];
+
+    if let Some(path) = args.get(2) {
+        let contents = fs::read_to_string(path)
+            .unwrap_or_else(|err| exit(&format!("failed to read {}: {}", path, err)));
+
+        for (number, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match parse_step(line) {
+                Some(step) => steps.push(step),
+                None => exit(&format!("{}:{}: invalid step '{}'", path, number + 1, line)),
+            }
+        }
+    }
+
+    run(headless::build_app(seed, InputScript::new(steps)), seed);
+}
+
+fn run(mut app: App, seed: u64) {
+    let mut frames = 0;
+
+    while frames < MAX_FRAMES {
+        app.update();
+        frames += 1;
+
+        let state = app.world().get_resource::<State<GameState>>().unwrap();
+        if state.get() == &GameState::GameOver {
+            break;
+        }
+    }
+
+    let score = app.world().get_resource::<Scoreboard>().unwrap().score;
+    println!("seed {} frames {} score {}", seed, frames, score as i16);
+}
+
+fn parse_step(line: &str) -> Option<ScriptedInput> {
+    let mut parts = line.split_whitespace();
+    let frame = parts.next()?.parse().ok()?;
+    let pressed = match parts.next()? {
+        "press" => true,
+        "release" => false,
+        _ => return None,
+    };
+    let key = match parts.next()? {
+        "left" => KeyCode::ArrowLeft,
+        "right" => KeyCode::ArrowRight,
+        "space" => KeyCode::Space,
+        "escape" => KeyCode::Escape,
+        _ => return None,
+    };
+
+    Some(ScriptedInput {
+        frame,
+        key,
+        pressed,
+    })
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
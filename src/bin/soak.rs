@@ -0,0 +1,328 @@
+//! Plays runs back to back without a window for a long stretch, to catch
+//! leaks and slowdowns that only show up after many runs.
+//!
+//! Usage: `soak [minutes] [seed]`, ten minutes of simulated play by default.
+//! Enemies spawn as fast as the spawner allows, one per step, and a bot
+//! steers the player away from whatever is falling towards it. Each run is
+//! started again as soon as the last one is over.
+//!
+//! Every simulated minute a line is printed with the entity count, the heap
+//! in use and how long each schedule took. Entities and heap are also sampled
+//! on every game over screen, where a run has been cleaned up; the soak
+//! fails if entities are left behind from one run to the next.
+//!
+//! Timings are per schedule, not per system. For a breakdown by system, build
+//! with `--features bevy/trace_chrome` and open the trace it writes.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cmp, env, process,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    app::MainScheduleOrder, ecs::schedule::InternedScheduleLabel, input::InputSystem, prelude::*,
+};
+use dodger::{
+    bounds::PlayArea,
+    config::GameConfig,
+    enemy::Collider,
+    headless::{self, InputScript},
+    movement::FIXED_STEP,
+    player::Player,
+    save::{MemoryBackend, Save},
+    states::{self, GameState},
+    SPRITE_SIZE,
+};
+
+const DEFAULT_MINUTES: u32 = 10;
+const DEFAULT_SEED: u64 = 1;
+const FRAMES_PER_MINUTE: u32 = 60 * 60;
+/// Long enough for loading to finish, which takes a varying number of frames.
+const MAX_LOADING_FRAMES: u32 = 600;
+/// How far above the player the bot looks for enemies to get out from under.
+const LOOKAHEAD: f32 = 160.0;
+
+/// Counts the bytes the program has allocated and not freed yet, which is
+/// the heap in use whatever the platform.
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+            ALLOCATED.fetch_add(new_size, Ordering::Relaxed);
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[derive(Default)]
+struct ScheduleTiming {
+    total: Duration,
+    max: Duration,
+    runs: u32,
+}
+
+/// Time spent in each of the main schedules since the last report, in the
+/// order they run.
+#[derive(Resource, Default)]
+struct ScheduleTimings(Vec<(InternedScheduleLabel, ScheduleTiming)>);
+
+/// Taken on each game over screen, once the run before it is cleaned up.
+#[derive(Clone, Copy)]
+struct Sample {
+    entities: u32,
+    heap: usize,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let usage = "usage: soak [minutes] [seed]";
+    let minutes = match args.get(1).map(|minutes| minutes.parse()) {
+        Some(Ok(minutes)) => minutes,
+        Some(Err(_)) => exit(usage),
+        None => DEFAULT_MINUTES,
+    };
+    let seed = match args.get(2).map(|seed| seed.parse()) {
+        Some(Ok(seed)) => seed,
+        Some(Err(_)) => exit(usage),
+        None => DEFAULT_SEED,
+    };
+
+    let mut app = headless::build_app_with_save(
+        seed,
+        InputScript::default(),
+        Save(Box::new(MemoryBackend::default())),
+    );
+    app.world_mut()
+        .get_resource_mut::<GameConfig>()
+        .unwrap()
+        .spawn_interval = FIXED_STEP;
+    app.init_resource::<ScheduleTimings>()
+        .add_systems(PreUpdate, bot.after(InputSystem));
+    let mut main = Schedule::new(Main);
+    main.add_systems(run_main_timed);
+    app.add_schedule(main);
+
+    for _ in 0..MAX_LOADING_FRAMES {
+        if state(&app) != GameState::Loading {
+            break;
+        }
+        app.update();
+    }
+    if state(&app) == GameState::Loading {
+        exit("loading didn't finish");
+    }
+    take_timings(&mut app);
+
+    let started = Instant::now();
+    let mut samples = Vec::new();
+    let mut runs = 0;
+    let mut was_over = false;
+    for frame in 1..=minutes * FRAMES_PER_MINUTE {
+        app.update();
+
+        let over = state(&app) == GameState::GameOver;
+        if over && !was_over {
+            runs += 1;
+            samples.push(Sample {
+                entities: app.world().entities().len(),
+                heap: ALLOCATED.load(Ordering::Relaxed),
+            });
+        }
+        was_over = over;
+
+        if frame % FRAMES_PER_MINUTE == 0 {
+            report(&mut app, frame / FRAMES_PER_MINUTE, runs, started.elapsed());
+        }
+    }
+
+    let (first, last) = match (samples.first(), samples.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => exit("no run ended, the soak didn't test anything"),
+    };
+    let max_entities = samples.iter().map(|sample| sample.entities).max().unwrap();
+    println!(
+        "{} runs in {} minutes, seed {}: entities {} -> {} (max {}), heap {} -> {} ({:+.1} KiB)",
+        runs,
+        minutes,
+        seed,
+        first.entities,
+        last.entities,
+        max_entities,
+        kib(first.heap),
+        kib(last.heap),
+        (last.heap as f64 - first.heap as f64) / 1024.0,
+    );
+
+    if samples
+        .iter()
+        .any(|sample| sample.entities != first.entities)
+    {
+        exit("entities were left behind between runs");
+    }
+}
+
+/// Moves away from the nearest enemy above the player that it would hit,
+/// and back to the middle when nothing is coming. Starts the next run as
+/// soon as one is over, skipping initials entry.
+fn bot(
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    play_area: Res<PlayArea>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    players: Query<&Transform, With<Player>>,
+    enemies: Query<(&Transform, &Visibility), With<Collider>>,
+) {
+    if matches!(state.get(), GameState::Title | GameState::GameOver) {
+        // Fails if a change is already queued this frame, so try again next
+        // frame.
+        states::try_change(&mut next_state, GameState::Playing);
+        return;
+    }
+
+    let player = match players.iter().next() {
+        Some(transform) => transform.translation,
+        None => return,
+    };
+    let threat = enemies
+        .iter()
+        .filter(|(_, visibility)| **visibility != Visibility::Hidden)
+        .map(|(transform, _)| (transform.translation, transform.scale.x))
+        .filter(|(enemy, scale)| {
+            let above = enemy.y - player.y;
+            let reach = SPRITE_SIZE * (1.0 + scale) / 2.0;
+            above > 0.0 && above < LOOKAHEAD && (enemy.x - player.x).abs() < reach
+        })
+        .min_by(|(a, _), (b, _)| a.y.partial_cmp(&b.y).unwrap_or(cmp::Ordering::Equal));
+
+    let margin = SPRITE_SIZE * 2.0;
+    let middle = (play_area.x.start + play_area.x.end) / 2.0;
+    let go_left = match threat {
+        Some(_) if player.x < play_area.x.start + margin => false,
+        Some(_) if player.x > play_area.x.end - margin => true,
+        Some((enemy, _)) => enemy.x >= player.x,
+        None if (player.x - middle).abs() < SPRITE_SIZE => {
+            keyboard.release(KeyCode::ArrowLeft);
+            keyboard.release(KeyCode::ArrowRight);
+            return;
+        }
+        None => player.x > middle,
+    };
+    let (press, release) = if go_left {
+        (KeyCode::ArrowLeft, KeyCode::ArrowRight)
+    } else {
+        (KeyCode::ArrowRight, KeyCode::ArrowLeft)
+    };
+    keyboard.release(release);
+    keyboard.press(press);
+}
+
+/// Stands in for [`Main::run_main`], timing each schedule it runs, commands
+/// applied at its end included. The fixed steps are timed together, as
+/// `RunFixedMainLoop`.
+fn run_main_timed(world: &mut World, mut started_up: Local<bool>) {
+    world.resource_scope(|world, order: Mut<MainScheduleOrder>| {
+        if !*started_up {
+            for &label in &order.startup_labels {
+                let _ = world.try_run_schedule(label);
+            }
+            *started_up = true;
+        }
+
+        for &label in &order.labels {
+            let started = Instant::now();
+            let _ = world.try_run_schedule(label);
+            let elapsed = started.elapsed();
+
+            let mut timings = world.resource_mut::<ScheduleTimings>();
+            let index = match timings.0.iter().position(|(timed, _)| *timed == label) {
+                Some(index) => index,
+                None => {
+                    timings.0.push((label, ScheduleTiming::default()));
+                    timings.0.len() - 1
+                }
+            };
+            let timing = &mut timings.0[index].1;
+            timing.total += elapsed;
+            timing.max = timing.max.max(elapsed);
+            timing.runs += 1;
+        }
+    });
+}
+
+fn take_timings(app: &mut App) -> ScheduleTimings {
+    std::mem::take(&mut *app.world_mut().resource_mut::<ScheduleTimings>())
+}
+
+fn report(app: &mut App, minute: u32, runs: u32, wall: Duration) {
+    let enemies = app
+        .world_mut()
+        .query_filtered::<&Visibility, With<Collider>>()
+        .iter(app.world())
+        .filter(|visibility| **visibility != Visibility::Hidden)
+        .count();
+    let timings = take_timings(app);
+    let schedules = timings
+        .0
+        .iter()
+        .map(|(label, timing)| {
+            format!(
+                "{:?} {:.3}/{:.3}ms",
+                label,
+                timing.total.as_secs_f64() * 1000.0 / timing.runs as f64,
+                timing.max.as_secs_f64() * 1000.0,
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    println!(
+        "minute {} ({:.1}s): {} runs, {} entities, {} enemies, heap {} KiB; avg/max {}",
+        minute,
+        wall.as_secs_f64(),
+        runs,
+        app.world().entities().len(),
+        enemies,
+        kib(ALLOCATED.load(Ordering::Relaxed)),
+        schedules,
+    );
+}
+
+fn kib(bytes: usize) -> usize {
+    bytes / 1024
+}
+
+fn state(app: &App) -> GameState {
+    app.world()
+        .get_resource::<State<GameState>>()
+        .unwrap()
+        .get()
+        .clone()
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
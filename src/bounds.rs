@@ -0,0 +1,59 @@
+use std::ops::Range;
+
+use bevy::prelude::*;
+
+use crate::{config::GameConfig, states::RunActive};
+
+pub struct BoundsPlugin;
+
+impl Plugin for BoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_play_area);
+
+        app.add_systems(Update, despawn_out_of_bounds.run_if(in_state(RunActive)));
+    }
+}
+
+/// The part of the world gameplay happens in: where enemies spawn, where the
+/// player stands and what counts as off screen. Starts out as the configured
+/// screen ranges, and the camera keeps it matched to the window after that.
+#[derive(Resource, Clone, Debug)]
+pub struct PlayArea {
+    pub x: Range<f32>,
+    pub y: Range<f32>,
+}
+
+/// Runs after `PreStartup`, so a portrait launch has already swapped the
+/// configured ranges.
+fn setup_play_area(mut commands: Commands, config: Res<GameConfig>) {
+    commands.insert_resource(PlayArea {
+        x: config.screen_x.clone(),
+        y: config.screen_y.clone(),
+    });
+}
+
+/// Despawns the entity once it is more than `margin` pixels outside the play
+/// area. Hidden entities are left alone, since pooled ones are parked off
+/// screen on purpose.
+#[derive(Component)]
+pub struct DespawnOutOfBounds {
+    pub margin: f32,
+}
+
+fn despawn_out_of_bounds(
+    mut commands: Commands,
+    play_area: Res<PlayArea>,
+    query: Query<(Entity, &Transform, &Visibility, &DespawnOutOfBounds)>,
+) {
+    for (entity, transform, visibility, bounds) in query.iter() {
+        let position = transform.translation;
+        let outside = position.x < play_area.x.start - bounds.margin
+            || position.x > play_area.x.end + bounds.margin
+            || position.y < play_area.y.start - bounds.margin
+            || position.y > play_area.y.end + bounds.margin;
+
+        if *visibility != Visibility::Hidden && outside {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
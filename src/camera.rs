@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+
+use crate::{
+    bounds::PlayArea,
+    player::{DeathTimer, Player},
+    shake::ScreenShake,
+    states::{GameState, InRun},
+};
+
+const DEATH_ZOOM_SECONDS: f32 = 0.5;
+const DEATH_ZOOM_SCALE: f32 = 0.5;
+/// Keeps the player and spawns clear of the top and bottom edges.
+const PLAY_AREA_INSET_Y: f32 = 20.0;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_cameras)
+            .add_systems(Update, fit_play_area)
+            .add_systems(Update, zoom_on_death.run_if(in_state(GameState::Dying)))
+            .add_systems(OnExit(InRun), reset_camera);
+    }
+}
+
+/// The point the gameplay camera rests on. Effects such as screen shake are
+/// applied as offsets from it rather than overwriting the camera position.
+/// The same camera draws the UI, which ignores both.
+#[derive(Component, Default)]
+pub struct CameraFocus(pub Vec2);
+
+fn spawn_cameras(mut commands: Commands) {
+    commands
+        .spawn(Camera2dBundle::default())
+        .insert(CameraFocus::default())
+        .insert(ScreenShake::default());
+}
+
+/// The camera shows one unit per logical pixel around its center, and is
+/// updated whenever the window is resized, so the play area follows it. The
+/// zoom scale is left out on purpose: zooming in on a death shouldn't shrink
+/// the world.
+fn fit_play_area(
+    query: Query<&Camera, (With<CameraFocus>, Changed<Camera>)>,
+    mut play_area: ResMut<PlayArea>,
+) {
+    for camera in query.iter() {
+        let half = match camera.logical_viewport_size() {
+            Some(size) => size / 2.0,
+            None => continue,
+        };
+        let x = -half.x..half.x;
+        let y = -half.y + PLAY_AREA_INSET_Y..half.y - PLAY_AREA_INSET_Y;
+
+        if play_area.x != x || play_area.y != y {
+            play_area.x = x;
+            play_area.y = y;
+        }
+    }
+}
+
+fn zoom_on_death(
+    death_timer: Option<Res<DeathTimer>>,
+    player_query: Query<&Transform, With<Player>>,
+    mut camera_query: Query<(&mut CameraFocus, &mut OrthographicProjection)>,
+) {
+    let death_timer = match death_timer {
+        Some(death_timer) => death_timer,
+        None => return,
+    };
+
+    let target = match player_query.get_single() {
+        Ok(transform) => transform.translation.truncate(),
+        Err(_) => return,
+    };
+
+    let t = (death_timer.0.elapsed_secs() / DEATH_ZOOM_SECONDS).min(1.0);
+    let eased = t * t * (3.0 - 2.0 * t);
+
+    for (mut focus, mut projection) in camera_query.iter_mut() {
+        focus.0 = target * eased;
+        projection.scale = 1.0 + (DEATH_ZOOM_SCALE - 1.0) * eased;
+    }
+}
+
+/// Undoes the death zoom and any leftover shake once a run is over.
+fn reset_camera(
+    mut query: Query<(
+        &mut CameraFocus,
+        &mut ScreenShake,
+        &mut OrthographicProjection,
+        &mut Transform,
+    )>,
+) {
+    for (mut focus, mut shake, mut projection, mut transform) in query.iter_mut() {
+        focus.0 = Vec2::ZERO;
+        *shake = ScreenShake::default();
+        projection.scale = 1.0;
+        transform.translation.x = 0.0;
+        transform.translation.y = 0.0;
+        transform.rotation = Quat::IDENTITY;
+    }
+}
@@ -0,0 +1,38 @@
+//! Collision math, kept apart from Bevy so it can be checked on its own.
+//! Positions and sizes are plain `[f32; 2]`s, which `Vec2` converts to and
+//! from with `into()`.
+
+/// An axis-aligned box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+impl Aabb {
+    pub fn new(center: [f32; 2], size: [f32; 2]) -> Self {
+        let [x, y] = center;
+        let [half_width, half_height] = [size[0] / 2.0, size[1] / 2.0];
+        Self {
+            min: [x - half_width, y - half_height],
+            max: [x + half_width, y + half_height],
+        }
+    }
+
+    /// Whether the boxes share some area. Boxes that only touch along an
+    /// edge don't overlap, the same as Bevy's `collide_aabb`.
+    pub fn overlaps(&self, other: &Aabb) -> bool {
+        self.min[0] < other.max[0]
+            && self.max[0] > other.min[0]
+            && self.min[1] < other.max[1]
+            && self.max[1] > other.min[1]
+    }
+
+    /// Whether `other` lies wholly inside this box, edges included.
+    pub fn contains(&self, other: &Aabb) -> bool {
+        self.min[0] <= other.min[0]
+            && self.max[0] >= other.max[0]
+            && self.min[1] <= other.min[1]
+            && self.max[1] >= other.max[1]
+    }
+}
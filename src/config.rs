@@ -0,0 +1,179 @@
+use std::ops::Range;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+
+const CONFIG_PATH: &str = "assets/config.ron";
+/// The same file, relative to the asset folder.
+const CONFIG_ASSET_PATH: &str = "config.ron";
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameConfig::load())
+            .init_asset::<GameConfig>()
+            .init_asset_loader::<GameConfigLoader>()
+            .add_systems(Startup, watch_config)
+            .add_systems(Update, reload_config);
+    }
+}
+
+/// Gameplay tuning read from `assets/config.ron` at startup. Any field left
+/// out of the file keeps its default value. Browsers have no file system to
+/// read it from, so web builds embed the file instead.
+///
+/// Desktop builds also watch the file, and apply it again whenever it is
+/// saved, see [`reload_config`].
+#[derive(Resource, Clone, Debug, Deserialize, Asset, TypePath)]
+#[serde(default)]
+pub struct GameConfig {
+    pub screen_x: Range<f32>,
+    pub screen_y: Range<f32>,
+    pub player_speed: f32,
+    pub trail_speed: f32,
+    pub spawn_interval: f32,
+    /// Base URL of the online leaderboard server. Without one, scores stay
+    /// on this machine.
+    pub leaderboard_url: Option<String>,
+    /// Base URL of the server anonymous run reports go to, for players who
+    /// opt in. Without one, nothing is sent.
+    pub telemetry_url: Option<String>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            screen_x: -320.0..320.0,
+            screen_y: -220.0..220.0,
+            player_speed: 100.0,
+            trail_speed: 100.0,
+            spawn_interval: 1.0,
+            leaderboard_url: None,
+            telemetry_url: None,
+        }
+    }
+}
+
+impl GameConfig {
+    fn load() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let contents = std::fs::read_to_string(CONFIG_PATH);
+        #[cfg(target_arch = "wasm32")]
+        let contents: std::io::Result<String> =
+            Ok(include_str!("../assets/config.ron").to_string());
+
+        let result = contents
+            .map_err(|err| err.to_string())
+            .and_then(|contents| ron::from_str(&contents).map_err(|err| err.to_string()))
+            .and_then(|config: Self| config.check().map(|()| config));
+
+        result.unwrap_or_else(|err| {
+            warn!("failed to load {}, using defaults: {}", CONFIG_PATH, err);
+            Self::default()
+        })
+    }
+
+    /// Catches values the game would panic on or get stuck with, like a
+    /// spawn interval of zero.
+    fn check(&self) -> Result<(), String> {
+        for (field, range) in [("screen_x", &self.screen_x), ("screen_y", &self.screen_y)] {
+            if !range.start.is_finite() || !range.end.is_finite() || range.is_empty() {
+                return Err(format!(
+                    "{} has to be finite with its end past its start",
+                    field
+                ));
+            }
+        }
+        if !self.spawn_interval.is_finite() || self.spawn_interval <= 0.0 {
+            return Err(format!(
+                "spawn_interval is {}, it has to be more than 0",
+                self.spawn_interval
+            ));
+        }
+        for (field, speed) in [
+            ("player_speed", self.player_speed),
+            ("trail_speed", self.trail_speed),
+        ] {
+            if !speed.is_finite() || speed < 0.0 {
+                return Err(format!("{} is {}, it can't be negative", field, speed));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Loads `config.ron` as an asset, only so the asset server watches it. It
+/// is the only plain `.ron` file in the assets; the others have a second
+/// extension of their own, such as `.enemies.ron`.
+#[derive(Default)]
+pub struct GameConfigLoader;
+
+impl AssetLoader for GameConfigLoader {
+    type Asset = GameConfig;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<GameConfig, anyhow::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let config: GameConfig = ron::de::from_bytes(&bytes)?;
+        Ok(config)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+#[derive(Resource)]
+struct ConfigHandle(Handle<GameConfig>);
+
+fn watch_config(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ConfigHandle(asset_server.load(CONFIG_ASSET_PATH)));
+}
+
+/// Applies `config.ron` again when it changes on disk. Speeds take effect
+/// straight away and the spawn interval from the next spawn. The screen
+/// ranges and the server URLs are only read at startup, so they are kept
+/// as they are. A file that doesn't parse is logged by the asset server, and
+/// one with values the game can't use is logged here; either changes nothing.
+///
+/// The first load is ignored: the same file was already read at startup.
+fn reload_config(
+    mut ev_asset: EventReader<AssetEvent<GameConfig>>,
+    handle: Res<ConfigHandle>,
+    assets: Res<Assets<GameConfig>>,
+    mut config: ResMut<GameConfig>,
+) {
+    for event in ev_asset.read() {
+        if !event.is_modified(&handle.0) {
+            continue;
+        }
+        let reloaded = match assets.get(&handle.0) {
+            Some(reloaded) => reloaded,
+            None => continue,
+        };
+        if let Err(err) = reloaded.check() {
+            warn!("kept the previous {}: {}", CONFIG_PATH, err);
+            continue;
+        }
+
+        info!("reloaded {}", CONFIG_PATH);
+        *config = GameConfig {
+            screen_x: config.screen_x.clone(),
+            screen_y: config.screen_y.clone(),
+            leaderboard_url: config.leaderboard_url.clone(),
+            telemetry_url: config.telemetry_url.clone(),
+            ..reloaded.clone()
+        };
+    }
+}
@@ -0,0 +1,260 @@
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        InputSystem,
+    },
+    prelude::*,
+    utils::HashMap,
+};
+
+use crate::{score, ui::TextFont, GameSystem};
+
+const CONSOLE_FONT_SIZE: f32 = 12.0;
+const CONSOLE_HISTORY: usize = 8;
+/// How many lines are kept for scrolling back through.
+const MAX_HISTORY: usize = 200;
+const CONSOLE_KEY: char = '`';
+
+/// Runs a console command with the words typed after its name. The returned
+/// text, or the error, is printed to the console.
+pub type ConsoleCommand = fn(&mut World, &[&str]) -> Result<String, String>;
+
+/// Every command the console understands, keyed by its name, and whether it
+/// changes the run.
+#[derive(Resource, Default)]
+pub struct ConsoleCommands(HashMap<String, (ConsoleCommand, bool)>);
+
+/// Lets any plugin add commands to the console.
+pub trait AddConsoleCommand {
+    fn add_console_command(&mut self, name: &str, command: ConsoleCommand) -> &mut Self;
+
+    /// Adds a command that changes how the run in progress plays out, like
+    /// spawning enemies. Once one succeeds, the run is unranked and earns
+    /// no coins, see [`score::tamper`].
+    fn add_cheat_command(&mut self, name: &str, command: ConsoleCommand) -> &mut Self;
+}
+
+impl AddConsoleCommand for App {
+    fn add_console_command(&mut self, name: &str, command: ConsoleCommand) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(ConsoleCommands::default)
+            .0
+            .insert(name.to_string(), (command, false));
+        self
+    }
+
+    fn add_cheat_command(&mut self, name: &str, command: ConsoleCommand) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(ConsoleCommands::default)
+            .0
+            .insert(name.to_string(), (command, true));
+        self
+    }
+}
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_console_command("help", help)
+            // The font is inserted with commands during startup.
+            .add_systems(PostStartup, spawn_console)
+            .add_systems(
+                PreUpdate,
+                toggle_console
+                    .in_set(GameSystem::TextInput)
+                    .after(InputSystem),
+            )
+            .add_systems(
+                PreUpdate,
+                edit_console_line
+                    .in_set(GameSystem::TextInput)
+                    .after(toggle_console),
+            )
+            .add_systems(PostUpdate, run_console_commands)
+            .add_systems(Update, update_console_text);
+    }
+}
+
+/// The drop-down console, opened with the backtick key.
+#[derive(Resource, Default)]
+struct Console {
+    open: bool,
+    line: String,
+    history: Vec<String>,
+    submitted: Vec<String>,
+}
+
+#[derive(Component)]
+struct ConsolePanel;
+
+fn spawn_console(mut commands: Commands, font: Res<TextFont>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Auto,
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                display: Display::None,
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+            ..default()
+        })
+        .insert(ConsolePanel)
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: font.0.clone(),
+                        font_size: CONSOLE_FONT_SIZE,
+                        color: Color::WHITE,
+                    },
+                ),
+                ..default()
+            });
+        });
+}
+
+/// Takes every held key away from the systems that run after it, except
+/// `keep`, for screens the player types text into. Without it, typing a
+/// word with an M in it toggles mute. Typed text still arrives as
+/// [`KeyboardInput`] events.
+pub fn take_typed_keys(keyboard_input: &mut ButtonInput<KeyCode>, keep: &[KeyCode]) {
+    let keys: Vec<KeyCode> = keyboard_input
+        .get_pressed()
+        .filter(|key| !keep.contains(key))
+        .copied()
+        .collect();
+    for key in keys {
+        keyboard_input.reset(key);
+    }
+}
+
+fn toggle_console(keyboard_input: Res<ButtonInput<KeyCode>>, mut console: ResMut<Console>) {
+    if keyboard_input.just_pressed(KeyCode::Backquote) {
+        console.open = !console.open;
+    }
+}
+
+/// Everything typed while the console is open goes to the console alone.
+fn edit_console_line(
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut ev_keyboard: EventReader<KeyboardInput>,
+    mut console: ResMut<Console>,
+) {
+    let typed: String = ev_keyboard
+        .read()
+        .filter(|event| event.state.is_pressed())
+        .filter_map(|event| match &event.logical_key {
+            Key::Character(text) => Some(text.as_str()),
+            Key::Space => Some(" "),
+            _ => None,
+        })
+        .flat_map(str::chars)
+        .filter(|&char| char != CONSOLE_KEY && !char.is_control())
+        .collect();
+
+    if !console.open {
+        return;
+    }
+
+    console.line.push_str(&typed);
+
+    if keyboard_input.just_pressed(KeyCode::Backspace) {
+        console.line.pop();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        let line = std::mem::take(&mut console.line);
+        if !line.trim().is_empty() {
+            console.submitted.push(line);
+        }
+    }
+
+    take_typed_keys(&mut keyboard_input, &[]);
+}
+
+fn run_console_commands(world: &mut World) {
+    let submitted = match world.get_resource_mut::<Console>() {
+        Some(mut console) => std::mem::take(&mut console.submitted),
+        None => return,
+    };
+
+    for line in submitted {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (name, args) = match words.split_first() {
+            Some(split) => split,
+            None => continue,
+        };
+
+        let command = world
+            .get_resource::<ConsoleCommands>()
+            .and_then(|commands| commands.0.get(*name).copied());
+        let output = match command {
+            Some((command, cheat)) => {
+                let output = command(world, args);
+                if cheat && output.is_ok() {
+                    score::tamper(world);
+                }
+                output
+            }
+            None => Err(format!("unknown command '{}'", name)),
+        };
+
+        let mut console = world.get_resource_mut::<Console>().unwrap();
+        console.history.push(format!("> {}", line));
+        match output {
+            Ok(message) if message.is_empty() => {}
+            Ok(message) => console.history.push(message),
+            Err(err) => console.history.push(format!("error: {}", err)),
+        }
+        let excess = console.history.len().saturating_sub(MAX_HISTORY);
+        console.history.drain(..excess);
+    }
+}
+
+fn update_console_text(
+    console: Res<Console>,
+    mut panels: Query<(&mut Style, &Children), With<ConsolePanel>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+
+    for (mut style, children) in panels.iter_mut() {
+        style.display = if console.open {
+            Display::Flex
+        } else {
+            Display::None
+        };
+
+        let start = console.history.len().saturating_sub(CONSOLE_HISTORY);
+        let mut value: String = console.history[start..]
+            .iter()
+            .map(|line| format!("{}\n", line))
+            .collect();
+        value.push_str(&format!("> {}_", console.line));
+
+        for &child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(child) {
+                text.sections[0].value = value.clone();
+            }
+        }
+    }
+}
+
+fn help(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let mut names: Vec<&str> = world
+        .get_resource::<ConsoleCommands>()
+        .map(|commands| commands.0.keys().map(String::as_str).collect())
+        .unwrap_or_default();
+    names.sort_unstable();
+
+    Ok(names.join(" "))
+}
@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Extra bindings for a solo player, so the whole game can be played with
+/// one hand or with just the mouse. The arrow keys, Space and Escape keep
+/// working under every preset.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ControlPreset {
+    #[default]
+    Standard,
+    /// A and D to move, next to Space and Escape.
+    LeftHand,
+    /// Arrows to move, Enter to start and Backspace to pause.
+    RightHand,
+    /// The left and right buttons move, either one starts and the middle
+    /// button pauses.
+    Mouse,
+}
+
+impl ControlPreset {
+    /// The message id of its name.
+    pub fn name_id(&self) -> &'static str {
+        match self {
+            ControlPreset::Standard => "controls-standard",
+            ControlPreset::LeftHand => "controls-left-hand",
+            ControlPreset::RightHand => "controls-right-hand",
+            ControlPreset::Mouse => "controls-mouse",
+        }
+    }
+
+    pub fn next(&self) -> ControlPreset {
+        match self {
+            ControlPreset::Standard => ControlPreset::LeftHand,
+            ControlPreset::LeftHand => ControlPreset::RightHand,
+            ControlPreset::RightHand => ControlPreset::Mouse,
+            ControlPreset::Mouse => ControlPreset::Standard,
+        }
+    }
+
+    /// Which way the preset's own bindings are steering: -1, 0 or 1.
+    pub fn direction(
+        &self,
+        keyboard: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+    ) -> f32 {
+        let (left, right) = match self {
+            ControlPreset::Standard | ControlPreset::RightHand => return 0.0,
+            ControlPreset::LeftHand => (
+                keyboard.pressed(KeyCode::KeyA),
+                keyboard.pressed(KeyCode::KeyD),
+            ),
+            ControlPreset::Mouse => (
+                mouse.pressed(MouseButton::Left),
+                mouse.pressed(MouseButton::Right),
+            ),
+        };
+
+        right as i32 as f32 - left as i32 as f32
+    }
+
+    pub fn start_pressed(
+        &self,
+        keyboard: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+    ) -> bool {
+        match self {
+            ControlPreset::Standard | ControlPreset::LeftHand => false,
+            ControlPreset::RightHand => keyboard.just_pressed(KeyCode::Enter),
+            ControlPreset::Mouse => mouse.any_just_pressed([MouseButton::Left, MouseButton::Right]),
+        }
+    }
+
+    pub fn pause_pressed(
+        &self,
+        keyboard: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+    ) -> bool {
+        match self {
+            ControlPreset::Standard | ControlPreset::LeftHand => false,
+            ControlPreset::RightHand => keyboard.just_pressed(KeyCode::Backspace),
+            ControlPreset::Mouse => mouse.just_pressed(MouseButton::Middle),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+use crate::settings::Settings;
+
+const OVERLAY_WIDTH: u32 = 640;
+const OVERLAY_HEIGHT: u32 = 480;
+const SCANLINE_ALPHA: f32 = 0.25;
+const VIGNETTE_ALPHA: f32 = 0.6;
+const CORNER_RADIUS: f32 = 24.0;
+
+pub struct CrtPlugin;
+
+impl Plugin for CrtPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, create_overlay)
+            .add_systems(Update, update_crt_overlay);
+    }
+}
+
+#[derive(Component)]
+struct CrtOverlay;
+
+#[derive(Resource)]
+struct CrtTexture(Handle<Image>);
+
+/// Bakes scanlines, a vignette and rounded screen corners into a single
+/// texture that is laid over the whole window, standing in for a
+/// post-processing pass.
+fn create_overlay(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let (width, height) = (OVERLAY_WIDTH as f32, OVERLAY_HEIGHT as f32);
+    let mut data = Vec::with_capacity((OVERLAY_WIDTH * OVERLAY_HEIGHT * 4) as usize);
+
+    for y in 0..OVERLAY_HEIGHT {
+        for x in 0..OVERLAY_WIDTH {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+
+            let scanline = if y % 2 == 1 { SCANLINE_ALPHA } else { 0.0 };
+
+            let u = px / width * 2.0 - 1.0;
+            let v = py / height * 2.0 - 1.0;
+            let vignette = VIGNETTE_ALPHA * ((u * u + v * v) / 2.0).powf(1.5);
+
+            let dx = (CORNER_RADIUS - px.min(width - px)).max(0.0);
+            let dy = (CORNER_RADIUS - py.min(height - py)).max(0.0);
+            let corner = ((dx * dx + dy * dy).sqrt() - CORNER_RADIUS + 1.0).clamp(0.0, 1.0);
+
+            let alpha = 1.0 - (1.0 - scanline) * (1.0 - vignette) * (1.0 - corner);
+            data.extend_from_slice(&[0, 0, 0, (alpha.clamp(0.0, 1.0) * 255.0) as u8]);
+        }
+    }
+
+    let image = Image::new(
+        Extent3d {
+            width: OVERLAY_WIDTH,
+            height: OVERLAY_HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+
+    commands.insert_resource(CrtTexture(images.add(image)));
+}
+
+fn update_crt_overlay(
+    mut commands: Commands,
+    texture: Res<CrtTexture>,
+    settings: Res<Settings>,
+    query: Query<Entity, With<CrtOverlay>>,
+) {
+    match (settings.crt, query.iter().next()) {
+        (true, None) => {
+            commands
+                .spawn(ImageBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    },
+                    image: UiImage::new(texture.0.clone()),
+                    ..default()
+                })
+                .insert(CrtOverlay);
+        }
+        (false, Some(overlay)) => commands.entity(overlay).despawn(),
+        _ => {}
+    }
+}
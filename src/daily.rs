@@ -0,0 +1,284 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    history::{self, SECONDS_PER_DAY},
+    locale::Locale,
+    player::PlayerCount,
+    rng::RunSeed,
+    save::Save,
+    score::{Ranked, Scoreboard},
+    settings::Settings,
+    shop::Upgrades,
+    states::{self, GameState, InRun, MenuSelectEvent},
+    ui::TextFont,
+    versus::Versus,
+};
+
+const DAILY_KEY: &str = "daily.ron";
+const DAILY_HISTORY_SIZE: usize = 30;
+const PAST_DAYS_SHOWN: usize = 7;
+const DAILY_FONT_SIZE: f32 = 16.0;
+const DAILY_PADDING: f32 = 16.0;
+
+/// One run a day on a seed everyone shares, opened with D from the title.
+/// The seed comes from the date in UTC, so every player dodges the same
+/// enemies that day. A daily run is always solo, at full speed, with no
+/// assists or upgrades, and only the first attempt of the day counts.
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        let results =
+            DailyResults::load(&app.world_mut().get_resource_or_insert_with(Save::default));
+        app.insert_resource(results)
+            .add_systems(Update, open_daily.run_if(in_state(GameState::Title)))
+            .add_systems(OnEnter(GameState::GameOver), record_daily)
+            .add_systems(OnExit(GameState::GameOver), end_daily)
+            .add_systems(OnExit(InRun), end_daily.run_if(states::run_abandoned))
+            .add_systems(OnEnter(GameState::Daily), spawn_daily)
+            .add_systems(Update, start_daily.run_if(in_state(GameState::Daily)));
+    }
+}
+
+/// Past daily attempts, newest first.
+#[derive(Resource, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DailyResults {
+    pub entries: Vec<DailyEntry>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DailyEntry {
+    /// Days since the Unix epoch.
+    pub day: u64,
+    /// `None` until the attempt ends, and for good if it was unranked.
+    pub score: Option<u32>,
+}
+
+impl DailyResults {
+    pub fn load(save: &Save) -> Self {
+        save.0
+            .load(DAILY_KEY)
+            .ok()
+            .flatten()
+            .and_then(|bytes| ron::de::from_bytes(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, save: &Save) {
+        let result = ron::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                save.0
+                    .store(DAILY_KEY, contents.as_bytes())
+                    .map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result {
+            warn!("failed to save daily results: {}", err);
+        }
+    }
+
+    fn attempted(&self, day: u64) -> bool {
+        self.entries.iter().any(|entry| entry.day == day)
+    }
+}
+
+/// Present while a daily run is being played.
+#[derive(Resource)]
+pub struct DailyRun {
+    day: u64,
+    previous_seed: Option<u64>,
+}
+
+/// Today, in days since the Unix epoch, or `None` without a clock.
+fn today() -> Option<u64> {
+    match history::now() {
+        0 => None,
+        now => Some(now / SECONDS_PER_DAY),
+    }
+}
+
+/// Spreads consecutive days over unrelated seeds with SplitMix64, which must
+/// never change, or players on different versions would get different runs.
+pub fn daily_seed(day: u64) -> u64 {
+    let mut seed = day.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    seed = (seed ^ (seed >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    seed = (seed ^ (seed >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    seed ^ (seed >> 31)
+}
+
+fn open_daily(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyD)
+        && states::try_change(&mut next_state, GameState::Daily)
+    {
+        ev_menu.send(MenuSelectEvent);
+    }
+}
+
+fn spawn_daily(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    upgrades: Res<Upgrades>,
+    results: Res<DailyResults>,
+) {
+    let status = match today() {
+        None => locale.text("daily-no-clock"),
+        Some(day) => match results.entries.iter().find(|entry| entry.day == day) {
+            Some(DailyEntry {
+                score: Some(score), ..
+            }) => locale.format("daily-score", &[("score", (*score).into())]),
+            Some(_) => locale.text("daily-not-counted"),
+            None if !settings.ranked() || !upgrades.active.is_empty() => {
+                locale.text("daily-not-eligible")
+            }
+            None => locale.text("daily-start"),
+        },
+    };
+
+    let mut value = format!(
+        "{}\n\n{}\n\n{}\n\n{}\n",
+        locale.format(
+            "daily-title",
+            &[("day", format_day(today().unwrap_or(0)).into())]
+        ),
+        locale.text("daily-about"),
+        status,
+        locale.text("daily-past-days")
+    );
+    for entry in results.entries.iter().take(PAST_DAYS_SHOWN) {
+        let score = entry
+            .score
+            .map_or_else(|| "-".to_string(), |score| score.to_string());
+        value.push_str(&format!("{}  {}\n", format_day(entry.day), score));
+    }
+    if results.entries.is_empty() {
+        value.push_str(&format!("{}\n", locale.text("daily-none-yet")));
+    }
+    value.push_str(&format!("\n\n{}", locale.text("daily-help")));
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                value,
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: DAILY_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(DAILY_PADDING),
+                left: Val::Px(DAILY_PADDING),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(StateScoped(GameState::Daily));
+}
+
+fn format_day(day: u64) -> String {
+    history::format_date(day * SECONDS_PER_DAY)
+}
+
+/// The attempt is stored as soon as it starts, so quitting doesn't give
+/// another one.
+#[allow(clippy::too_many_arguments)]
+fn start_daily(
+    mut commands: Commands,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    upgrades: Res<Upgrades>,
+    save: Res<Save>,
+    mut results: ResMut<DailyResults>,
+    mut run_seed: ResMut<RunSeed>,
+    mut player_count: ResMut<PlayerCount>,
+    mut versus: ResMut<Versus>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        keyboard_input.reset(KeyCode::Escape);
+        states::try_change(&mut next_state, GameState::Title);
+        return;
+    }
+
+    if !keyboard_input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+    let day = match today() {
+        Some(day) if !results.attempted(day) => day,
+        _ => return,
+    };
+    if !settings.ranked() || !upgrades.active.is_empty() {
+        return;
+    }
+
+    keyboard_input.reset(KeyCode::Enter);
+    if !states::try_change(&mut next_state, GameState::Playing) {
+        return;
+    }
+
+    info!("starting the daily challenge for {}", format_day(day));
+    results.entries.insert(0, DailyEntry { day, score: None });
+    results.entries.truncate(DAILY_HISTORY_SIZE);
+    results.save(&save);
+
+    commands.insert_resource(DailyRun {
+        day,
+        previous_seed: run_seed.0.replace(daily_seed(day)),
+    });
+    player_count.0 = 1;
+    versus.active = false;
+    ev_menu.send(MenuSelectEvent);
+}
+
+fn record_daily(
+    daily: Option<Res<DailyRun>>,
+    scoreboard: Res<Scoreboard>,
+    ranked: Res<Ranked>,
+    save: Res<Save>,
+    mut results: ResMut<DailyResults>,
+) {
+    let daily = match daily {
+        Some(daily) if ranked.0 => daily,
+        _ => return,
+    };
+
+    if let Some(entry) = results
+        .entries
+        .iter_mut()
+        .find(|entry| entry.day == daily.day)
+    {
+        entry.score = Some(scoreboard.score as u32);
+        results.save(&save);
+    }
+}
+
+fn end_daily(mut commands: Commands, daily: Option<Res<DailyRun>>, mut run_seed: ResMut<RunSeed>) {
+    if let Some(daily) = daily {
+        run_seed.0 = daily.previous_seed;
+        commands.remove_resource::<DailyRun>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Everyone's daily run comes from these, so they must never change.
+    /// Day 0 is the first output of the reference SplitMix64.
+    #[test]
+    fn daily_seeds_are_pinned() {
+        assert_eq!(daily_seed(0), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(daily_seed(1), 0x910a_2dec_8902_5cc1);
+        assert_eq!(daily_seed(19_000), 0xd27c_8b8b_04e6_47f1);
+    }
+}
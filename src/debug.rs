@@ -0,0 +1,195 @@
+use bevy::{diagnostic::DiagnosticsStore, prelude::*};
+
+use crate::{
+    bounds::PlayArea,
+    diagnostics::{ENEMY_COUNT, EVENTS_PER_SECOND},
+    enemy::{Collider, SpawnTimer},
+    player::{self, Player},
+    settings::Settings,
+    time_scale::GameSpeed,
+    ui::TextFont,
+    SPRITE_SIZE,
+};
+
+const DEBUG_FONT_SIZE: f32 = 12.0;
+const PLAYER_HITBOX_COLOR: Color = Color::srgb(0.0, 1.0, 0.0);
+const ENEMY_HITBOX_COLOR: Color = Color::srgb(1.0, 0.0, 0.0);
+const SPAWN_ZONE_COLOR: Color = Color::srgb(1.0, 1.0, 0.0);
+const SPAWN_WARNING_COLOR: Color = Color::srgb(1.0, 0.5, 0.0);
+const SPEED_STEP: f32 = 0.25;
+const MAX_SPEED: f32 = 4.0;
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>()
+            .add_systems(Update, toggle_debug_overlay)
+            .add_systems(Update, adjust_game_speed)
+            .add_systems(Update, (draw_hitboxes, draw_spawn_zones))
+            .add_systems(Update, update_debug_text);
+    }
+}
+
+/// Toggled with F4. Draws every hitbox and where enemies spawn, shows entity
+/// counts, and lets `[` and `]` slow down or speed up the game.
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+#[derive(Component)]
+struct DebugText;
+
+fn toggle_debug_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F4) {
+        overlay.enabled = !overlay.enabled;
+    }
+}
+
+fn adjust_game_speed(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    overlay: Res<DebugOverlay>,
+    mut game_speed: ResMut<GameSpeed>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::BracketLeft) {
+        game_speed.0 = (game_speed.0 - SPEED_STEP).max(0.0);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::BracketRight) {
+        game_speed.0 = (game_speed.0 + SPEED_STEP).min(MAX_SPEED);
+    }
+}
+
+/// Hitboxes are axis aligned, like the collision test, so they ignore the
+/// sprite's rotation. Gizmos only last a frame, so nothing is left to clean
+/// up when the overlay is turned off.
+fn draw_hitboxes(
+    mut gizmos: Gizmos,
+    overlay: Res<DebugOverlay>,
+    settings: Res<Settings>,
+    players: Query<&Transform, With<Player>>,
+    enemies: Query<(&Transform, &Visibility), With<Collider>>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+
+    for transform in players.iter() {
+        gizmos.rect_2d(
+            transform.translation.truncate(),
+            0.0,
+            player::hitbox_size(&settings),
+            PLAYER_HITBOX_COLOR,
+        );
+    }
+
+    for (transform, visibility) in enemies.iter() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+        gizmos.rect_2d(
+            transform.translation.truncate(),
+            0.0,
+            transform.scale.truncate() * SPRITE_SIZE,
+            ENEMY_HITBOX_COLOR,
+        );
+    }
+}
+
+/// Marks the line along the top that enemies spawn on, and where each drop
+/// still waiting to spawn will come in.
+fn draw_spawn_zones(
+    mut gizmos: Gizmos,
+    overlay: Res<DebugOverlay>,
+    play_area: Res<PlayArea>,
+    spawn_timer: Option<Res<SpawnTimer>>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+
+    let top = play_area.y.end;
+    gizmos.line_2d(
+        Vec2::new(play_area.x.start, top),
+        Vec2::new(play_area.x.end, top),
+        SPAWN_ZONE_COLOR,
+    );
+
+    let drops = spawn_timer
+        .iter()
+        .flat_map(|spawn_timer| spawn_timer.drop_positions());
+    for x in drops {
+        gizmos.rect_2d(
+            Vec2::new(x, top - SPRITE_SIZE / 2.0),
+            0.0,
+            Vec2::splat(SPRITE_SIZE),
+            SPAWN_WARNING_COLOR,
+        );
+    }
+}
+
+fn update_debug_text(
+    mut commands: Commands,
+    overlay: Res<DebugOverlay>,
+    font: Res<TextFont>,
+    diagnostics: Res<DiagnosticsStore>,
+    game_speed: Res<GameSpeed>,
+    entities: Query<Entity>,
+    mut text_query: Query<(Entity, &mut Text), With<DebugText>>,
+) {
+    let debug_text = text_query.get_single_mut();
+
+    if !overlay.enabled {
+        if let Ok((entity, _)) = debug_text {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let measurement = |path| {
+        diagnostics
+            .get(path)
+            .and_then(|diagnostic| diagnostic.value())
+            .unwrap_or_default()
+    };
+    let value = format!(
+        "entities: {}\nenemies: {}\nevents/s: {:.0}\nspeed: {:.2}",
+        entities.iter().count(),
+        measurement(&ENEMY_COUNT),
+        measurement(&EVENTS_PER_SECOND),
+        game_speed.0,
+    );
+
+    match debug_text {
+        Ok((_, mut text)) => text.sections[0].value = value,
+        Err(_) => {
+            commands
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        value,
+                        TextStyle {
+                            font: font.0.clone(),
+                            font_size: DEBUG_FONT_SIZE,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        bottom: Val::Px(16.0),
+                        right: Val::Px(16.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(DebugText);
+        }
+    }
+}
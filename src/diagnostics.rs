@@ -0,0 +1,113 @@
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    prelude::*,
+};
+
+use crate::{
+    enemy::{Collider, EnemySpawnEvent},
+    player::CollisionEvent,
+    score::ScoreEvent,
+    states::{GameState, RunState},
+};
+
+/// Frames slower than this are logged as spikes.
+const FRAME_SPIKE_SECONDS: f32 = 0.05;
+const SPAWN_REPORT_SECONDS: f32 = 10.0;
+const DIAGNOSTIC_HISTORY: usize = 20;
+
+pub const ENEMY_COUNT: DiagnosticPath = DiagnosticPath::const_new("enemy_count");
+pub const EVENTS_PER_SECOND: DiagnosticPath = DiagnosticPath::const_new("events_per_second");
+
+/// Logs what the game is doing under the `dodger::diagnostics` target, so
+/// the detail can be picked with `RUST_LOG`: state changes at info, spawn
+/// counts and collisions at debug and frame-time spikes at warn. Also
+/// records the live enemy count and game events per second as diagnostics.
+pub struct DiagnosticsPlugin;
+
+impl Plugin for DiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(
+            Diagnostic::new(ENEMY_COUNT).with_max_history_length(DIAGNOSTIC_HISTORY),
+        )
+        .register_diagnostic(
+            Diagnostic::new(EVENTS_PER_SECOND).with_max_history_length(DIAGNOSTIC_HISTORY),
+        )
+        .insert_resource(SpawnReport {
+            count: 0,
+            timer: Timer::from_seconds(SPAWN_REPORT_SECONDS, TimerMode::Repeating),
+        })
+        .add_systems(Update, log_state_changes)
+        .add_systems(Update, log_frame_spikes)
+        .add_systems(Update, log_collisions)
+        .add_systems(Update, measure_events)
+        .add_systems(Update, measure_enemy_count)
+        .add_systems(Update, report_spawns.run_if(in_state(RunState::Running)));
+    }
+}
+
+#[derive(Resource)]
+struct SpawnReport {
+    count: usize,
+    timer: Timer,
+}
+
+fn log_state_changes(state: Res<State<GameState>>, mut previous: Local<Option<GameState>>) {
+    let current = state.get();
+    if previous.as_ref() != Some(current) {
+        info!("state: {:?} -> {:?}", *previous, current);
+        *previous = Some(current.clone());
+    }
+}
+
+fn log_frame_spikes(time: Res<Time>) {
+    let delta_time = time.delta_seconds();
+    if delta_time > FRAME_SPIKE_SECONDS {
+        warn!("slow frame: {:.1}ms", delta_time * 1000.0);
+    }
+}
+
+fn log_collisions(mut ev_collision: EventReader<CollisionEvent>) {
+    for collision in ev_collision.read() {
+        debug!("collision: {:?} hit {:?}", collision.0, collision.1);
+    }
+}
+
+fn report_spawns(
+    time: Res<Time>,
+    mut report: ResMut<SpawnReport>,
+    mut ev_spawn: EventReader<EnemySpawnEvent>,
+) {
+    report.count += ev_spawn.read().count();
+
+    if report.timer.tick(time.delta()).just_finished() {
+        debug!(
+            "{} enemies spawned in the last {}s",
+            report.count, SPAWN_REPORT_SECONDS
+        );
+        report.count = 0;
+    }
+}
+
+fn measure_events(
+    time: Res<Time>,
+    mut diagnostics: Diagnostics,
+    mut ev_spawn: EventReader<EnemySpawnEvent>,
+    mut ev_collision: EventReader<CollisionEvent>,
+    mut ev_score: EventReader<ScoreEvent>,
+) {
+    let delta_time = time.delta_seconds_f64();
+    if delta_time == 0.0 {
+        return;
+    }
+
+    let events = ev_spawn.read().count() + ev_collision.read().count() + ev_score.read().count();
+    diagnostics.add_measurement(&EVENTS_PER_SECOND, || events as f64 / delta_time);
+}
+
+fn measure_enemy_count(mut diagnostics: Diagnostics, query: Query<&Visibility, With<Collider>>) {
+    let count = query
+        .iter()
+        .filter(|visibility| **visibility != Visibility::Hidden)
+        .count();
+    diagnostics.add_measurement(&ENEMY_COUNT, || count as f64);
+}
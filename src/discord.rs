@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use discord_rich_presence::{activity::Activity, DiscordIpc, DiscordIpcClient};
+
+use crate::{score::Scoreboard, states::GameState};
+
+/// The Discord application to show presence for, set when building with
+/// `DISCORD_APP_ID=<id> cargo build --features discord`.
+const DISCORD_APP_ID: Option<&str> = option_env!("DISCORD_APP_ID");
+/// Discord drops presence updates sent more often than this.
+const UPDATE_SECONDS: f32 = 15.0;
+
+/// Shows what the player is doing on their Discord profile. Does nothing,
+/// apart from a warning, when Discord isn't running.
+pub struct DiscordPlugin;
+
+impl Plugin for DiscordPlugin {
+    fn build(&self, app: &mut App) {
+        let app_id = match DISCORD_APP_ID {
+            Some(app_id) => app_id,
+            None => {
+                warn!("built without DISCORD_APP_ID, discord presence is disabled");
+                return;
+            }
+        };
+
+        let client = DiscordIpcClient::new(app_id).and_then(|mut client| {
+            client.connect()?;
+            Ok(client)
+        });
+        let client = match client {
+            Ok(client) => client,
+            Err(err) => {
+                warn!("discord is unavailable: {}", err);
+                return;
+            }
+        };
+
+        app.insert_non_send_resource(client)
+            .insert_resource(Presence {
+                sent: None,
+                timer: Timer::from_seconds(UPDATE_SECONDS, TimerMode::Repeating),
+            })
+            .add_systems(Update, update_presence);
+    }
+}
+
+#[derive(Resource)]
+struct Presence {
+    sent: Option<String>,
+    timer: Timer,
+}
+
+/// State changes are sent right away; the score only as often as Discord
+/// allows.
+fn update_presence(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    scoreboard: Res<Scoreboard>,
+    mut presence: ResMut<Presence>,
+    mut client: NonSendMut<DiscordIpcClient>,
+) {
+    let ready = presence.timer.tick(time.delta()).finished();
+    if !ready && !state.is_changed() {
+        return;
+    }
+
+    let text = match state.get() {
+        GameState::Loading
+        | GameState::Title
+        | GameState::Leaderboards
+        | GameState::History
+        | GameState::Skins
+        | GameState::Shop
+        | GameState::Daily
+        | GameState::Profiles => "In menu".to_string(),
+        GameState::Playing | GameState::Dying => {
+            format!("Dodging \u{2014} score {}", scoreboard.score as i32)
+        }
+        GameState::GameOver => "Game over".to_string(),
+    };
+    if presence.sent.as_ref() == Some(&text) {
+        return;
+    }
+
+    if let Err(err) = client.set_activity(Activity::new().state(&text)) {
+        warn!("failed to update discord presence: {}", err);
+    }
+    presence.sent = Some(text);
+    presence.timer.reset();
+}
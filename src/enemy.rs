@@ -0,0 +1,614 @@
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    animation::SpawnAnim,
+    bounds::{DespawnOutOfBounds, PlayArea},
+    config::GameConfig,
+    console::AddConsoleCommand,
+    enemy_table::{Behavior, EnemyTable, EnemyTableLoader, ENEMY_TABLE_PATH},
+    loading::LoadingAssets,
+    movement::{AngularVelocity, Interpolated, Velocity, FIXED_STEP},
+    particles::Trail,
+    prefab::{self, ApplyPrefab, Prefab},
+    rng::GameRng,
+    score::{ScoreEvent, ScoreSource},
+    settings::Settings,
+    states::{GameState, InRun, RunState},
+    time_scale::TimeScale,
+    GameSystem, SpriteSheet, ACTOR_Z, SPRITE_SIZE,
+};
+
+const PARKED_POSITION: Vec3 = Vec3::new(0.0, -10_000.0, ACTOR_Z);
+/// Wide enough that swaying enemies can drift past the side of the screen
+/// and come back. Enemies leaving the bottom are recycled before this.
+const ENEMY_BOUNDS_MARGIN: f32 = 128.0;
+/// The most extra enemies one command can ask for.
+pub const MAX_BURST: u32 = 32;
+
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<EnemySpawnEvent>()
+            .add_event::<EnemyBurstEvent>()
+            .add_event::<EnemyDropEvent>()
+            .init_resource::<AutoSpawn>()
+            .init_asset::<EnemyTable>()
+            .init_asset_loader::<EnemyTableLoader>()
+            .add_systems(Startup, load_enemy_table)
+            .add_cheat_command("spawn", spawn_command)
+            .add_systems(OnEnter(GameState::Playing), setup_spawner)
+            .add_systems(
+                Update,
+                (queue_bursts, queue_drops, apply_spawn_interval)
+                    .run_if(in_state(RunState::Running)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    enemy_spawner,
+                    sway_enemies.before(GameSystem::Movement),
+                    recycle_enemies.after(GameSystem::Movement),
+                )
+                    .run_if(in_state(RunState::Running)),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct Collider;
+
+#[derive(Event)]
+pub struct EnemySpawnEvent(pub Vec3);
+
+/// Asks for this many enemies on top of the regular spawns.
+#[derive(Event)]
+pub struct EnemyBurstEvent(pub u32);
+
+/// Asks for one enemy of a chosen kind, by its index in the [`EnemyTable`],
+/// at a chosen x position.
+#[derive(Event)]
+pub struct EnemyDropEvent {
+    pub kind: usize,
+    pub x: f32,
+}
+
+/// Whether the spawner drops enemies on its own timer. Bursts and drops are
+/// spawned either way.
+#[derive(Resource)]
+pub struct AutoSpawn(pub bool);
+
+impl Default for AutoSpawn {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// How an enemy moves and what it is worth, copied from its [`EnemyTable`]
+/// entry when it spawns.
+#[derive(Component)]
+pub struct Enemy {
+    kind: usize,
+    behavior: Behavior,
+    score: f32,
+    age: f32,
+}
+
+impl Enemy {
+    /// The index of its kind in the [`EnemyTable`].
+    pub fn kind(&self) -> usize {
+        self.kind
+    }
+}
+
+/// The enemy's kind's tint, which the palette's enemy color is multiplied by.
+#[derive(Component)]
+pub struct Tint(pub Color);
+
+impl Tint {
+    pub fn apply(&self, color: Color) -> Color {
+        let (color, tint) = (color.to_srgba(), self.0.to_srgba());
+        Color::srgba(
+            color.red * tint.red,
+            color.green * tint.green,
+            color.blue * tint.blue,
+            color.alpha * tint.alpha,
+        )
+    }
+}
+
+#[derive(Resource)]
+pub struct EnemyTableHandle(pub Handle<EnemyTable>);
+
+/// The run's spawner, inserted when a run starts.
+#[derive(Resource)]
+pub struct SpawnTimer {
+    timer: Timer,
+    /// Extra enemies requested from the console or by a burst, spawned one
+    /// per step.
+    pending: u32,
+    /// Enemies chosen with [`EnemyDropEvent`]s, spawned one per step.
+    drops: Vec<(usize, f32)>,
+}
+
+impl SpawnTimer {
+    /// Where the queued drops will come in, next first.
+    pub fn drop_positions(&self) -> impl Iterator<Item = f32> + '_ {
+        self.drops.iter().map(|&(_, x)| x)
+    }
+}
+
+/// Enemies that have left the screen, hidden and waiting to be reused.
+#[derive(Resource, Default)]
+struct EnemyPool(Vec<Entity>);
+
+/// Where the spawner had got to when a run was suspended, and the enemies
+/// that were still falling.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SpawnerSnapshot {
+    /// Seconds into the current spawn interval.
+    pub elapsed: f32,
+    pub pending: u32,
+    pub drops: Vec<(usize, f32)>,
+    pub enemies: Vec<EnemySnapshot>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EnemySnapshot {
+    pub sprite: usize,
+    pub position: (f32, f32),
+    /// Around the z axis, in radians.
+    pub rotation: f32,
+    pub scale: f32,
+    pub velocity: (f32, f32),
+    pub spin: f32,
+    pub behavior: Behavior,
+    pub score: f32,
+    pub age: f32,
+    pub trail: bool,
+    /// Snapshots from before variants had tints have none, and load as white.
+    #[serde(default)]
+    pub tint: Color,
+    /// Snapshots from before kinds were kept load as the first kind.
+    #[serde(default)]
+    pub kind: usize,
+}
+
+fn load_enemy_table(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    let table = asset_server.load(ENEMY_TABLE_PATH);
+    loading.add(&table);
+    commands.insert_resource(EnemyTableHandle(table));
+}
+
+fn setup_spawner(mut commands: Commands, config: Res<GameConfig>) {
+    commands.insert_resource(SpawnTimer {
+        timer: Timer::from_seconds(config.spawn_interval, TimerMode::Repeating),
+        pending: 0,
+        drops: Vec::new(),
+    });
+    commands.insert_resource(EnemyPool::default());
+}
+
+/// The spawner is inserted with commands, so it isn't there yet on the frame
+/// a run starts.
+fn queue_bursts(
+    mut ev_burst: EventReader<EnemyBurstEvent>,
+    spawn_timer: Option<ResMut<SpawnTimer>>,
+) {
+    if let Some(mut spawn_timer) = spawn_timer {
+        for burst in ev_burst.read() {
+            spawn_timer.pending = spawn_timer.pending.saturating_add(burst.0);
+        }
+    }
+}
+
+/// Like bursts, drops are queued here and spawned in `FixedUpdate`, which
+/// doesn't run every frame.
+fn queue_drops(mut ev_drop: EventReader<EnemyDropEvent>, spawn_timer: Option<ResMut<SpawnTimer>>) {
+    if let Some(mut spawn_timer) = spawn_timer {
+        for drop in ev_drop.read() {
+            spawn_timer.drops.push((drop.kind, drop.x));
+        }
+    }
+}
+
+/// Picks up a spawn interval changed in `config.ron` while a run is going.
+fn apply_spawn_interval(config: Res<GameConfig>, spawn_timer: Option<ResMut<SpawnTimer>>) {
+    if let Some(mut spawn_timer) = spawn_timer {
+        if config.is_changed() {
+            let interval = Duration::from_secs_f32(config.spawn_interval);
+            spawn_timer.timer.set_duration(interval);
+        }
+    }
+}
+
+type PooledEnemy<'a> = (
+    &'a mut Transform,
+    &'a mut Interpolated,
+    &'a mut Velocity,
+    &'a mut AngularVelocity,
+    &'a mut SpawnAnim,
+    &'a mut Trail,
+    &'a mut Sprite,
+    &'a mut TextureAtlas,
+    &'a mut Visibility,
+    &'a mut Enemy,
+    &'a mut Tint,
+);
+
+#[allow(clippy::too_many_arguments)]
+fn enemy_spawner(
+    mut commands: Commands,
+    mut spawn_timer: ResMut<SpawnTimer>,
+    mut pool: ResMut<EnemyPool>,
+    mut rng: ResMut<GameRng>,
+    time_scale: Res<TimeScale>,
+    tables: Res<Assets<EnemyTable>>,
+    table_handle: Res<EnemyTableHandle>,
+    sprite_sheet: Res<SpriteSheet>,
+    settings: Res<Settings>,
+    config: Res<GameConfig>,
+    play_area: Res<PlayArea>,
+    auto_spawn: Res<AutoSpawn>,
+    mut ev_spawn: EventWriter<EnemySpawnEvent>,
+    mut pooled: Query<PooledEnemy, With<Collider>>,
+) {
+    let table = match tables.get(&table_handle.0) {
+        Some(table) => table,
+        None => return,
+    };
+
+    spawn_timer
+        .timer
+        .tick(Duration::from_secs_f32(FIXED_STEP * time_scale.0));
+
+    let due = auto_spawn.0 && spawn_timer.timer.finished();
+    let drop = if spawn_timer.drops.is_empty() {
+        None
+    } else {
+        Some(spawn_timer.drops.remove(0))
+    };
+    // Only one enemy spawns per step, so a timed spawn that lands on the
+    // same step as a drop waits for the next one instead of being lost.
+    if due && drop.is_some() {
+        spawn_timer.pending = spawn_timer.pending.saturating_add(1);
+    }
+
+    if due || drop.is_some() || spawn_timer.pending > 0 {
+        if !due && drop.is_none() {
+            spawn_timer.pending -= 1;
+        }
+
+        let (index, kind, x) = match drop {
+            Some((index, x)) => match table.kinds.get(index) {
+                Some(kind) => (index, kind, x),
+                None => {
+                    warn!("no enemy kind {} to drop", index);
+                    return;
+                }
+            },
+            None => match table.kinds.choose_weighted(&mut **rng, |kind| kind.weight) {
+                Ok(kind) => {
+                    let index = table
+                        .kinds
+                        .iter()
+                        .position(|other| std::ptr::eq(other, kind))
+                        .unwrap();
+                    (index, kind, rng.gen_range(play_area.x.clone()))
+                }
+                Err(err) => {
+                    warn!("no enemy to spawn: {}", err);
+                    return;
+                }
+            },
+        };
+        let velocity = rng.gen_range(kind.speed.clone());
+        let scale = rng.gen_range(kind.size.clone());
+        let spin = rng.gen_range(kind.spin.clone());
+        let translation = Vec3::new(x, play_area.y.end, ACTOR_Z);
+        let spawn_anim = SpawnAnim::new(scale);
+        let start_scale = Vec3::new(spawn_anim.start_scale(), spawn_anim.start_scale(), 1.0);
+        let trail = velocity > config.trail_speed;
+        let tint = Tint(kind.tint);
+        let mut color = tint.apply(settings.palette.colors().enemy);
+        color.set_alpha(0.0);
+        let enemy = Enemy {
+            kind: index,
+            behavior: kind.behavior,
+            score: kind.score,
+            age: 0.0,
+        };
+
+        let recycled = pool.0.pop().and_then(|entity| pooled.get_mut(entity).ok());
+
+        if let Some((
+            mut transform,
+            mut position,
+            mut enemy_velocity,
+            mut angular_velocity,
+            mut anim,
+            mut enemy_trail,
+            mut sprite,
+            mut atlas,
+            mut visibility,
+            mut recycled_enemy,
+            mut recycled_tint,
+        )) = recycled
+        {
+            *transform = Transform {
+                translation,
+                scale: start_scale,
+                ..default()
+            };
+            *position = Interpolated::new(translation);
+            enemy_velocity.0 = Vec3::new(0.0, -velocity, 0.0);
+            angular_velocity.0 = spin;
+            anim.restart(scale);
+            enemy_trail.enabled = trail;
+            atlas.index = kind.sprite;
+            sprite.color = color;
+            *visibility = Visibility::Inherited;
+            *recycled_enemy = enemy;
+            *recycled_tint = tint;
+        } else {
+            let entity = commands
+                .spawn((
+                    SpriteBundle {
+                        sprite: Sprite { color, ..default() },
+                        texture: sprite_sheet.texture.clone(),
+                        transform: Transform {
+                            translation,
+                            scale: start_scale,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    sprite_sheet.atlas(kind.sprite),
+                ))
+                .insert(Velocity(Vec3::new(0.0, -velocity, 0.0)))
+                .insert(Interpolated::new(translation))
+                .insert(AngularVelocity(spin))
+                .insert(Collider)
+                .insert(enemy)
+                .insert(tint)
+                .insert(spawn_anim)
+                .insert(Trail::new(trail))
+                .insert(DespawnOutOfBounds {
+                    margin: ENEMY_BOUNDS_MARGIN,
+                })
+                .insert(StateScoped(InRun))
+                .id();
+            // Recycled enemies keep the prefab from when they were spawned.
+            commands.add(ApplyPrefab {
+                entity,
+                prefab: Prefab::Enemy,
+            });
+        }
+
+        ev_spawn.send(EnemySpawnEvent(translation));
+    }
+}
+
+/// Steers swaying enemies from side to side, ahead of the movement step.
+fn sway_enemies(
+    time_scale: Res<TimeScale>,
+    mut query: Query<(&mut Enemy, &mut Velocity, &Visibility), With<Collider>>,
+) {
+    for (mut enemy, mut velocity, visibility) in query.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        enemy.age += FIXED_STEP * time_scale.0;
+        if let Behavior::Sway {
+            amplitude,
+            frequency,
+        } = enemy.behavior
+        {
+            let angular_frequency = TAU * frequency;
+            velocity.0.x = amplitude * angular_frequency * (angular_frequency * enemy.age).cos();
+        }
+    }
+}
+
+type FallingEnemy<'a> = (
+    Entity,
+    &'a mut Interpolated,
+    &'a mut Velocity,
+    &'a mut Trail,
+    &'a mut Visibility,
+    &'a Transform,
+    &'a Enemy,
+);
+
+/// Hides enemies once they have fallen past the bottom of the screen, awards
+/// their score and parks them far away until the spawner needs them again.
+fn recycle_enemies(
+    mut pool: ResMut<EnemyPool>,
+    play_area: Res<PlayArea>,
+    mut ev_score: EventWriter<ScoreEvent>,
+    mut query: Query<FallingEnemy, With<Collider>>,
+) {
+    for (entity, mut position, mut velocity, mut trail, mut visibility, transform, enemy) in
+        query.iter_mut()
+    {
+        let bottom = play_area.y.start - transform.scale.y * SPRITE_SIZE;
+        if *visibility == Visibility::Hidden || position.current.y > bottom {
+            continue;
+        }
+
+        *position = Interpolated::new(PARKED_POSITION);
+        velocity.0 = Vec3::ZERO;
+        trail.enabled = false;
+        *visibility = Visibility::Hidden;
+        pool.0.push(entity);
+
+        if enemy.score > 0.0 {
+            ev_score.send(ScoreEvent {
+                amount: enemy.score,
+                source: ScoreSource::Dodge,
+            });
+        }
+    }
+}
+
+/// Pooled enemies and ones already hit aren't falling, so they are left out.
+pub fn snapshot_spawner(world: &mut World) -> SpawnerSnapshot {
+    let mut snapshot = match world.get_resource::<SpawnTimer>() {
+        Some(spawn_timer) => SpawnerSnapshot {
+            elapsed: spawn_timer.timer.elapsed_secs(),
+            pending: spawn_timer.pending,
+            drops: spawn_timer.drops.clone(),
+            enemies: Vec::new(),
+        },
+        None => SpawnerSnapshot::default(),
+    };
+
+    let mut query = world.query_filtered::<(
+        &Interpolated,
+        &Transform,
+        &Velocity,
+        &AngularVelocity,
+        &SpawnAnim,
+        &Trail,
+        &TextureAtlas,
+        &Visibility,
+        &Enemy,
+        &Tint,
+    ), With<Collider>>();
+    for (position, transform, velocity, spin, anim, trail, atlas, visibility, enemy, tint) in
+        query.iter(world)
+    {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        let (axis, angle) = transform.rotation.to_axis_angle();
+        snapshot.enemies.push(EnemySnapshot {
+            sprite: atlas.index,
+            position: (position.current.x, position.current.y),
+            rotation: angle * axis.z,
+            scale: anim.scale(),
+            velocity: (velocity.0.x, velocity.0.y),
+            spin: spin.0,
+            behavior: enemy.behavior,
+            score: enemy.score,
+            age: enemy.age,
+            trail: trail.enabled,
+            tint: tint.0,
+            kind: enemy.kind,
+        });
+    }
+    snapshot
+}
+
+/// Sets the spawner back to where a snapshot left it, and replaces every
+/// enemy with the ones that were falling then. They fade in where they were,
+/// so restoring doesn't drop anyone straight into a hit they can't see
+/// coming.
+pub fn restore_spawner(world: &mut World, snapshot: &SpawnerSnapshot) {
+    let enemies: Vec<Entity> = world
+        .query_filtered::<Entity, With<Collider>>()
+        .iter(world)
+        .collect();
+    for entity in enemies {
+        world.entity_mut(entity).despawn_recursive();
+    }
+    if let Some(mut pool) = world.get_resource_mut::<EnemyPool>() {
+        pool.0.clear();
+    }
+
+    if let Some(mut spawn_timer) = world.get_resource_mut::<SpawnTimer>() {
+        spawn_timer
+            .timer
+            .set_elapsed(Duration::from_secs_f32(snapshot.elapsed));
+        spawn_timer.pending = snapshot.pending;
+        spawn_timer.drops = snapshot.drops.clone();
+    }
+
+    let sprite_sheet = world.get_resource::<SpriteSheet>().unwrap();
+    let texture = sprite_sheet.texture.clone();
+    let layout = sprite_sheet.layout.clone();
+    let enemy_color = world
+        .get_resource::<Settings>()
+        .unwrap()
+        .palette
+        .colors()
+        .enemy;
+
+    for enemy in &snapshot.enemies {
+        let tint = Tint(enemy.tint);
+        let mut color = tint.apply(enemy_color);
+        color.set_alpha(0.0);
+        let translation = Vec3::new(enemy.position.0, enemy.position.1, ACTOR_Z);
+        let spawn_anim = SpawnAnim::new(enemy.scale);
+        let start_scale = spawn_anim.start_scale();
+
+        let entity = world
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite { color, ..default() },
+                    texture: texture.clone(),
+                    transform: Transform {
+                        translation,
+                        rotation: Quat::from_rotation_z(enemy.rotation),
+                        scale: Vec3::new(start_scale, start_scale, 1.0),
+                    },
+                    ..default()
+                },
+                TextureAtlas {
+                    layout: layout.clone(),
+                    index: enemy.sprite,
+                },
+            ))
+            .insert(Velocity(Vec3::new(enemy.velocity.0, enemy.velocity.1, 0.0)))
+            .insert(Interpolated::new(translation))
+            .insert(AngularVelocity(enemy.spin))
+            .insert(Collider)
+            .insert(Enemy {
+                kind: enemy.kind,
+                behavior: enemy.behavior,
+                score: enemy.score,
+                age: enemy.age,
+            })
+            .insert(tint)
+            .insert(spawn_anim)
+            .insert(Trail::new(enemy.trail))
+            .insert(DespawnOutOfBounds {
+                margin: ENEMY_BOUNDS_MARGIN,
+            })
+            .insert(StateScoped(InRun))
+            .id();
+        prefab::apply(world, entity, Prefab::Enemy);
+    }
+}
+
+/// `spawn enemy <count>` spawns up to [`MAX_BURST`] extra enemies on top of
+/// the usual ones.
+fn spawn_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let count: u32 = match args {
+        ["enemy", count] => count
+            .parse()
+            .map_err(|_| format!("invalid count '{}'", count))?,
+        _ => return Err("usage: spawn enemy <count>".to_string()),
+    };
+    if count > MAX_BURST {
+        return Err(format!("can't spawn more than {} at once", MAX_BURST));
+    }
+
+    match world.get_resource_mut::<SpawnTimer>() {
+        Some(mut spawn_timer) => {
+            spawn_timer.pending = spawn_timer.pending.saturating_add(count);
+            Ok(format!("spawning {} enemies", count))
+        }
+        None => Err("no run in progress".to_string()),
+    }
+}
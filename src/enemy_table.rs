@@ -0,0 +1,201 @@
+use std::ops::Range;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use ron::extensions::Extensions;
+use serde::{Deserialize, Serialize};
+
+use crate::SPRITE_COUNT;
+
+pub const ENEMY_TABLE_PATH: &str = "default.enemies.ron";
+
+/// Every kind of enemy the spawner can pick from. Loaded from any file ending
+/// in `.enemies.ron`, by default `assets/default.enemies.ron`, with variants
+/// already resolved, see [`KindEntry`].
+#[derive(Debug, Asset, TypePath)]
+pub struct EnemyTable {
+    pub kinds: Vec<EnemyKind>,
+}
+
+impl EnemyTable {
+    /// Reads a table the way the asset loader does, failing on the first
+    /// kind that is missing a field or has a value the spawner can't use.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        // Lets overridden fields be written without `Some(...)`.
+        let file: EnemyTableFile = ron::Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME)
+            .from_bytes(bytes)?;
+
+        let mut kinds: Vec<EnemyKind> = Vec::with_capacity(file.kinds.len());
+        for entry in file.kinds {
+            let kind = entry.resolve(&kinds)?;
+            kind.check()?;
+            kinds.push(kind);
+        }
+        if !kinds.iter().any(|kind| kind.weight > 0.0) {
+            anyhow::bail!("no kind has a weight above 0, so none would ever spawn");
+        }
+        Ok(Self { kinds })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EnemyKind {
+    pub name: String,
+    pub sprite: usize,
+    pub size: Range<f32>,
+    pub speed: Range<f32>,
+    pub spin: Range<f32>,
+    pub behavior: Behavior,
+    /// Points awarded when one falls past the bottom of the screen.
+    pub score: f32,
+    /// How often this kind is picked relative to the others.
+    pub weight: f32,
+    /// What dropping one costs in versus mode.
+    pub cost: f32,
+    /// Multiplies the palette's enemy color.
+    pub tint: Color,
+}
+
+impl EnemyKind {
+    /// The spawner picks from `size`, `speed` and `spin`, so each has to
+    /// hold at least one number, and weights can't be negative. The sprite
+    /// has to be on the sheet.
+    fn check(&self) -> Result<(), anyhow::Error> {
+        if self.sprite >= SPRITE_COUNT {
+            anyhow::bail!(
+                "'{}' has sprite {}, but the sheet only has {}",
+                self.name,
+                self.sprite,
+                SPRITE_COUNT
+            );
+        }
+        for (field, range) in [
+            ("size", &self.size),
+            ("speed", &self.speed),
+            ("spin", &self.spin),
+        ] {
+            if !range.start.is_finite() || !range.end.is_finite() || range.is_empty() {
+                anyhow::bail!(
+                    "'{}' has a {} of {}..{}, which has to be finite with its end past its start",
+                    self.name,
+                    field,
+                    range.start,
+                    range.end
+                );
+            }
+        }
+        if !self.weight.is_finite() || self.weight < 0.0 {
+            anyhow::bail!(
+                "'{}' has a weight of {}, which can't be negative",
+                self.name,
+                self.weight
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct EnemyTableFile {
+    kinds: Vec<KindEntry>,
+}
+
+/// A kind as written in the file. A variant names a `base` kind from earlier
+/// in the file and only lists the fields it changes, like a fast red rock
+/// that is a `rock` with its own `speed` and `tint`. Any other kind has to
+/// give `sprite`, `size`, `speed`, `spin` and `weight`.
+#[derive(Deserialize)]
+struct KindEntry {
+    name: String,
+    base: Option<String>,
+    sprite: Option<usize>,
+    size: Option<Range<f32>>,
+    speed: Option<Range<f32>>,
+    spin: Option<Range<f32>>,
+    behavior: Option<Behavior>,
+    score: Option<f32>,
+    weight: Option<f32>,
+    cost: Option<f32>,
+    tint: Option<Color>,
+}
+
+impl KindEntry {
+    fn resolve(self, kinds: &[EnemyKind]) -> Result<EnemyKind, anyhow::Error> {
+        let base = match &self.base {
+            Some(base) => match kinds.iter().find(|kind| kind.name == *base) {
+                Some(kind) => kind.clone(),
+                None => anyhow::bail!(
+                    "'{}' is based on '{}', which has to come before it",
+                    self.name,
+                    base
+                ),
+            },
+            None => {
+                let missing = |field| anyhow::anyhow!("'{}' has no {}", self.name, field);
+                EnemyKind {
+                    name: self.name.clone(),
+                    sprite: self.sprite.ok_or_else(|| missing("sprite"))?,
+                    size: self.size.clone().ok_or_else(|| missing("size"))?,
+                    speed: self.speed.clone().ok_or_else(|| missing("speed"))?,
+                    spin: self.spin.clone().ok_or_else(|| missing("spin"))?,
+                    behavior: Behavior::default(),
+                    score: 0.0,
+                    weight: self.weight.ok_or_else(|| missing("weight"))?,
+                    cost: 1.0,
+                    tint: Color::WHITE,
+                }
+            }
+        };
+
+        Ok(EnemyKind {
+            name: self.name,
+            sprite: self.sprite.unwrap_or(base.sprite),
+            size: self.size.unwrap_or(base.size),
+            speed: self.speed.unwrap_or(base.speed),
+            spin: self.spin.unwrap_or(base.spin),
+            behavior: self.behavior.unwrap_or(base.behavior),
+            score: self.score.unwrap_or(base.score),
+            weight: self.weight.unwrap_or(base.weight),
+            cost: self.cost.unwrap_or(base.cost),
+            tint: self.tint.unwrap_or(base.tint),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub enum Behavior {
+    /// Falls straight down.
+    #[default]
+    Fall,
+    /// Drifts from side to side while falling, `amplitude` pixels either way
+    /// and `frequency` times a second.
+    Sway { amplitude: f32, frequency: f32 },
+}
+
+#[derive(Default)]
+pub struct EnemyTableLoader;
+
+impl AssetLoader for EnemyTableLoader {
+    type Asset = EnemyTable;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<EnemyTable, anyhow::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let table = EnemyTable::from_bytes(&bytes)?;
+        Ok(table)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["enemies.ron"]
+    }
+}
@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+
+use crate::{
+    lifetime::Lifetime,
+    settings::Settings,
+    states::{InRun, RunActive},
+};
+
+pub struct FlashPlugin;
+
+impl Plugin for FlashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<FlashEvent>();
+
+        app.add_systems(
+            Update,
+            (spawn_flash, fade_flash).run_if(in_state(RunActive)),
+        );
+    }
+}
+
+/// Requests a fullscreen flash of `color` that fades out over `duration` seconds.
+#[derive(Event)]
+pub struct FlashEvent {
+    pub color: Color,
+    pub duration: f32,
+}
+
+#[derive(Component)]
+struct Flash {
+    color: Color,
+}
+
+fn spawn_flash(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut ev_flash: EventReader<FlashEvent>,
+) {
+    for flash in ev_flash.read() {
+        if settings.reduced_motion {
+            continue;
+        }
+
+        commands
+            .spawn(NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                background_color: BackgroundColor(flash.color),
+                ..default()
+            })
+            .insert(Flash { color: flash.color })
+            .insert(Lifetime::from_seconds(flash.duration))
+            .insert(StateScoped(InRun));
+    }
+}
+
+fn fade_flash(mut query: Query<(&Flash, &Lifetime, &mut BackgroundColor)>) {
+    for (flash, lifetime, mut color) in query.iter_mut() {
+        let remaining = 1.0 - lifetime.0.fraction();
+        color.0 = flash.color;
+        color
+            .0
+            .set_alpha(flash.color.alpha() * remaining * remaining);
+    }
+}
@@ -0,0 +1,38 @@
+use bevy::prelude::*;
+
+use crate::{bounds::PlayArea, player::PLAYER_IDLE_FRAMES, states::InRun, SpriteSheet, ACTOR_Z};
+
+/// How see-through a ghost is.
+pub const GHOST_ALPHA: f32 = 0.4;
+/// Drawn just behind the real player.
+const GHOST_Z: f32 = ACTOR_Z - 0.1;
+
+/// A translucent player that nothing collides with. Its position comes from
+/// somewhere other than the local controls, such as another player online.
+#[derive(Component)]
+pub struct Ghost;
+
+/// Spawns a ghost for the current run where the players start.
+pub fn spawn_ghost(
+    commands: &mut Commands,
+    sprite_sheet: &SpriteSheet,
+    play_area: &PlayArea,
+    color: Color,
+) -> Entity {
+    let mut sprite = Sprite { color, ..default() };
+    sprite.color.set_alpha(GHOST_ALPHA);
+
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: sprite_sheet.texture.clone(),
+                transform: Transform::from_xyz(0.0, play_area.y.start, GHOST_Z),
+                sprite,
+                ..default()
+            },
+            sprite_sheet.atlas(PLAYER_IDLE_FRAMES[0]),
+        ))
+        .insert(Ghost)
+        .insert(StateScoped(InRun))
+        .id()
+}
@@ -0,0 +1,324 @@
+use std::sync::{
+    mpsc::{self, Receiver, Sender},
+    Mutex,
+};
+
+use bevy::{prelude::*, tasks::IoTaskPool};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::GameConfig,
+    leaderboard::Leaderboard,
+    locale::{Locale, LocalizedText},
+    player::PlayerCount,
+    replay::ReplayRecorder,
+    rng::GameRng,
+    score::{Ranked, Scoreboard},
+    settings::Settings,
+    states::{self, GameState, MenuSelectEvent},
+    ui::TextFont,
+    versus::{self, Versus},
+    GameSystem,
+};
+
+const TOP_SCORES: usize = 100;
+const VISIBLE_ROWS: usize = 15;
+const LEADERBOARDS_FONT_SIZE: f32 = 16.0;
+const LEADERBOARDS_PADDING: f32 = 16.0;
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Sends ranked solo scores to the server at `leaderboard_url` in
+/// `config.ron`, once the player has opted in from the pause menu, and shows
+/// its top 100 on a screen opened with L from the title. Requests run on the
+/// IO task pool, so a slow or missing server never holds up a frame.
+///
+/// The server is expected to accept `POST /scores` with a JSON body of
+/// `{ name, score, seed, replay_hash }`, and to answer `GET /scores?limit=N`
+/// with a JSON array of `{ name, score }`, best first.
+pub struct GlobalLeaderboardPlugin;
+
+impl Plugin for GlobalLeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        let url = app
+            .world()
+            .get_resource::<GameConfig>()
+            .and_then(|config| config.leaderboard_url.clone());
+        let url = match url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => {
+                info!("leaderboard_url is not set, the online leaderboard is disabled");
+                return;
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        app.insert_resource(LeaderboardServer {
+            url,
+            sender: Mutex::new(sender),
+            receiver: Mutex::new(receiver),
+        })
+        .init_resource::<GlobalScores>()
+        .add_systems(Update, receive_responses)
+        .add_systems(OnEnter(GameState::Title), spawn_leaderboards_hint)
+        .add_systems(Update, open_leaderboards.run_if(in_state(GameState::Title)))
+        .add_systems(
+            OnExit(GameState::GameOver),
+            submit_score.after(GameSystem::Initials),
+        )
+        .add_systems(
+            OnEnter(GameState::Leaderboards),
+            (fetch_scores, spawn_leaderboards),
+        )
+        .add_systems(
+            Update,
+            (
+                scroll_leaderboards,
+                update_leaderboards.after(scroll_leaderboards),
+            )
+                .run_if(in_state(GameState::Leaderboards)),
+        );
+    }
+}
+
+/// Where to send requests, and a channel for their tasks to answer on.
+#[derive(Resource)]
+struct LeaderboardServer {
+    url: String,
+    sender: Mutex<Sender<Response>>,
+    receiver: Mutex<Receiver<Response>>,
+}
+
+enum Response {
+    Submitted(Result<(), String>),
+    Scores(Result<Vec<GlobalScore>, String>),
+}
+
+#[derive(Serialize)]
+struct Submission {
+    name: String,
+    score: u32,
+    seed: u64,
+    /// Lets the server match the score to a replay uploaded later.
+    replay_hash: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct GlobalScore {
+    name: String,
+    score: u32,
+}
+
+/// The last fetched table, or `None` while a fetch is in flight.
+#[derive(Resource, Default)]
+struct GlobalScores {
+    scores: Option<Result<Vec<GlobalScore>, String>>,
+    scroll: usize,
+}
+
+#[derive(Component)]
+struct LeaderboardsText;
+
+impl LeaderboardServer {
+    /// Runs a blocking request on the IO task pool and sends back its result.
+    fn spawn(&self, request: impl FnOnce(&str) -> Response + Send + 'static) {
+        let url = self.url.clone();
+        let sender = self.sender.lock().unwrap().clone();
+        IoTaskPool::get()
+            .spawn(async move {
+                let _ = sender.send(request(&url));
+            })
+            .detach();
+    }
+}
+
+/// FNV-1a over the serialized frames. It only has to tell replays apart,
+/// not resist tampering.
+fn replay_hash(recorder: &ReplayRecorder) -> String {
+    let bytes = ron::to_string(recorder.frames()).unwrap_or_default();
+    let hash = bytes.bytes().fold(FNV_OFFSET, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    });
+    format!("{:016x}", hash)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn submit_score(
+    server: Res<LeaderboardServer>,
+    settings: Res<Settings>,
+    ranked: Res<Ranked>,
+    player_count: Res<PlayerCount>,
+    versus: Res<Versus>,
+    scoreboard: Res<Scoreboard>,
+    rng: Res<GameRng>,
+    recorder: Res<ReplayRecorder>,
+    leaderboard: Res<Leaderboard>,
+) {
+    if !settings.submit_scores || !ranked.0 || !versus::is_solo_run(&player_count, &versus) {
+        return;
+    }
+
+    let submission = Submission {
+        name: leaderboard.last_initials.clone(),
+        score: scoreboard.score as u32,
+        seed: rng.seed,
+        replay_hash: replay_hash(&recorder),
+    };
+    server.spawn(move |url| {
+        let result = ureq::post(&format!("{}/scores", url))
+            .send_json(&submission)
+            .map(|_| ())
+            .map_err(|err| err.to_string());
+        Response::Submitted(result)
+    });
+}
+
+fn fetch_scores(server: Res<LeaderboardServer>, mut scores: ResMut<GlobalScores>) {
+    *scores = GlobalScores::default();
+    server.spawn(|url| {
+        let result = ureq::get(&format!("{}/scores", url))
+            .query("limit", &TOP_SCORES.to_string())
+            .call()
+            .map_err(|err| err.to_string())
+            .and_then(|response| {
+                response
+                    .into_json::<Vec<GlobalScore>>()
+                    .map_err(|err| err.to_string())
+            });
+        Response::Scores(result)
+    });
+}
+
+fn receive_responses(server: Res<LeaderboardServer>, mut scores: ResMut<GlobalScores>) {
+    for response in server.receiver.lock().unwrap().try_iter() {
+        match response {
+            Response::Submitted(Ok(())) => info!("submitted score to the online leaderboard"),
+            Response::Submitted(Err(err)) => warn!("failed to submit score: {}", err),
+            Response::Scores(result) => {
+                if let Err(err) = &result {
+                    warn!("failed to fetch the online leaderboard: {}", err);
+                }
+                scores.scores = Some(result.map(|mut rows| {
+                    rows.truncate(TOP_SCORES);
+                    rows
+                }));
+            }
+        }
+    }
+}
+
+fn spawn_leaderboards_hint(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+) {
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                locale.text("leaderboards-hint"),
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: LEADERBOARDS_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(LEADERBOARDS_PADDING),
+                left: Val::Px(LEADERBOARDS_PADDING),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(LocalizedText("leaderboards-hint"))
+        .insert(StateScoped(GameState::Title));
+}
+
+fn open_leaderboards(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyL)
+        && states::try_change(&mut next_state, GameState::Leaderboards)
+    {
+        ev_menu.send(MenuSelectEvent);
+    }
+}
+
+fn spawn_leaderboards(mut commands: Commands, font: Res<TextFont>, settings: Res<Settings>) {
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: LEADERBOARDS_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(LEADERBOARDS_PADDING),
+                left: Val::Px(LEADERBOARDS_PADDING),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(LeaderboardsText)
+        .insert(StateScoped(GameState::Leaderboards));
+}
+
+/// Up and down scroll the table, Escape goes back to the title.
+fn scroll_leaderboards(
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut scores: ResMut<GlobalScores>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        keyboard_input.reset(KeyCode::Escape);
+        states::try_change(&mut next_state, GameState::Title);
+        return;
+    }
+
+    let rows = match &scores.scores {
+        Some(Ok(rows)) => rows.len(),
+        _ => return,
+    };
+    let max_scroll = rows.saturating_sub(VISIBLE_ROWS);
+    if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        scores.scroll = (scores.scroll + 1).min(max_scroll);
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        scores.scroll = scores.scroll.saturating_sub(1);
+    }
+}
+
+fn update_leaderboards(
+    scores: Res<GlobalScores>,
+    locale: Res<Locale>,
+    mut texts: Query<&mut Text, With<LeaderboardsText>>,
+) {
+    let mut value = format!("{}\n\n", locale.text("leaderboards-title"));
+    match &scores.scores {
+        None => value.push_str(&locale.text("leaderboards-loading")),
+        Some(Err(_)) => value.push_str(&locale.text("leaderboards-unreachable")),
+        Some(Ok(rows)) if rows.is_empty() => value.push_str(&locale.text("leaderboards-empty")),
+        Some(Ok(rows)) => {
+            for (rank, row) in rows
+                .iter()
+                .enumerate()
+                .skip(scores.scroll)
+                .take(VISIBLE_ROWS)
+            {
+                value.push_str(&format!("{:>3}. {} {}\n", rank + 1, row.name, row.score));
+            }
+        }
+    }
+    value.push_str(&format!("\n\n{}", locale.text("leaderboards-help")));
+
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use bevy::{
+    asset::AssetPlugin,
+    input::{InputPlugin, InputSystem},
+    prelude::*,
+    state::app::StatesPlugin,
+    time::{TimeSystem, TimeUpdateStrategy},
+};
+
+use crate::{
+    movement::FIXED_STEP,
+    replay::{self, Replay, ReplayFrame},
+    rng::RunSeed,
+    save::Save,
+    settings::Settings,
+    shop::Upgrades,
+    snapshot::{self, RunSnapshot},
+    states::{GameState, RunState},
+    SimulationPlugins, SpriteSheet,
+};
+
+/// Builds an app that runs the simulation without a window, renderer or audio
+/// device. Each call to `App::update` advances exactly one frame of
+/// [`FIXED_STEP`] seconds, and keyboard input comes from the given script, so
+/// the same seed and script always play out the same run.
+pub fn build_app(seed: u64, script: InputScript) -> App {
+    build_app_with_save(seed, script, Save::default())
+}
+
+/// Like [`build_app`], but settings are loaded from and saved to `save`
+/// rather than the usual place.
+pub fn build_app_with_save(seed: u64, script: InputScript, save: Save) -> App {
+    let mut app = App::new();
+    app.insert_resource(save)
+        .add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            InputPlugin,
+            StatesPlugin,
+        ))
+        // There is no renderer to register images, but the player's shadow
+        // still loads one.
+        .init_asset::<Image>()
+        .insert_resource(SpriteSheet {
+            texture: Handle::default(),
+            layout: Handle::default(),
+        })
+        .insert_resource(RunSeed(Some(seed)))
+        .insert_resource(script)
+        .add_plugins((SimulationPlugins, HeadlessPlugin));
+    app
+}
+
+/// Builds an app that plays back a recorded run, with the settings and
+/// upgrades it was played with. The run is started on the first frame and
+/// put back the way the replay starts, and while it is `Playing` each frame
+/// takes as long, and holds the same keys, as it did when it was recorded.
+pub fn build_replay_app(replay: Replay) -> App {
+    let start = if replay.player_count == 2 {
+        KeyCode::Digit2
+    } else {
+        KeyCode::Space
+    };
+    let script = InputScript::new(vec![
+        ScriptedInput {
+            frame: 0,
+            key: start,
+            pressed: true,
+        },
+        ScriptedInput {
+            frame: 1,
+            key: start,
+            pressed: false,
+        },
+    ]);
+
+    let mut app = build_app(replay.seed, script);
+    // Nothing has run yet, so the changed settings aren't saved.
+    replay.apply_settings(&mut app.world_mut().resource_mut::<Settings>());
+    app.insert_resource(Upgrades {
+        active: replay.upgrades,
+        ..default()
+    });
+    app.insert_resource(ReplayPlayback {
+        start: replay.start,
+        frames: replay.frames,
+        next: 0,
+        current: None,
+    });
+    app
+}
+
+struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(fixed_step()))
+            .add_systems(First, step_time.before(TimeSystem))
+            .add_systems(PreUpdate, (play_script, play_replay).after(InputSystem))
+            .add_systems(PostUpdate, start_replay);
+    }
+}
+
+/// Key presses and releases to replay, each on a given frame.
+#[derive(Resource, Default)]
+pub struct InputScript {
+    steps: Vec<ScriptedInput>,
+    frame: u32,
+}
+
+impl InputScript {
+    pub fn new(steps: Vec<ScriptedInput>) -> Self {
+        Self { steps, frame: 0 }
+    }
+}
+
+pub struct ScriptedInput {
+    pub frame: u32,
+    pub key: KeyCode,
+    pub pressed: bool,
+}
+
+/// How far the clock is moved every frame, instead of by the wall clock,
+/// which would make runs depend on how fast the machine is. The same
+/// duration as the fixed timestep, so each frame runs it exactly once.
+fn fixed_step() -> Duration {
+    Time::<Fixed>::from_seconds(FIXED_STEP as f64).timestep()
+}
+
+#[derive(Resource)]
+struct ReplayPlayback {
+    /// Put over the run once it has started, if the replay doesn't start
+    /// with it.
+    start: Option<RunSnapshot>,
+    frames: Vec<ReplayFrame>,
+    next: usize,
+    /// The recorded frame being played this frame, if any.
+    current: Option<ReplayFrame>,
+}
+
+/// Checks the state at the same point the recording did, so both agree on
+/// which frames were `Playing`.
+fn step_time(
+    mut strategy: ResMut<TimeUpdateStrategy>,
+    run_state: Option<Res<State<RunState>>>,
+    playback: Option<ResMut<ReplayPlayback>>,
+) {
+    let mut step = fixed_step();
+
+    if let Some(mut playback) = playback {
+        playback.current = None;
+        if run_state.is_some_and(|state| state.get() == &RunState::Running) {
+            playback.current = playback.frames.get(playback.next).cloned();
+            playback.next += 1;
+        }
+
+        if let Some(frame) = &playback.current {
+            step = Duration::from_nanos(frame.delta_nanos);
+        }
+    }
+
+    *strategy = TimeUpdateStrategy::ManualDuration(step);
+}
+
+/// Frames are counted from the end of loading, which takes a varying time.
+fn play_script(
+    state: Res<State<GameState>>,
+    mut script: ResMut<InputScript>,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+) {
+    if state.get() == &GameState::Loading {
+        return;
+    }
+
+    let frame = script.frame;
+    for step in script.steps.iter().filter(|step| step.frame == frame) {
+        if step.pressed {
+            keyboard_input.press(step.key);
+        } else {
+            keyboard_input.release(step.key);
+        }
+    }
+
+    script.frame += 1;
+}
+
+/// Does what the replay plugin does for a watched replay that starts in the
+/// middle of a run.
+fn start_replay(world: &mut World) {
+    let pending = world
+        .get_resource::<ReplayPlayback>()
+        .is_some_and(|playback| playback.start.is_some());
+    if !pending || !snapshot::run_started(world) {
+        return;
+    }
+
+    let start = world.resource_mut::<ReplayPlayback>().start.take();
+    if let Some(start) = start {
+        start.restore(world);
+    }
+}
+
+fn play_replay(
+    playback: Option<Res<ReplayPlayback>>,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+) {
+    let frame = match playback
+        .as_ref()
+        .and_then(|playback| playback.current.as_ref())
+    {
+        Some(frame) => frame,
+        None => return,
+    };
+
+    replay::press_frame_keys(frame, &mut keyboard_input);
+}
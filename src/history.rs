@@ -0,0 +1,385 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    locale::Locale,
+    player::{PlayerCount, MAX_PLAYERS},
+    replay::{Replay, ReplayRecorder, ReplaySavedEvent, WatchedReplay},
+    rng::{GameRng, RunSeed},
+    save::Save,
+    score::Scoreboard,
+    settings::Settings,
+    shop::Upgrades,
+    states::{self, GameState, MenuSelectEvent},
+    suspend::ResumedRun,
+    ui::TextFont,
+    versus::Versus,
+};
+
+const HISTORY_KEY: &str = "history.ron";
+const HISTORY_SIZE: usize = 50;
+const VISIBLE_RUNS: usize = 12;
+const HISTORY_FONT_SIZE: f32 = 16.0;
+const HISTORY_PADDING: f32 = 16.0;
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Remembers the last 50 runs, and lists them on a screen opened with H from
+/// the title. Runs whose replay was saved from the game over screen can be
+/// watched again from there with Enter.
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        let history = RunHistory::load(&app.world_mut().get_resource_or_insert_with(Save::default));
+        app.insert_resource(history)
+            .init_resource::<HistorySelection>()
+            .add_systems(Update, open_history.run_if(in_state(GameState::Title)))
+            .add_systems(OnEnter(GameState::GameOver), record_run)
+            .add_systems(
+                Update,
+                link_saved_replay.run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(OnEnter(GameState::History), spawn_history)
+            .add_systems(
+                Update,
+                (browse_history, update_history.after(browse_history))
+                    .run_if(in_state(GameState::History)),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct RunHistory {
+    /// Newest first.
+    pub runs: Vec<RunRecord>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RunRecord {
+    /// When the run ended, in seconds since the Unix epoch. Browsers don't
+    /// give us a clock, so web builds leave it at 0.
+    pub date: u64,
+    pub score: u32,
+    /// Seconds spent `Playing`.
+    pub duration: f32,
+    pub mode: RunMode,
+    pub seed: u64,
+    /// The save key of the run's replay, once one is saved.
+    #[serde(default)]
+    pub replay: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RunMode {
+    Solo,
+    CoOp,
+    Versus,
+}
+
+impl RunMode {
+    /// The message id of its name.
+    pub fn name_id(&self) -> &'static str {
+        match self {
+            RunMode::Solo => "run-mode-solo",
+            RunMode::CoOp => "run-mode-co-op",
+            RunMode::Versus => "run-mode-versus",
+        }
+    }
+}
+
+impl RunHistory {
+    pub fn load(save: &Save) -> Self {
+        save.0
+            .load(HISTORY_KEY)
+            .ok()
+            .flatten()
+            .and_then(|bytes| ron::de::from_bytes(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, save: &Save) {
+        let result = ron::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                save.0
+                    .store(HISTORY_KEY, contents.as_bytes())
+                    .map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result {
+            warn!("failed to save run history: {}", err);
+        }
+    }
+}
+
+/// Which run the history screen has selected, and the first one shown.
+#[derive(Resource, Default)]
+struct HistorySelection {
+    selected: usize,
+    scroll: usize,
+}
+
+#[derive(Component)]
+struct HistoryText;
+
+/// Seconds since the Unix epoch, or 0 where there is no clock to read.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> u64 {
+    0
+}
+
+/// Formats seconds since the Unix epoch as a UTC date, using the days to
+/// civil date conversion from Howard Hinnant's date algorithms.
+pub fn format_date(date: u64) -> String {
+    if date == 0 {
+        return "----------".to_string();
+    }
+
+    let days = (date / SECONDS_PER_DAY) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Watched replays aren't new runs, and spectators have no run of their own.
+/// A resumed run counts the time played before it was suspended too.
+#[allow(clippy::too_many_arguments)]
+fn record_run(
+    scoreboard: Res<Scoreboard>,
+    recorder: Res<ReplayRecorder>,
+    rng: Res<GameRng>,
+    player_count: Res<PlayerCount>,
+    versus: Res<Versus>,
+    watched: Option<Res<WatchedReplay>>,
+    resumed: Option<Res<ResumedRun>>,
+    save: Res<Save>,
+    mut history: ResMut<RunHistory>,
+) {
+    if watched.is_some() || player_count.0 == 0 {
+        return;
+    }
+
+    let mode = if versus.active {
+        RunMode::Versus
+    } else if player_count.0 > 1 {
+        RunMode::CoOp
+    } else {
+        RunMode::Solo
+    };
+    let earlier = resumed.map_or(0.0, |resumed| resumed.elapsed);
+
+    history.runs.insert(
+        0,
+        RunRecord {
+            date: now(),
+            score: scoreboard.score as u32,
+            duration: earlier + recorder.duration(),
+            mode,
+            seed: rng.seed,
+            replay: None,
+        },
+    );
+    history.runs.truncate(HISTORY_SIZE);
+    history.save(&save);
+}
+
+/// Replays are saved by seed, so a saved one belongs to the latest run when
+/// the seeds match.
+fn link_saved_replay(
+    mut ev_saved: EventReader<ReplaySavedEvent>,
+    save: Res<Save>,
+    mut history: ResMut<RunHistory>,
+) {
+    for event in ev_saved.read() {
+        if let Some(run) = history
+            .runs
+            .first_mut()
+            .filter(|run| run.seed == event.seed)
+        {
+            run.replay = Some(event.key.clone());
+            history.save(&save);
+        }
+    }
+}
+
+fn open_history(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyH)
+        && states::try_change(&mut next_state, GameState::History)
+    {
+        ev_menu.send(MenuSelectEvent);
+    }
+}
+
+fn spawn_history(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    mut selection: ResMut<HistorySelection>,
+) {
+    *selection = HistorySelection::default();
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: HISTORY_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(HISTORY_PADDING),
+                left: Val::Px(HISTORY_PADDING),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(HistoryText)
+        .insert(StateScoped(GameState::History));
+}
+
+/// Up and down pick a run, Enter watches its replay and Escape goes back to
+/// the title.
+#[allow(clippy::too_many_arguments)]
+fn browse_history(
+    mut commands: Commands,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    history: Res<RunHistory>,
+    save: Res<Save>,
+    mut selection: ResMut<HistorySelection>,
+    mut run_seed: ResMut<RunSeed>,
+    mut settings: ResMut<Settings>,
+    mut upgrades: ResMut<Upgrades>,
+    mut player_count: ResMut<PlayerCount>,
+    mut versus: ResMut<Versus>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        keyboard_input.reset(KeyCode::Escape);
+        states::try_change(&mut next_state, GameState::Title);
+        return;
+    }
+
+    let last = history.runs.len().saturating_sub(1);
+    if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        selection.selected = (selection.selected + 1).min(last);
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        selection.selected = selection.selected.saturating_sub(1);
+    }
+    if selection.selected < selection.scroll {
+        selection.scroll = selection.selected;
+    } else if selection.selected >= selection.scroll + VISIBLE_RUNS {
+        selection.scroll = selection.selected + 1 - VISIBLE_RUNS;
+    }
+
+    if !keyboard_input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+    let key = match history
+        .runs
+        .get(selection.selected)
+        .and_then(|run| run.replay.as_ref())
+    {
+        Some(key) => key,
+        None => return,
+    };
+
+    let result = save
+        .0
+        .load(key)
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| bytes.ok_or_else(|| "it no longer exists".to_string()))
+        .and_then(|bytes| ron::de::from_bytes::<Replay>(&bytes).map_err(|err| err.to_string()));
+    let replay = match result {
+        Ok(replay) => replay,
+        Err(err) => {
+            warn!("failed to load replay {}: {}", key, err);
+            return;
+        }
+    };
+
+    keyboard_input.reset(KeyCode::Enter);
+    if states::try_change(&mut next_state, GameState::Playing) {
+        player_count.0 = replay.player_count.clamp(1, MAX_PLAYERS);
+        commands.insert_resource(WatchedReplay::new(
+            replay,
+            &mut run_seed,
+            &mut settings,
+            &mut upgrades,
+        ));
+        versus.active = false;
+        ev_menu.send(MenuSelectEvent);
+    }
+}
+
+fn update_history(
+    history: Res<RunHistory>,
+    selection: Res<HistorySelection>,
+    locale: Res<Locale>,
+    mut texts: Query<&mut Text, With<HistoryText>>,
+) {
+    let mut value = format!("{}\n\n", locale.text("history-title"));
+    if history.runs.is_empty() {
+        value.push_str(&locale.text("history-empty"));
+    }
+    for (index, run) in history
+        .runs
+        .iter()
+        .enumerate()
+        .skip(selection.scroll)
+        .take(VISIBLE_RUNS)
+    {
+        value.push_str(&format!(
+            "{} {}  {:<6} {:>6}  {:>6.1}s  {}{}\n",
+            if index == selection.selected {
+                ">"
+            } else {
+                " "
+            },
+            format_date(run.date),
+            locale.text(run.mode.name_id()),
+            run.score,
+            run.duration,
+            // Passed as a string, since Fluent numbers are floats and would
+            // round large seeds.
+            locale.format("history-seed", &[("seed", run.seed.to_string().into())]),
+            if run.replay.is_some() {
+                format!("  {}", locale.text("history-replay"))
+            } else {
+                String::new()
+            },
+        ));
+    }
+    value.push_str(&format!("\n\n{}", locale.text("history-help")));
+
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
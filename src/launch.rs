@@ -0,0 +1,331 @@
+use bevy::{prelude::*, window::WindowMode};
+
+#[cfg(feature = "online")]
+use crate::online::{OnlineOptions, OnlineRole};
+use crate::{
+    config::GameConfig,
+    player::{DeathTimer, PlayerCount},
+    profiles,
+    rng::RunSeed,
+    score::Ranked,
+    settings::Settings,
+    states::{self, GameState, RunState},
+    time_scale::TimeScale,
+    versus::Versus,
+    GamePlugins,
+};
+
+pub const USAGE: &str = "usage: dodger [--seed <number>] [--fullscreen] [--mute] [--skip-title] \
+     [--mode <solo|coop|versus|timed>] [--portrait] [--profile <name>] \
+     [--online <port> <peer address> [--host [--spectator <address>]...]] \
+     [--spectate <port> <host address>]";
+/// How long a run lasts with `--mode timed`.
+pub const TIMED_RUN_SECONDS: f32 = 120.0;
+/// How long the players take to shrink away once the time is up.
+const TIME_UP_DELAY: f32 = 1.0;
+
+pub struct LaunchPlugin;
+
+impl Plugin for LaunchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LaunchOptions>()
+            .init_resource::<TimedRun>()
+            .add_systems(PreStartup, use_portrait_playfield)
+            .add_systems(Startup, apply_launch_mute)
+            .add_systems(Update, skip_title.run_if(in_state(GameState::Title)))
+            .add_systems(OnEnter(GameState::Playing), start_timed_run)
+            .add_systems(Update, end_timed_run.run_if(in_state(RunState::Running)));
+    }
+}
+
+/// Options given on the command line. The binary applies the seed and
+/// window size before building the app; the rest is read while running.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LaunchOptions {
+    pub seed: Option<u64>,
+    pub fullscreen: bool,
+    pub mute: bool,
+    /// Starts the first run straight away, in `mode`. Cleared once it has.
+    pub skip_title: bool,
+    pub mode: LaunchMode,
+    /// Turns the window and playfield on their side, for phones.
+    pub portrait: bool,
+    /// The profile to play as, created if it doesn't exist yet.
+    pub profile: Option<String>,
+    #[cfg(feature = "online")]
+    pub online: Option<OnlineOptions>,
+}
+
+/// The kind of run `--skip-title` starts. Timed runs are solo runs that end
+/// after [`TIMED_RUN_SECONDS`], and with `--mode timed` every run of the
+/// session is one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LaunchMode {
+    #[default]
+    Solo,
+    CoOp,
+    Versus,
+    Timed,
+}
+
+impl LaunchOptions {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+        #[cfg(feature = "online")]
+        let (mut host, mut spectators) = (false, Vec::new());
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let seed = args.next().ok_or("--seed needs a number")?;
+                    let seed = seed
+                        .parse()
+                        .map_err(|_| format!("invalid seed '{}'", seed))?;
+                    options.seed = Some(seed);
+                }
+                "--fullscreen" => options.fullscreen = true,
+                "--mute" => options.mute = true,
+                "--skip-title" => options.skip_title = true,
+                "--mode" => {
+                    let mode = args.next().ok_or("--mode needs a mode")?;
+                    options.mode = match mode.as_str() {
+                        "solo" => LaunchMode::Solo,
+                        "coop" => LaunchMode::CoOp,
+                        "versus" => LaunchMode::Versus,
+                        "timed" => LaunchMode::Timed,
+                        _ => return Err(format!("unknown mode '{}'", mode)),
+                    };
+                }
+                "--portrait" => options.portrait = true,
+                "--profile" => {
+                    let name = args.next().ok_or("--profile needs a name")?;
+                    profiles::check_name(&name)
+                        .map_err(|err| format!("invalid profile '{}': {}", name, err))?;
+                    options.profile = Some(name);
+                }
+                #[cfg(feature = "online")]
+                "--online" | "--spectate" => {
+                    let port = args.next().ok_or_else(|| format!("{} needs a port", arg))?;
+                    let port = port
+                        .parse()
+                        .map_err(|_| format!("invalid port '{}'", port))?;
+                    let peer = args
+                        .next()
+                        .ok_or_else(|| format!("{} needs an address", arg))?;
+                    let peer = peer
+                        .parse()
+                        .map_err(|_| format!("invalid address '{}'", peer))?;
+                    let role = if arg == "--spectate" {
+                        OnlineRole::Spectator
+                    } else {
+                        OnlineRole::Guest
+                    };
+                    options.online = Some(OnlineOptions { port, peer, role });
+                }
+                #[cfg(feature = "online")]
+                "--host" => host = true,
+                #[cfg(feature = "online")]
+                "--spectator" => {
+                    let spectator = args.next().ok_or("--spectator needs an address")?;
+                    let spectator = spectator
+                        .parse()
+                        .map_err(|_| format!("invalid address '{}'", spectator))?;
+                    spectators.push(spectator);
+                }
+                _ => return Err(format!("unknown option '{}'", arg)),
+            }
+        }
+
+        #[cfg(feature = "online")]
+        if let Some(online) = &mut options.online {
+            // Both players need the same enemies, so the seed can't be random.
+            if options.seed.is_none() {
+                return Err("--online needs a --seed shared with the other player".to_string());
+            }
+            if host && matches!(online.role, OnlineRole::Guest) {
+                online.role = OnlineRole::Host { spectators };
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Builds and runs the full game with the given options. Shared by the
+/// desktop binary and the mobile entry point.
+pub fn run(options: LaunchOptions) {
+    let (width, height): (f32, f32) = if options.portrait {
+        (480.0, 640.0)
+    } else {
+        (640.0, 480.0)
+    };
+
+    let window = Window {
+        title: "Dodger".to_string(),
+        resolution: (width, height).into(),
+        mode: if options.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        },
+        #[cfg(target_arch = "wasm32")]
+        canvas: Some("#bevy".to_string()),
+        ..default()
+    };
+    App::new()
+        .insert_resource(RunSeed(options.seed))
+        .insert_resource(options.clone())
+        .add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(window),
+                    ..default()
+                })
+                // Lets `config.ron` and the enemy tables be edited while the
+                // game runs.
+                .set(AssetPlugin {
+                    watch_for_changes_override: Some(cfg!(feature = "desktop")),
+                    ..default()
+                }),
+        )
+        .add_plugins(GamePlugins)
+        .run();
+}
+
+/// Swaps the playfield's width and height before anything is spawned in it.
+fn use_portrait_playfield(options: Res<LaunchOptions>, mut config: ResMut<GameConfig>) {
+    if options.portrait {
+        let config = &mut *config;
+        std::mem::swap(&mut config.screen_x, &mut config.screen_y);
+    }
+}
+
+/// `--mute` mutes the game the same way the mute key does.
+fn apply_launch_mute(options: Res<LaunchOptions>, mut settings: ResMut<Settings>) {
+    if options.mute {
+        settings.muted = true;
+    }
+}
+
+/// Only the first visit to the title screen is skipped, so going back to it
+/// after a run stays possible. Versus mode only exists with a window.
+fn skip_title(
+    mut options: ResMut<LaunchOptions>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut player_count: ResMut<PlayerCount>,
+    versus: Option<ResMut<Versus>>,
+) {
+    if !options.skip_title || !states::try_change(&mut next_state, GameState::Playing) {
+        return;
+    }
+    options.skip_title = false;
+
+    player_count.0 = if options.mode == LaunchMode::CoOp {
+        2
+    } else {
+        1
+    };
+    if let Some(mut versus) = versus {
+        *versus = Versus::default();
+        versus.active = options.mode == LaunchMode::Versus;
+    }
+}
+
+/// Time left in a run started with `--mode timed`.
+#[derive(Resource, Default)]
+struct TimedRun(Option<Timer>);
+
+fn start_timed_run(options: Res<LaunchOptions>, mut timed_run: ResMut<TimedRun>) {
+    timed_run.0 = (options.mode == LaunchMode::Timed)
+        .then(|| Timer::from_seconds(TIMED_RUN_SECONDS, TimerMode::Once));
+}
+
+/// Ends a timed run once its time is up, the same way as when the last
+/// player is hit. Timed runs are shorter than the others, so their scores
+/// don't go on the leaderboards.
+fn end_timed_run(
+    mut commands: Commands,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut timed_run: ResMut<TimedRun>,
+    mut ranked: ResMut<Ranked>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(timer) = &mut timed_run.0 else {
+        return;
+    };
+    ranked.0 = false;
+
+    if timer.tick(time_scale.delta(&time)).just_finished()
+        && states::try_change(&mut next_state, GameState::Dying)
+    {
+        commands.insert_resource(DeathTimer(Timer::from_seconds(
+            TIME_UP_DELAY,
+            TimerMode::Once,
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<LaunchOptions, String> {
+        LaunchOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_are_the_defaults() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.seed, None);
+        assert!(!options.fullscreen && !options.mute && !options.skip_title);
+        assert_eq!(options.mode, LaunchMode::Solo);
+    }
+
+    #[test]
+    fn parses_every_flag() {
+        let options = parse(&[
+            "--seed",
+            "42",
+            "--fullscreen",
+            "--mute",
+            "--skip-title",
+            "--mode",
+            "timed",
+            "--portrait",
+            "--profile",
+            "speedrun",
+        ])
+        .unwrap();
+
+        assert_eq!(options.seed, Some(42));
+        assert!(options.fullscreen && options.mute && options.skip_title && options.portrait);
+        assert_eq!(options.mode, LaunchMode::Timed);
+        assert_eq!(options.profile.as_deref(), Some("speedrun"));
+    }
+
+    #[test]
+    fn parses_every_mode() {
+        for (name, mode) in [
+            ("solo", LaunchMode::Solo),
+            ("coop", LaunchMode::CoOp),
+            ("versus", LaunchMode::Versus),
+            ("timed", LaunchMode::Timed),
+        ] {
+            assert_eq!(parse(&["--mode", name]).unwrap().mode, mode);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--seed", "soon"]).is_err());
+        assert!(parse(&["--mode"]).is_err());
+        assert!(parse(&["--mode", "endless"]).is_err());
+        assert!(parse(&["--profile"]).is_err());
+        assert!(parse(&["--profile", "../speedrun"]).is_err());
+        assert!(parse(&["--profile", "a very long name"]).is_err());
+        assert!(parse(&["--speed"]).is_err());
+    }
+}
@@ -0,0 +1,409 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    player::PlayerCount,
+    save::Save,
+    score::{Ranked, Scoreboard},
+    settings::Settings,
+    states::GameState,
+    ui::TextFont,
+    versus::{self, Versus},
+    GameSystem,
+};
+
+const LEADERBOARD_KEY: &str = "leaderboard.ron";
+const LEADERBOARD_SIZE: usize = 10;
+const INITIALS_LEN: usize = 3;
+const LEADERBOARD_FONT_SIZE: f32 = 16.0;
+const LEADERBOARD_PADDING: f32 = 16.0;
+/// Seconds to wait after the last change before saving, so picking initials
+/// doesn't write the file on every key press.
+const SAVE_DELAY: f32 = 0.5;
+
+/// The ten best ranked solo scores on this machine, shown on the title and
+/// game over screens. A run that makes the table asks for initials the
+/// arcade way: up and down pick a letter, left and right move between
+/// them, and Enter confirms. Leaving the screen keeps whatever was picked.
+///
+/// The table is saved shortly after every change, so a new score is kept
+/// even if the game is closed or crashes before the initials are done.
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        let leaderboard =
+            Leaderboard::load(&app.world_mut().get_resource_or_insert_with(Save::default));
+        app.insert_resource(leaderboard)
+            .add_systems(Update, save_leaderboard)
+            .add_systems(OnEnter(GameState::Title), spawn_table)
+            .add_systems(Update, update_table.run_if(in_state(GameState::Title)))
+            .add_systems(OnEnter(GameState::GameOver), (start_initials, spawn_table))
+            .add_systems(
+                Update,
+                (
+                    enter_initials.in_set(GameSystem::Initials),
+                    update_table.after(enter_initials),
+                )
+                    .run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(
+                OnExit(GameState::GameOver),
+                finish_initials.in_set(GameSystem::Initials),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Debug, Deserialize, Serialize)]
+pub struct Leaderboard {
+    /// Best first.
+    pub entries: Vec<LeaderboardEntry>,
+    /// The initials entered most recently, which new entries start from.
+    #[serde(default = "default_initials")]
+    pub last_initials: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LeaderboardEntry {
+    pub initials: String,
+    pub score: u32,
+}
+
+impl Default for Leaderboard {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            last_initials: default_initials(),
+        }
+    }
+}
+
+fn default_initials() -> String {
+    "A".repeat(INITIALS_LEN)
+}
+
+/// Makes initials from a hand-edited file safe to edit a letter at a time:
+/// exactly [`INITIALS_LEN`] uppercase ASCII letters, anything else dropped
+/// and the rest padded with `A`.
+fn normalize_initials(initials: &str) -> String {
+    initials
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|letter| letter.to_ascii_uppercase())
+        .chain(std::iter::repeat('A'))
+        .take(INITIALS_LEN)
+        .collect()
+}
+
+impl Leaderboard {
+    pub fn load(save: &Save) -> Self {
+        let mut leaderboard: Self = save
+            .0
+            .load(LEADERBOARD_KEY)
+            .ok()
+            .flatten()
+            .and_then(|bytes| ron::de::from_bytes(&bytes).ok())
+            .unwrap_or_default();
+
+        leaderboard.last_initials = normalize_initials(&leaderboard.last_initials);
+        for entry in &mut leaderboard.entries {
+            entry.initials = normalize_initials(&entry.initials);
+        }
+        // Stable, so equal scores keep the order they were set in.
+        leaderboard
+            .entries
+            .sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        leaderboard.entries.truncate(LEADERBOARD_SIZE);
+        leaderboard
+    }
+
+    fn save(&self, save: &Save) {
+        let result = ron::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                save.0
+                    .store(LEADERBOARD_KEY, contents.as_bytes())
+                    .map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result {
+            warn!("failed to save leaderboard: {}", err);
+        }
+    }
+
+    /// Adds a score if it makes the table, below any equal scores, and
+    /// returns where it went.
+    fn insert(&mut self, score: u32) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .position(|entry| entry.score < score)
+            .unwrap_or(self.entries.len());
+        if rank >= LEADERBOARD_SIZE {
+            return None;
+        }
+
+        self.entries.insert(
+            rank,
+            LeaderboardEntry {
+                initials: self.last_initials.clone(),
+                score,
+            },
+        );
+        self.entries.truncate(LEADERBOARD_SIZE);
+        Some(rank)
+    }
+}
+
+/// The entry whose initials are being picked, and which letter is selected.
+#[derive(Resource)]
+struct InitialsEntry {
+    rank: usize,
+    cursor: usize,
+}
+
+#[derive(Component)]
+struct LeaderboardText;
+
+fn start_initials(
+    mut commands: Commands,
+    scoreboard: Res<Scoreboard>,
+    ranked: Res<Ranked>,
+    player_count: Res<PlayerCount>,
+    versus: Res<Versus>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    if !ranked.0 || !versus::is_solo_run(&player_count, &versus) {
+        return;
+    }
+
+    if let Some(rank) = leaderboard.insert(scoreboard.score as u32) {
+        commands.insert_resource(InitialsEntry { rank, cursor: 0 });
+    }
+}
+
+fn spawn_table(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    state: Res<State<GameState>>,
+) {
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: LEADERBOARD_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(LEADERBOARD_PADDING),
+                right: Val::Px(LEADERBOARD_PADDING),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(LeaderboardText)
+        .insert(StateScoped(state.get().clone()));
+}
+
+fn enter_initials(
+    mut commands: Commands,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    entry: Option<ResMut<InitialsEntry>>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    let mut entry = match entry {
+        Some(entry) => entry,
+        None => return,
+    };
+
+    let pressed = |key| keyboard_input.just_pressed(key);
+    let (confirm, left, right, up, down) = (
+        pressed(KeyCode::Enter),
+        pressed(KeyCode::ArrowLeft),
+        pressed(KeyCode::ArrowRight),
+        pressed(KeyCode::ArrowUp),
+        pressed(KeyCode::ArrowDown),
+    );
+    // Don't let the same presses start the next run or reach a menu.
+    for key in [
+        KeyCode::Enter,
+        KeyCode::ArrowLeft,
+        KeyCode::ArrowRight,
+        KeyCode::ArrowUp,
+        KeyCode::ArrowDown,
+    ] {
+        keyboard_input.reset(key);
+    }
+
+    if confirm {
+        leaderboard.last_initials = leaderboard.entries[entry.rank].initials.clone();
+        commands.remove_resource::<InitialsEntry>();
+        return;
+    }
+
+    if left {
+        entry.cursor = (entry.cursor + INITIALS_LEN - 1) % INITIALS_LEN;
+    }
+    if right {
+        entry.cursor = (entry.cursor + 1) % INITIALS_LEN;
+    }
+
+    let step = match (up, down) {
+        (true, false) => 1,
+        (false, true) => 25,
+        _ => return,
+    };
+
+    let initials = &mut leaderboard.entries[entry.rank].initials;
+    let mut letters = initials.clone().into_bytes();
+    let letter = &mut letters[entry.cursor];
+    *letter = b'A' + (letter.saturating_sub(b'A') + step) % 26;
+    *initials = String::from_utf8(letters).expect("initials are ASCII letters");
+}
+
+fn update_table(
+    leaderboard: Res<Leaderboard>,
+    entry: Option<Res<InitialsEntry>>,
+    mut query: Query<&mut Text, With<LeaderboardText>>,
+) {
+    let entry = entry.as_deref();
+    let rows: Vec<String> = leaderboard
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(rank, row)| {
+            let initials = match entry {
+                Some(entry) if entry.rank == rank => row
+                    .initials
+                    .chars()
+                    .enumerate()
+                    .map(|(index, letter)| {
+                        if index == entry.cursor {
+                            format!("[{}]", letter)
+                        } else {
+                            letter.to_string()
+                        }
+                    })
+                    .collect(),
+                _ => row.initials.clone(),
+            };
+            format!("{:>2}. {} {}", rank + 1, initials, row.score)
+        })
+        .collect();
+    let value = rows.join("\n");
+
+    for mut text in query.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+fn finish_initials(
+    mut commands: Commands,
+    entry: Option<Res<InitialsEntry>>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    if let Some(entry) = entry {
+        leaderboard.last_initials = leaderboard.entries[entry.rank].initials.clone();
+        commands.remove_resource::<InitialsEntry>();
+    }
+}
+
+/// Saves once the table has gone `SAVE_DELAY` seconds without changing.
+fn save_leaderboard(
+    time: Res<Time>,
+    save: Res<Save>,
+    leaderboard: Res<Leaderboard>,
+    mut pending: Local<Option<Timer>>,
+) {
+    if leaderboard.is_changed() && !leaderboard.is_added() {
+        *pending = Some(Timer::from_seconds(SAVE_DELAY, TimerMode::Once));
+    }
+
+    let finished = match pending.as_mut() {
+        Some(timer) => timer.tick(time.delta()).finished(),
+        None => false,
+    };
+    if finished {
+        leaderboard.save(&save);
+        *pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save::MemoryBackend;
+
+    fn scores(leaderboard: &Leaderboard) -> Vec<u32> {
+        leaderboard
+            .entries
+            .iter()
+            .map(|entry| entry.score)
+            .collect()
+    }
+
+    #[test]
+    fn insert_keeps_best_first_and_ties_in_order() {
+        let mut leaderboard = Leaderboard::default();
+        assert_eq!(leaderboard.insert(20), Some(0));
+        assert_eq!(leaderboard.insert(30), Some(0));
+        assert_eq!(leaderboard.insert(10), Some(2));
+
+        leaderboard.last_initials = "ZZZ".to_string();
+        // An equal score goes below the one already there.
+        assert_eq!(leaderboard.insert(20), Some(2));
+        assert_eq!(scores(&leaderboard), [30, 20, 20, 10]);
+        assert_eq!(leaderboard.entries[1].initials, "AAA");
+        assert_eq!(leaderboard.entries[2].initials, "ZZZ");
+    }
+
+    #[test]
+    fn insert_truncates_to_the_table_size() {
+        let mut leaderboard = Leaderboard::default();
+        for score in 1..=LEADERBOARD_SIZE as u32 {
+            leaderboard.insert(score * 10);
+        }
+
+        assert_eq!(leaderboard.insert(5), None);
+        // Ties with the last entry don't make it either.
+        assert_eq!(leaderboard.insert(10), None);
+        assert_eq!(leaderboard.insert(15), Some(LEADERBOARD_SIZE - 1));
+        assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+        assert_eq!(leaderboard.entries.last().unwrap().score, 15);
+    }
+
+    #[test]
+    fn normalize_initials_keeps_three_uppercase_letters() {
+        assert_eq!(normalize_initials("abc"), "ABC");
+        assert_eq!(normalize_initials("a1-b"), "ABA");
+        assert_eq!(normalize_initials(""), "AAA");
+        assert_eq!(normalize_initials("LONGER"), "LON");
+        assert_eq!(normalize_initials("é?z"), "ZAA");
+    }
+
+    #[test]
+    fn load_sorts_truncates_and_normalizes_an_edited_file() {
+        let save = Save(Box::new(MemoryBackend::default()));
+        let entries: Vec<String> = (1..=LEADERBOARD_SIZE + 2)
+            .map(|score| format!("(initials: \"x\", score: {})", score))
+            .collect();
+        let contents = format!("(entries: [{}], last_initials: \"q\")", entries.join(", "));
+        save.0.store(LEADERBOARD_KEY, contents.as_bytes()).unwrap();
+
+        let leaderboard = Leaderboard::load(&save);
+        let best: Vec<u32> = (3..=LEADERBOARD_SIZE as u32 + 2).rev().collect();
+        assert_eq!(scores(&leaderboard), best);
+        assert!(leaderboard
+            .entries
+            .iter()
+            .all(|entry| entry.initials == "XAA"));
+        assert_eq!(leaderboard.last_initials, "QAA");
+    }
+}
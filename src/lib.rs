@@ -0,0 +1,286 @@
+pub mod animation;
+pub mod audio;
+pub mod background;
+pub mod bounds;
+pub mod camera;
+pub mod collision;
+pub mod config;
+pub mod console;
+pub mod controls;
+pub mod crt;
+pub mod daily;
+pub mod debug;
+pub mod diagnostics;
+#[cfg(feature = "discord")]
+pub mod discord;
+pub mod enemy;
+pub mod enemy_table;
+pub mod flash;
+pub mod ghost;
+#[cfg(feature = "global-leaderboard")]
+pub mod global_leaderboard;
+pub mod headless;
+pub mod history;
+pub mod launch;
+pub mod leaderboard;
+pub mod lifetime;
+pub mod loading;
+pub mod locale;
+pub mod mobile;
+#[cfg(feature = "mods")]
+pub mod mods;
+pub mod movement;
+pub mod narration;
+#[cfg(feature = "online")]
+pub mod online;
+pub mod palette;
+pub mod particles;
+pub mod pause;
+pub mod personal_best;
+#[cfg(feature = "rapier")]
+pub mod physics;
+pub mod player;
+pub mod prefab;
+pub mod profiles;
+pub mod prompts;
+pub mod race;
+pub mod replay;
+pub mod rng;
+pub mod save;
+pub mod score;
+pub mod screenshot;
+pub mod settings;
+pub mod shake;
+pub mod shop;
+pub mod skins;
+pub mod snapshot;
+pub mod spatial;
+pub mod states;
+#[cfg(feature = "steam")]
+pub mod steam;
+pub mod suspend;
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
+pub mod time_scale;
+#[cfg(feature = "twitch")]
+pub mod twitch;
+pub mod ui;
+pub mod versus;
+pub mod video;
+pub mod waves;
+#[cfg(feature = "web")]
+pub mod web;
+
+use bevy::{
+    app::PluginGroupBuilder,
+    asset::LoadState,
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+pub const SPRITE_SIZE: f32 = 16.0;
+const SPRITE_SHEET_COLUMNS: usize = 49;
+const SPRITE_SHEET_ROWS: usize = 22;
+/// How many sprites the sheet has. Higher indices don't exist.
+pub const SPRITE_COUNT: usize = SPRITE_SHEET_COLUMNS * SPRITE_SHEET_ROWS;
+pub const ACTOR_Z: f32 = 1.0;
+
+/// Labels for gameplay systems whose relative order matters.
+///
+/// In `FixedUpdate` a step runs `Movement`, then `SpatialGrid`, then
+/// `Collision`. `Update` afterwards runs `Death`, which reacts to collisions,
+/// and then `Score`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, SystemSet)]
+pub enum GameSystem {
+    Movement,
+    SpatialGrid,
+    Collision,
+    Death,
+    Score,
+    /// Initials for the local leaderboard are picked, and settled when the
+    /// game over screen is left.
+    Initials,
+    /// In `PreUpdate`, right after input: the console or a text field takes
+    /// the keys it was typed with, before anything else sees them.
+    TextInput,
+}
+
+/// The shared sprite atlas every gameplay sprite is drawn from.
+#[derive(Resource)]
+pub struct SpriteSheet {
+    pub texture: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+}
+
+impl SpriteSheet {
+    /// The sprite at `index` on the sheet, to go with a `SpriteBundle` using
+    /// [`SpriteSheet::texture`].
+    pub fn atlas(&self, index: usize) -> TextureAtlas {
+        TextureAtlas {
+            layout: self.layout.clone(),
+            index,
+        }
+    }
+}
+
+/// Every plugin that makes up the game, added on top of Bevy's
+/// `DefaultPlugins`. Features can be left out with `build`, for example
+/// `GamePlugins.build().disable::<audio::AudioPlugin>()` to run without
+/// sound.
+pub struct GamePlugins;
+
+impl PluginGroup for GamePlugins {
+    fn build(self) -> PluginGroupBuilder {
+        let group = PluginGroupBuilder::start::<Self>()
+            .add(SpriteSheetPlugin)
+            // Decides where every plugin after it loads and saves.
+            .add(profiles::ProfilePlugin)
+            .add_group(SimulationPlugins)
+            .add(audio::AudioPlugin)
+            .add(mobile::MobilePlugin)
+            .add(locale::LocalePlugin)
+            .add(prefab::PrefabPlugin)
+            .add(prompts::PromptPlugin)
+            .add(narration::NarrationPlugin)
+            .add(replay::ReplayPlugin)
+            .add(versus::VersusPlugin)
+            .add(personal_best::PersonalBestPlugin)
+            .add(race::RacePlugin)
+            .add(leaderboard::LeaderboardPlugin)
+            .add(history::HistoryPlugin)
+            .add(skins::SkinsPlugin)
+            .add(shop::ShopPlugin)
+            .add(daily::DailyPlugin)
+            .add(suspend::SuspendPlugin)
+            .add(ui::UiPlugin)
+            .add(pause::PausePlugin)
+            .add(palette::PalettePlugin)
+            .add(video::VideoPlugin)
+            .add(camera::CameraPlugin)
+            .add(background::BackgroundPlugin)
+            .add(shake::ShakePlugin)
+            .add(crt::CrtPlugin)
+            .add(diagnostics::DiagnosticsPlugin)
+            .add(debug::DebugPlugin)
+            .add(screenshot::ScreenshotPlugin)
+            .add(console::ConsolePlugin);
+
+        #[cfg(feature = "web")]
+        let group = group.add(web::WebPlugin);
+        #[cfg(feature = "steam")]
+        let group = group.add(steam::SteamPlugin);
+        #[cfg(feature = "discord")]
+        let group = group.add(discord::DiscordPlugin);
+        #[cfg(feature = "online")]
+        let group = group.add(online::OnlinePlugin);
+        #[cfg(feature = "twitch")]
+        let group = group.add(twitch::TwitchPlugin);
+        #[cfg(feature = "global-leaderboard")]
+        let group = group.add(global_leaderboard::GlobalLeaderboardPlugin);
+        #[cfg(feature = "mods")]
+        let group = group.add(mods::ModPlugin);
+        #[cfg(feature = "telemetry")]
+        let group = group.add(telemetry::TelemetryPlugin);
+        group
+    }
+}
+
+/// The plugins that decide how a run plays out. None of them need a window,
+/// a renderer or an audio device, so they can also be run headless, see
+/// [`headless`].
+pub struct SimulationPlugins;
+
+impl PluginGroup for SimulationPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        let group = PluginGroupBuilder::start::<Self>()
+            .add(config::ConfigPlugin)
+            .add(states::StatesPlugin)
+            .add(loading::LoadingPlugin)
+            .add(launch::LaunchPlugin)
+            .add(settings::SettingsPlugin)
+            .add(time_scale::TimeScalePlugin)
+            .add(rng::RngPlugin)
+            .add(movement::MovementPlugin)
+            .add(player::PlayerPlugin)
+            .add(enemy::EnemyPlugin)
+            .add(waves::WavePlugin)
+            .add(snapshot::SnapshotPlugin)
+            .add(score::ScorePlugin)
+            .add(animation::AnimationPlugin)
+            .add(particles::ParticlePlugin)
+            .add(flash::FlashPlugin)
+            .add(bounds::BoundsPlugin)
+            .add(lifetime::LifetimePlugin);
+
+        #[cfg(feature = "rapier")]
+        let group = group.add(physics::PhysicsPlugin);
+        #[cfg(not(feature = "rapier"))]
+        let group = group.add(spatial::SpatialPlugin);
+        group
+    }
+}
+
+/// Loads the shared sprite atlas at startup.
+pub struct SpriteSheetPlugin;
+
+impl Plugin for SpriteSheetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_sprite_sheet)
+            .add_systems(OnExit(states::GameState::Loading), use_placeholder_sprites);
+    }
+}
+
+fn load_sprite_sheet(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut loading: ResMut<loading::LoadingAssets>,
+) {
+    let texture: Handle<Image> = asset_server.load("colored-transparent.png");
+    loading.add(&texture);
+    let layout = TextureAtlasLayout::from_grid(
+        UVec2::splat(SPRITE_SIZE as u32),
+        SPRITE_SHEET_COLUMNS as u32,
+        SPRITE_SHEET_ROWS as u32,
+        Some(UVec2::ONE),
+        None,
+    );
+
+    commands.insert_resource(SpriteSheet {
+        texture,
+        layout: layouts.add(layout),
+    });
+}
+
+/// If the sprite sheet failed to load, fills its texture with solid white so
+/// every sprite still shows up as a square in its tint color.
+fn use_placeholder_sprites(
+    asset_server: Res<AssetServer>,
+    sprite_sheet: Res<SpriteSheet>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let layout = match layouts.get(&sprite_sheet.layout) {
+        Some(layout) => layout,
+        None => return,
+    };
+
+    if let Some(LoadState::Failed(_)) = asset_server.get_load_state(&sprite_sheet.texture) {
+        let size = Extent3d {
+            width: layout.size.x,
+            height: layout.size.y,
+            depth_or_array_layers: 1,
+        };
+        let placeholder = Image::new_fill(
+            size,
+            TextureDimension::D2,
+            &[255, 255, 255, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        images.insert(&sprite_sheet.texture, placeholder);
+    }
+}
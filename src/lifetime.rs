@@ -0,0 +1,35 @@
+use bevy::prelude::*;
+
+use crate::{states::RunActive, time_scale::TimeScale};
+
+pub struct LifetimePlugin;
+
+impl Plugin for LifetimePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, expire_lifetimes.run_if(in_state(RunActive)));
+    }
+}
+
+/// Despawns the entity, along with its children, once the timer finishes.
+/// The timer runs on scaled game time.
+#[derive(Component)]
+pub struct Lifetime(pub Timer);
+
+impl Lifetime {
+    pub fn from_seconds(seconds: f32) -> Self {
+        Self(Timer::from_seconds(seconds, TimerMode::Once))
+    }
+}
+
+fn expire_lifetimes(
+    mut commands: Commands,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut query: Query<(Entity, &mut Lifetime)>,
+) {
+    for (entity, mut lifetime) in query.iter_mut() {
+        if lifetime.0.tick(time_scale.delta(&time)).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::{
+    settings::Settings,
+    states::{self, GameState},
+};
+
+const PROGRESS_BAR_WIDTH: Val = Val::Px(320.0);
+const PROGRESS_BAR_HEIGHT: Val = Val::Px(16.0);
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingAssets>()
+            .init_resource::<FailedAssets>()
+            .add_systems(OnEnter(GameState::Loading), spawn_progress_bar)
+            .add_systems(Update, track_loading.run_if(in_state(GameState::Loading)));
+    }
+}
+
+/// Assets that have to finish loading before the title screen shows. Startup
+/// systems that load something the game can't do without add it here.
+#[derive(Resource, Default)]
+pub struct LoadingAssets(Vec<UntypedHandle>);
+
+impl LoadingAssets {
+    pub fn add<T: Asset>(&mut self, handle: &Handle<T>) {
+        self.0.push(handle.clone().untyped());
+    }
+}
+
+/// Paths of the assets that failed to load, filled in once loading finishes.
+#[derive(Resource, Default)]
+pub struct FailedAssets(pub Vec<String>);
+
+#[derive(Component)]
+struct ProgressBar;
+
+fn spawn_progress_bar(mut commands: Commands, settings: Res<Settings>) {
+    let colors = settings.palette.colors();
+    let mut track_color = colors.text;
+    track_color.set_alpha(0.25);
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: BackgroundColor(Color::NONE),
+            ..default()
+        })
+        .insert(StateScoped(GameState::Loading))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: PROGRESS_BAR_WIDTH,
+                        height: PROGRESS_BAR_HEIGHT,
+                        ..default()
+                    },
+                    background_color: BackgroundColor(track_color),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(colors.score),
+                            ..default()
+                        })
+                        .insert(ProgressBar);
+                });
+        });
+}
+
+/// Fills the progress bar and moves on to the title once every asset has
+/// either loaded or failed. Failures are already logged by the asset server
+/// and are kept in [`FailedAssets`] so the game can fall back and report them.
+fn track_loading(
+    asset_server: Res<AssetServer>,
+    loading: Res<LoadingAssets>,
+    mut failed: ResMut<FailedAssets>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut query: Query<&mut Style, With<ProgressBar>>,
+) {
+    let done = loading
+        .0
+        .iter()
+        .filter(|handle| {
+            matches!(
+                asset_server.get_load_state(handle.id()),
+                Some(LoadState::Loaded | LoadState::Failed(_))
+            )
+        })
+        .count();
+
+    for mut style in query.iter_mut() {
+        style.width = Val::Percent(100.0 * done as f32 / loading.0.len().max(1) as f32);
+    }
+
+    if done == loading.0.len() && states::try_change(&mut next_state, GameState::Title) {
+        failed.0 = loading
+            .0
+            .iter()
+            .filter(|handle| {
+                matches!(
+                    asset_server.get_load_state(handle.id()),
+                    Some(LoadState::Failed(_))
+                )
+            })
+            .filter_map(|handle| handle.path())
+            .map(|path| path.path().display().to_string())
+            .collect();
+    }
+}
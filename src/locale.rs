@@ -0,0 +1,238 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue};
+use serde::{Deserialize, Serialize};
+use unic_langid::LanguageIdentifier;
+
+use crate::{loading::LoadingAssets, settings::Settings};
+
+/// Built into the binary, so every message has English text even when the
+/// translations fail to load.
+const FALLBACK_TRANSLATIONS: &str = include_str!("../assets/locales/en-US.ftl");
+
+/// Translates the game's text with the Fluent files in `assets/locales`, one
+/// per [`Language`]. The language is picked in the pause menu, and every
+/// [`LocalizedText`] and prompt is rewritten straight away. Like the other
+/// assets, the files are reloaded when they change on disk.
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Locale::new())
+            .init_asset::<Translations>()
+            .init_asset_loader::<TranslationsLoader>()
+            .add_systems(Startup, load_translations)
+            .add_systems(Update, update_locale)
+            .add_systems(Update, update_localized_text.after(update_locale));
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    const ALL: [Language; 2] = [Language::English, Language::Spanish];
+
+    /// Written in the language itself, so it can be found from any other.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+    }
+
+    pub fn next(&self) -> Language {
+        match self {
+            Language::English => Language::Spanish,
+            Language::Spanish => Language::English,
+        }
+    }
+
+    /// The Unicode language identifier, which also names its file.
+    fn id(&self) -> &'static str {
+        match self {
+            Language::English => "en-US",
+            Language::Spanish => "es",
+        }
+    }
+}
+
+/// The source of a `.ftl` file.
+#[derive(Debug, Asset, TypePath)]
+pub struct Translations(pub String);
+
+#[derive(Default)]
+pub struct TranslationsLoader;
+
+impl AssetLoader for TranslationsLoader {
+    type Asset = Translations;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Translations, anyhow::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let source = String::from_utf8(bytes)?;
+        Ok(Translations(source))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ftl"]
+    }
+}
+
+#[derive(Resource)]
+struct TranslationHandles(Vec<(Language, Handle<Translations>)>);
+
+type Bundle = FluentBundle<FluentResource>;
+
+/// Looks up the text for a message id in the chosen language, falling back
+/// to the built-in English text, and to the id itself if there is none.
+#[derive(Resource)]
+pub struct Locale {
+    language: Language,
+    /// `None` until the language's file has loaded, or if it failed to.
+    bundle: Option<Bundle>,
+    fallback: Bundle,
+}
+
+impl Locale {
+    fn new() -> Self {
+        Self {
+            language: Language::default(),
+            bundle: None,
+            fallback: create_bundle(Language::English, FALLBACK_TRANSLATIONS.to_string()),
+        }
+    }
+
+    pub fn text(&self, id: &str) -> String {
+        self.format(id, &[])
+    }
+
+    /// Numbers passed as arguments pick plural forms, and `NUMBER()` in a
+    /// message can set how many fraction digits they show.
+    pub fn format(&self, id: &str, args: &[(&str, FluentValue)]) -> String {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, value.clone());
+        }
+
+        for bundle in self.bundle.iter().chain([&self.fallback]) {
+            let pattern = match bundle.get_message(id).and_then(|message| message.value()) {
+                Some(pattern) => pattern,
+                None => continue,
+            };
+
+            let mut errors = Vec::new();
+            let text = bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+            if !errors.is_empty() {
+                warn!("failed to format '{}': {:?}", id, errors);
+            }
+            return text.into_owned();
+        }
+
+        warn!("no translation for '{}'", id);
+        id.to_string()
+    }
+}
+
+fn create_bundle(language: Language, source: String) -> Bundle {
+    let id: LanguageIdentifier = language.id().parse().expect("language ids are valid");
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // The isolation marks around arguments would be drawn as boxes.
+    bundle.set_use_isolating(false);
+    bundle
+        .add_function("NUMBER", number)
+        .expect("NUMBER is only added once");
+
+    let resource = FluentResource::try_new(source).unwrap_or_else(|(resource, errors)| {
+        warn!("errors in {} translations: {:?}", language.id(), errors);
+        resource
+    });
+    if let Err(errors) = bundle.add_resource(resource) {
+        warn!("errors in {} translations: {:?}", language.id(), errors);
+    }
+    bundle
+}
+
+/// `NUMBER($value, minimumFractionDigits: 1)` and the like. Fluent leaves
+/// this function to the application.
+fn number<'a>(positional: &[FluentValue<'a>], named: &FluentArgs) -> FluentValue<'a> {
+    match positional.first() {
+        Some(FluentValue::Number(number)) => {
+            let mut number = number.clone();
+            number.options.merge(named);
+            FluentValue::Number(number)
+        }
+        _ => FluentValue::Error,
+    }
+}
+
+/// Text whose first section shows the message with this id.
+#[derive(Component)]
+pub struct LocalizedText(pub &'static str);
+
+fn load_translations(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    let handles = Language::ALL
+        .into_iter()
+        .map(|language| {
+            let handle: Handle<Translations> =
+                asset_server.load(format!("locales/{}.ftl", language.id()));
+            loading.add(&handle);
+            (language, handle)
+        })
+        .collect();
+    commands.insert_resource(TranslationHandles(handles));
+}
+
+/// Rebuilds the translations when the language setting changes, or when its
+/// file finishes loading or is edited.
+fn update_locale(
+    settings: Res<Settings>,
+    handles: Res<TranslationHandles>,
+    translations: Res<Assets<Translations>>,
+    mut ev_asset: EventReader<AssetEvent<Translations>>,
+    mut locale: ResMut<Locale>,
+) {
+    let language = settings.language;
+    let handle = match handles.0.iter().find(|(other, _)| *other == language) {
+        Some((_, handle)) => handle,
+        None => return,
+    };
+    let reloaded = ev_asset
+        .read()
+        .any(|event| event.is_added(handle) || event.is_modified(handle));
+    if !reloaded && locale.language == language {
+        return;
+    }
+
+    locale.language = language;
+    locale.bundle = translations
+        .get(handle)
+        .map(|translations| create_bundle(language, translations.0.clone()));
+}
+
+fn update_localized_text(locale: Res<Locale>, mut query: Query<(&LocalizedText, &mut Text)>) {
+    if !locale.is_changed() {
+        return;
+    }
+
+    for (localized, mut text) in query.iter_mut() {
+        text.sections[0].value = locale.text(localized.0);
+    }
+}
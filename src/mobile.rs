@@ -0,0 +1,115 @@
+use bevy::{
+    input::{touch::Touches, InputSystem},
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::GameSystem;
+
+#[cfg(any(target_os = "android", target_os = "ios"))]
+use crate::launch::{self, LaunchOptions};
+
+/// Bevy can't ask the device for its notch and gesture bar insets, so
+/// phones keep this much room clear on every edge.
+#[cfg(any(target_os = "android", target_os = "ios"))]
+const MOBILE_SAFE_INSET: f32 = 32.0;
+
+/// Touch controls and the layout helpers phones need. Nothing here gets in
+/// the way on desktop, where there are no touches and no insets.
+pub struct MobilePlugin;
+
+impl Plugin for MobilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SafeArea>().add_systems(
+            PreUpdate,
+            touch_controls
+                .after(InputSystem)
+                .after(GameSystem::TextInput),
+        );
+    }
+}
+
+/// Space, in logical pixels, that UI anchored to a screen edge should leave
+/// free for notches, rounded corners and system bars.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SafeArea {
+    pub top: f32,
+    pub bottom: f32,
+    pub left: f32,
+    pub right: f32,
+}
+
+impl Default for SafeArea {
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    fn default() -> Self {
+        Self::uniform(0.0)
+    }
+
+    #[cfg(any(target_os = "android", target_os = "ios"))]
+    fn default() -> Self {
+        Self::uniform(MOBILE_SAFE_INSET)
+    }
+}
+
+impl SafeArea {
+    pub fn uniform(inset: f32) -> Self {
+        Self {
+            top: inset,
+            bottom: inset,
+            left: inset,
+            right: inset,
+        }
+    }
+}
+
+/// Turns touches into the keys the game already listens for. Holding the
+/// left or right half of the screen steers, and each new touch taps Space to
+/// start a run or pick a menu item.
+fn touch_controls(
+    touches: Res<Touches>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut held: Local<Vec<KeyCode>>,
+) {
+    let half_width = match windows.get_single() {
+        Ok(window) => window.width() / 2.0,
+        Err(_) => return,
+    };
+
+    let mut keys: Vec<KeyCode> = touches
+        .iter()
+        .map(|touch| {
+            if touch.position().x < half_width {
+                KeyCode::ArrowLeft
+            } else {
+                KeyCode::ArrowRight
+            }
+        })
+        .collect();
+
+    if touches.iter_just_pressed().next().is_some() {
+        keys.push(KeyCode::Space);
+    }
+
+    for &key in held.iter().filter(|key| !keys.contains(key)) {
+        keyboard_input.release(key);
+    }
+
+    for &key in &keys {
+        if !held.contains(&key) {
+            keyboard_input.press(key);
+        }
+    }
+
+    *held = keys;
+}
+
+/// Entry point for the Android and iOS builds, which have no command line.
+#[cfg(any(target_os = "android", target_os = "ios"))]
+#[bevy_main]
+fn main() {
+    launch::run(LaunchOptions {
+        portrait: true,
+        ..default()
+    });
+}
@@ -0,0 +1,283 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use bevy::prelude::*;
+use rhai::{CallFnOptions, Dynamic, Engine, FuncArgs, Scope, AST};
+
+use crate::{
+    enemy::{EnemyBurstEvent, EnemyDropEvent, EnemySpawnEvent, MAX_BURST},
+    player::{CollisionEvent, Player},
+    score::{Ranked, ScoreEvent, ScoreSource, Scoreboard, Tampered},
+    states::RunState,
+    time_scale::GameSpeed,
+    GameSystem,
+};
+
+const MODS_DIR: &str = "mods";
+const MOD_EXTENSION: &str = "rhai";
+/// How much work one hook may do before it is stopped, so a runaway loop
+/// in a mod can't hang the game.
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 4.0;
+
+const ON_SPAWN_TICK: &str = "on_spawn_tick";
+const ON_COLLISION: &str = "on_collision";
+const ON_SCORE: &str = "on_score";
+
+/// Loads every `.rhai` script in the `mods` directory and runs the hooks
+/// each one defines during a run:
+///
+/// - `on_spawn_tick(x)` whenever the spawner drops an enemy, with where it
+///   dropped.
+/// - `on_collision(player)` for every step a player touches an enemy, even
+///   while they can't be hit.
+/// - `on_score(score)` whenever the score passes a whole point.
+///
+/// From a hook, a mod can call `spawn_enemy(kind, x)`, `spawn_enemies(count)`,
+/// `set_speed(scale)` and `add_score(points)`, and `print` to the log. That is
+/// all a mod can reach: it has no access to files or the network. A run stops
+/// counting for high scores as soon as a mod changes it.
+pub struct ModPlugin;
+
+impl Plugin for ModPlugin {
+    fn build(&self, app: &mut App) {
+        let mods = Mods::load(Path::new(MODS_DIR));
+        if mods.scripts.is_empty() {
+            info!("no mods in {}", MODS_DIR);
+            return;
+        }
+
+        app.insert_resource(mods).add_systems(
+            Update,
+            (
+                run_spawn_hooks,
+                run_collision_hooks,
+                run_score_hooks.after(GameSystem::Score),
+                apply_mod_actions
+                    .after(run_spawn_hooks)
+                    .after(run_collision_hooks)
+                    .after(run_score_hooks),
+            )
+                .run_if(in_state(RunState::Running)),
+        );
+    }
+}
+
+/// What a mod asked for from a hook, applied once every hook has run.
+enum ModAction {
+    SpawnEnemy { kind: i64, x: f32 },
+    SpawnEnemies(u32),
+    SetSpeed(f32),
+    AddScore(f32),
+}
+
+struct Mod {
+    name: String,
+    ast: AST,
+    /// The hooks the script defines.
+    hooks: HashSet<&'static str>,
+}
+
+#[derive(Resource)]
+struct Mods {
+    engine: Engine,
+    scripts: Vec<Mod>,
+    actions: Arc<Mutex<Vec<ModAction>>>,
+}
+
+impl Mods {
+    /// Scripts run in file name order. One that doesn't compile, or fails
+    /// as it starts, is left out.
+    fn load(dir: &Path) -> Self {
+        let actions = Arc::new(Mutex::new(Vec::new()));
+        let engine = create_engine(&actions);
+
+        let mut paths: Vec<_> = match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == MOD_EXTENSION))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        paths.sort();
+
+        let mut scripts = Vec::new();
+        for path in paths {
+            let name = path.display().to_string();
+            let ast = match engine.compile_file(path) {
+                Ok(ast) => ast,
+                Err(err) => {
+                    warn!("failed to load mod {}: {}", name, err);
+                    continue;
+                }
+            };
+            if let Err(err) = engine.run_ast(&ast) {
+                warn!("mod {} failed to start: {}", name, err);
+                continue;
+            }
+
+            let hooks = [ON_SPAWN_TICK, ON_COLLISION, ON_SCORE]
+                .into_iter()
+                .filter(|hook| {
+                    ast.iter_functions()
+                        .any(|function| function.name == *hook && function.params.len() == 1)
+                })
+                .collect();
+            info!("loaded mod {}", name);
+            scripts.push(Mod { name, ast, hooks });
+        }
+
+        // Only hooks get to change a run.
+        actions.lock().unwrap().clear();
+
+        Self {
+            engine,
+            scripts,
+            actions,
+        }
+    }
+
+    /// A mod that fails in a hook is turned off for the rest of the session,
+    /// rather than failing again every frame.
+    fn run_hook(&mut self, hook: &str, args: impl FuncArgs + Clone) {
+        let engine = &self.engine;
+        self.scripts.retain(|script| {
+            if !script.hooks.contains(hook) {
+                return true;
+            }
+
+            let options = CallFnOptions::new().eval_ast(false);
+            let result = engine.call_fn_with_options::<Dynamic>(
+                options,
+                &mut Scope::new(),
+                &script.ast,
+                hook,
+                args.clone(),
+            );
+            match result {
+                Ok(_) => true,
+                Err(err) => {
+                    warn!(
+                        "mod {} turned off after {} failed: {}",
+                        script.name, hook, err
+                    );
+                    false
+                }
+            }
+        });
+    }
+}
+
+fn create_engine(actions: &Arc<Mutex<Vec<ModAction>>>) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .on_print(|text| info!("mod: {}", text))
+        .on_debug(|text, _, _| debug!("mod: {}", text));
+
+    let queue = actions.clone();
+    engine.register_fn("spawn_enemy", move |kind: i64, x: f64| {
+        queue
+            .lock()
+            .unwrap()
+            .push(ModAction::SpawnEnemy { kind, x: x as f32 });
+    });
+    let queue = actions.clone();
+    engine.register_fn("spawn_enemies", move |count: i64| {
+        queue.lock().unwrap().push(ModAction::SpawnEnemies(
+            count.clamp(0, MAX_BURST as i64) as u32
+        ));
+    });
+    let queue = actions.clone();
+    engine.register_fn("set_speed", move |scale: f64| {
+        queue
+            .lock()
+            .unwrap()
+            .push(ModAction::SetSpeed(scale as f32));
+    });
+    let queue = actions.clone();
+    engine.register_fn("add_score", move |points: f64| {
+        queue
+            .lock()
+            .unwrap()
+            .push(ModAction::AddScore(points as f32));
+    });
+
+    engine
+}
+
+fn run_spawn_hooks(mut mods: ResMut<Mods>, mut ev_spawn: EventReader<EnemySpawnEvent>) {
+    for spawn in ev_spawn.read() {
+        mods.run_hook(ON_SPAWN_TICK, (spawn.0.x as f64,));
+    }
+}
+
+fn run_collision_hooks(
+    mut mods: ResMut<Mods>,
+    mut ev_collision: EventReader<CollisionEvent>,
+    player_query: Query<&Player>,
+) {
+    for collision in ev_collision.read() {
+        if let Ok(player) = player_query.get(collision.0) {
+            mods.run_hook(ON_COLLISION, (player.index as i64,));
+        }
+    }
+}
+
+/// A new run starts below the last score, so `previous` needs no resetting.
+fn run_score_hooks(mut mods: ResMut<Mods>, scoreboard: Res<Scoreboard>, mut previous: Local<f32>) {
+    if scoreboard.score.floor() > previous.floor() {
+        mods.run_hook(ON_SCORE, (scoreboard.score.floor() as i64,));
+    }
+    *previous = scoreboard.score;
+}
+
+fn apply_mod_actions(
+    mods: Res<Mods>,
+    mut game_speed: ResMut<GameSpeed>,
+    mut ranked: ResMut<Ranked>,
+    mut tampered: ResMut<Tampered>,
+    mut ev_drop: EventWriter<EnemyDropEvent>,
+    mut ev_burst: EventWriter<EnemyBurstEvent>,
+    mut ev_score: EventWriter<ScoreEvent>,
+) {
+    let actions = std::mem::take(&mut *mods.actions.lock().unwrap());
+    if actions.is_empty() {
+        return;
+    }
+    ranked.0 = false;
+    tampered.0 = true;
+
+    for action in actions {
+        match action {
+            ModAction::SpawnEnemy { kind, x } => match usize::try_from(kind) {
+                Ok(kind) => {
+                    ev_drop.send(EnemyDropEvent { kind, x });
+                }
+                Err(_) => warn!("a mod asked for enemy kind {}", kind),
+            },
+            ModAction::SpawnEnemies(count) => {
+                ev_burst.send(EnemyBurstEvent(count));
+            }
+            ModAction::SetSpeed(scale) if scale.is_finite() => {
+                game_speed.0 = scale.clamp(MIN_SPEED, MAX_SPEED);
+            }
+            ModAction::AddScore(points) if points.is_finite() => {
+                ev_score.send(ScoreEvent {
+                    amount: points,
+                    source: ScoreSource::Mod,
+                });
+            }
+            ModAction::SetSpeed(_) | ModAction::AddScore(_) => {
+                warn!("a mod asked for a number that isn't finite");
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+
+use crate::{states::RunState, time_scale::TimeScale, GameSystem};
+
+/// Seconds of simulation advanced by each run of the [`FixedUpdate`]
+/// schedule, which runs as many times per frame as needed before
+/// [`Update`].
+pub const FIXED_STEP: f32 = 1.0 / 60.0;
+
+pub struct MovementPlugin;
+
+impl Plugin for MovementPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Time::<Fixed>::from_seconds(FIXED_STEP as f64))
+            .add_systems(
+                FixedUpdate,
+                apply_velocity
+                    .in_set(GameSystem::Movement)
+                    .run_if(in_state(RunState::Running)),
+            )
+            .add_systems(
+                Update,
+                (interpolate_positions, apply_angular_velocity).run_if(in_state(RunState::Running)),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct Velocity(pub Vec3);
+
+#[derive(Component)]
+pub struct AngularVelocity(pub f32);
+
+/// The simulated position of an entity moved on the fixed timestep. Its
+/// transform is drawn between the last two simulated positions so motion
+/// stays smooth at any frame rate.
+#[derive(Component)]
+pub struct Interpolated {
+    previous: Vec3,
+    pub current: Vec3,
+}
+
+impl Interpolated {
+    pub fn new(translation: Vec3) -> Self {
+        Self {
+            previous: translation,
+            current: translation,
+        }
+    }
+}
+
+/// Advances every moving entity by one fixed step.
+pub fn apply_velocity(
+    time_scale: Res<TimeScale>,
+    mut query: Query<(&mut Interpolated, &Velocity)>,
+) {
+    let delta_time = FIXED_STEP * time_scale.0;
+    for (mut position, velocity) in query.iter_mut() {
+        position.previous = position.current;
+        position.current += velocity.0 * delta_time;
+    }
+}
+
+fn interpolate_positions(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&Interpolated, &mut Transform)>,
+) {
+    let alpha = fixed_time.overstep_fraction();
+
+    for (position, mut transform) in query.iter_mut() {
+        transform.translation = position.previous.lerp(position.current, alpha);
+    }
+}
+
+fn apply_angular_velocity(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut query: Query<(&mut Transform, &AngularVelocity)>,
+) {
+    let delta_time = time_scale.delta_seconds(&time);
+    for (mut transform, angular_velocity) in query.iter_mut() {
+        transform.rotate(Quat::from_rotation_z(angular_velocity.0 * delta_time));
+    }
+}
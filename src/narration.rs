@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use crate::{
+    locale::Locale,
+    prompts::{InputDevice, Prompt},
+    score::Scoreboard,
+    states::{GameState, RunState},
+};
+
+/// Reads out what is on screen so the menus can be used without seeing
+/// them: each new screen, the focused menu item and the final score.
+/// Anything can send an [`Announcement`]. They are only spoken when the
+/// game is built with the `narration` feature, through the platform's
+/// speech engine or the browser's speech API on the web.
+pub struct NarrationPlugin;
+
+impl Plugin for NarrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Announcement>()
+            .add_systems(OnEnter(GameState::Title), announce_title)
+            .add_systems(OnEnter(GameState::Playing), announce_run)
+            .add_systems(OnEnter(RunState::Paused), announce_pause)
+            .add_systems(OnEnter(GameState::GameOver), announce_game_over);
+
+        #[cfg(feature = "narration")]
+        app.add_systems(Startup, setup_narrator)
+            .add_systems(Last, speak_announcements);
+    }
+}
+
+/// Text to read out. Everything announced in one frame is read together,
+/// cutting off whatever was still being read.
+#[derive(Event)]
+pub struct Announcement(pub String);
+
+fn announce_title(
+    device: Res<InputDevice>,
+    locale: Res<Locale>,
+    mut ev_announce: EventWriter<Announcement>,
+) {
+    let prompts = format!(
+        "{}. {}. {}",
+        Prompt::Start.text(*device, &locale),
+        Prompt::Modes.text(*device, &locale),
+        Prompt::Screens.text(*device, &locale)
+    );
+    ev_announce.send(Announcement(
+        locale.format("narration-title", &[("prompts", prompts.into())]),
+    ));
+}
+
+fn announce_run(locale: Res<Locale>, mut ev_announce: EventWriter<Announcement>) {
+    ev_announce.send(Announcement(locale.text("narration-go")));
+}
+
+fn announce_pause(locale: Res<Locale>, mut ev_announce: EventWriter<Announcement>) {
+    ev_announce.send(Announcement(locale.text("pause-title")));
+}
+
+fn announce_game_over(
+    scoreboard: Res<Scoreboard>,
+    device: Res<InputDevice>,
+    locale: Res<Locale>,
+    mut ev_announce: EventWriter<Announcement>,
+) {
+    ev_announce.send(Announcement(locale.format(
+        "narration-game-over",
+        &[
+            ("score", (scoreboard.score as i16).into()),
+            ("prompt", Prompt::Start.text(*device, &locale).into()),
+        ],
+    )));
+}
+
+#[cfg(feature = "narration")]
+struct Narrator(tts::Tts);
+
+#[cfg(feature = "narration")]
+fn setup_narrator(world: &mut World) {
+    match tts::Tts::default() {
+        Ok(tts) => world.insert_non_send_resource(Narrator(tts)),
+        Err(err) => warn!("narration is unavailable: {}", err),
+    }
+}
+
+#[cfg(feature = "narration")]
+fn speak_announcements(
+    narrator: Option<NonSendMut<Narrator>>,
+    mut ev_announce: EventReader<Announcement>,
+) {
+    let mut narrator = match narrator {
+        Some(narrator) => narrator,
+        None => return,
+    };
+
+    let text = ev_announce
+        .read()
+        .map(|announcement| announcement.0.as_str())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(". ");
+
+    if text.is_empty() {
+        return;
+    }
+
+    if let Err(err) = narrator.0.speak(text, true) {
+        warn!("failed to narrate: {}", err);
+    }
+}
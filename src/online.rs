@@ -0,0 +1,495 @@
+use std::{cmp::Ordering, net::SocketAddr};
+
+use bevy::prelude::*;
+use bytemuck::{Pod, Zeroable};
+use ggrs::{
+    Config, GGRSError, GGRSRequest, P2PSession, PlayerType, SessionBuilder, SessionState,
+    SpectatorSession, UdpNonBlockingSocket,
+};
+
+use crate::{
+    bounds::PlayArea,
+    ghost,
+    launch::LaunchOptions,
+    locale::Locale,
+    player::{Player, PlayerCount},
+    score::{ScoreText, Scoreboard},
+    settings::Settings,
+    states::{self, GameState, InRun},
+    ui::{TextFont, SCOREBOARD_FONT_SIZE},
+    SpriteSheet,
+};
+
+const PLAYER_COLORS: [Color; 2] = [Color::srgb(0.6, 0.8, 1.0), Color::srgb(1.0, 0.5, 0.5)];
+const INPUT_DELAY: usize = 2;
+
+/// Two players online, each dodging the same seeded enemies on their own
+/// machine. Nothing one player does changes the other's run, so the only
+/// state that is rolled back is where each player is, their score and
+/// whether they are still up. The opponent is drawn as a ghost, and whoever
+/// stays up longer wins.
+///
+/// Spectators get the same inputs from the host once they are confirmed.
+/// They run the seed's enemies without a player of their own and draw both
+/// players over them, with the live scores along the top.
+pub struct OnlinePlugin;
+
+impl Plugin for OnlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetState>()
+            .add_systems(Startup, start_session)
+            .add_systems(FixedUpdate, advance_session)
+            .add_systems(Update, start_match.run_if(in_state(GameState::Title)))
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (spawn_net_players, spawn_spectator_text),
+            )
+            .add_systems(Update, move_net_players)
+            .add_systems(Update, update_spectator_text)
+            .add_systems(OnEnter(GameState::GameOver), spawn_result)
+            .add_systems(Update, update_result.run_if(in_state(GameState::GameOver)));
+    }
+}
+
+/// Where to listen and who to connect to, from `--online` or `--spectate`.
+#[derive(Clone, Debug)]
+pub struct OnlineOptions {
+    pub port: u16,
+    /// The other player, or the host when spectating.
+    pub peer: SocketAddr,
+    pub role: OnlineRole,
+}
+
+#[derive(Clone, Debug)]
+pub enum OnlineRole {
+    /// Player 0. Spectators connect to the host.
+    Host {
+        spectators: Vec<SocketAddr>,
+    },
+    /// Player 1.
+    Guest,
+    Spectator,
+}
+
+struct GgrsConfig;
+
+impl Config for GgrsConfig {
+    type Input = NetInput;
+    type State = NetState;
+    type Address = SocketAddr;
+}
+
+/// What each client sends every frame: where its player is, its score and
+/// whether it is still dodging.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+struct NetInput {
+    x: f32,
+    score: f32,
+    flags: u32,
+}
+
+impl NetInput {
+    const PLAYING: u32 = 1;
+    const ALIVE: u32 = 2;
+
+    fn is(&self, flag: u32) -> bool {
+        self.flags & flag != 0
+    }
+}
+
+/// Both players as of the last frame the session advanced to.
+#[derive(Resource, Clone, Default)]
+struct NetState {
+    frame: i32,
+    players: [NetInput; 2],
+    /// The frame each player went down on, once they have.
+    down_at: [Option<i32>; 2],
+}
+
+impl NetState {
+    fn advance(&mut self, inputs: impl IntoIterator<Item = NetInput>) {
+        self.frame += 1;
+        for (index, input) in inputs.into_iter().enumerate() {
+            self.players[index] = input;
+            let down = input.is(NetInput::PLAYING) && !input.is(NetInput::ALIVE);
+            if down && self.down_at[index].is_none() {
+                self.down_at[index] = Some(self.frame);
+            }
+        }
+    }
+
+    /// The message id of who won, from the point of view of `local`, or
+    /// `None` while someone is still up.
+    fn result(&self, local: Option<usize>) -> Option<&'static str> {
+        let (first, second) = match self.down_at {
+            [Some(first), Some(second)] => (first, second),
+            _ => return None,
+        };
+
+        let winner = match first.cmp(&second) {
+            Ordering::Greater => Some(0),
+            Ordering::Less => Some(1),
+            Ordering::Equal => None,
+        };
+        Some(match (winner, local) {
+            (None, _) => "versus-draw",
+            (Some(winner), Some(local)) if winner == local => "online-you-win",
+            (Some(_), Some(_)) => "online-you-lose",
+            (Some(0), None) => "versus-p1-wins",
+            (Some(_), None) => "versus-p2-wins",
+        })
+    }
+}
+
+/// Whether this game is in an online match, playing or spectating.
+pub fn in_session(world: &World) -> bool {
+    world.contains_non_send::<OnlineSession>()
+}
+
+enum OnlineSession {
+    Player {
+        session: P2PSession<GgrsConfig>,
+        local: usize,
+    },
+    Spectator(SpectatorSession<GgrsConfig>),
+}
+
+impl OnlineSession {
+    fn is_running(&self) -> bool {
+        let state = match self {
+            OnlineSession::Player { session, .. } => session.current_state(),
+            OnlineSession::Spectator(session) => session.current_state(),
+        };
+        state == SessionState::Running
+    }
+
+    /// The local player's handle, or `None` when spectating.
+    fn local(&self) -> Option<usize> {
+        match self {
+            OnlineSession::Player { local, .. } => Some(*local),
+            OnlineSession::Spectator(_) => None,
+        }
+    }
+}
+
+/// One of the online players, drawn as a ghost.
+#[derive(Component)]
+struct NetPlayer(usize);
+
+#[derive(Component)]
+struct SpectatorText;
+
+#[derive(Component)]
+struct OnlineResultText;
+
+fn start_session(world: &mut World) {
+    let options = match world
+        .get_resource::<LaunchOptions>()
+        .and_then(|options| options.online.clone())
+    {
+        Some(options) => options,
+        None => return,
+    };
+
+    let socket = match UdpNonBlockingSocket::bind_to_port(options.port) {
+        Ok(socket) => socket,
+        Err(err) => {
+            error!("could not listen on port {}: {}", options.port, err);
+            return;
+        }
+    };
+    let builder = SessionBuilder::<GgrsConfig>::new()
+        .with_num_players(2)
+        .with_input_delay(INPUT_DELAY);
+
+    let session = match options.role {
+        OnlineRole::Spectator => Ok(OnlineSession::Spectator(
+            builder.start_spectator_session(options.peer, socket),
+        )),
+        OnlineRole::Host { spectators } => {
+            start_player_session(builder, socket, 0, options.peer, &spectators)
+        }
+        OnlineRole::Guest => start_player_session(builder, socket, 1, options.peer, &[]),
+    };
+
+    match session {
+        Ok(session) => {
+            info!("waiting for {} on port {}", options.peer, options.port);
+            world.insert_non_send_resource(session);
+        }
+        Err(err) => error!("could not start online session: {}", err),
+    }
+}
+
+fn start_player_session(
+    builder: SessionBuilder<GgrsConfig>,
+    socket: UdpNonBlockingSocket,
+    local: usize,
+    peer: SocketAddr,
+    spectators: &[SocketAddr],
+) -> Result<OnlineSession, GGRSError> {
+    let mut builder = builder
+        .add_player(PlayerType::Local, local)?
+        .add_player(PlayerType::Remote(peer), 1 - local)?;
+    // Spectator handles come after the players'.
+    for (index, &spectator) in spectators.iter().enumerate() {
+        builder = builder.add_player(PlayerType::Spectator(spectator), 2 + index)?;
+    }
+
+    let session = builder.start_p2p_session(socket)?;
+    Ok(OnlineSession::Player { session, local })
+}
+
+/// Runs once per fixed step, like the rest of the simulation, and keeps
+/// running after the local player is down so the opponent's result arrives.
+fn advance_session(
+    online: Option<NonSendMut<OnlineSession>>,
+    state: Res<State<GameState>>,
+    scoreboard: Res<Scoreboard>,
+    mut net_state: ResMut<NetState>,
+    players: Query<&Transform, With<Player>>,
+) {
+    let mut online = match online {
+        Some(online) => online,
+        None => return,
+    };
+
+    let requests = match &mut *online {
+        OnlineSession::Player { session, local } => {
+            session.poll_remote_clients();
+            for event in session.events() {
+                info!("online: {:?}", event);
+            }
+            if session.current_state() != SessionState::Running {
+                return;
+            }
+
+            let mut input = NetInput {
+                score: scoreboard.score,
+                ..default()
+            };
+            match state.get() {
+                GameState::Playing => {
+                    input.flags = NetInput::PLAYING | NetInput::ALIVE;
+                    if let Some(transform) = players.iter().next() {
+                        input.x = transform.translation.x;
+                    }
+                }
+                GameState::Dying | GameState::GameOver => input.flags = NetInput::PLAYING,
+                _ => {}
+            }
+
+            if let Err(err) = session.add_local_input(*local, input) {
+                warn!("dropped online input: {}", err);
+                return;
+            }
+            session.advance_frame()
+        }
+        OnlineSession::Spectator(session) => {
+            session.poll_remote_clients();
+            for event in session.events() {
+                info!("online: {:?}", event);
+            }
+            if session.current_state() != SessionState::Running {
+                return;
+            }
+            session.advance_frame()
+        }
+    };
+
+    let requests = match requests {
+        Ok(requests) => requests,
+        // Too far ahead of the others; wait for them to catch up.
+        Err(err) => {
+            debug!("online frame skipped: {}", err);
+            return;
+        }
+    };
+
+    for request in requests {
+        match request {
+            GGRSRequest::SaveGameState { cell, frame } => {
+                cell.save(frame, Some(net_state.clone()), None);
+            }
+            GGRSRequest::LoadGameState { cell, .. } => {
+                *net_state = cell.load().unwrap_or_default();
+            }
+            GGRSRequest::AdvanceFrame { inputs } => {
+                net_state.advance(inputs.into_iter().map(|(input, _)| input));
+            }
+        }
+    }
+}
+
+/// Everyone starts as soon as they are connected. Spectators have no player
+/// of their own.
+fn start_match(
+    online: Option<NonSend<OnlineSession>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut player_count: ResMut<PlayerCount>,
+) {
+    let online = match online {
+        Some(online) if online.is_running() => online,
+        _ => return,
+    };
+
+    if states::try_change(&mut next_state, GameState::Playing) {
+        player_count.0 = if online.local().is_some() { 1 } else { 0 };
+    }
+}
+
+/// Players see their opponent as a ghost; spectators see both.
+fn spawn_net_players(
+    mut commands: Commands,
+    online: Option<NonSend<OnlineSession>>,
+    sprite_sheet: Res<SpriteSheet>,
+    play_area: Res<PlayArea>,
+) {
+    let online = match online {
+        Some(online) => online,
+        None => return,
+    };
+
+    for (index, &color) in PLAYER_COLORS.iter().enumerate() {
+        if online.local() != Some(index) {
+            let ghost = ghost::spawn_ghost(&mut commands, &sprite_sheet, &play_area, color);
+            commands.entity(ghost).insert(NetPlayer(index));
+        }
+    }
+}
+
+fn move_net_players(
+    net_state: Res<NetState>,
+    mut query: Query<(&NetPlayer, &mut Transform, &mut Visibility)>,
+) {
+    for (net_player, mut transform, mut visibility) in query.iter_mut() {
+        let input = net_state.players[net_player.0];
+        transform.translation.x = input.x;
+        *visibility = if input.is(NetInput::ALIVE) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn spawn_spectator_text(
+    mut commands: Commands,
+    online: Option<NonSend<OnlineSession>>,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+) {
+    match online {
+        Some(online) if online.local().is_none() => {}
+        _ => return,
+    }
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: SCOREBOARD_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(SCOREBOARD_FONT_SIZE / 2.0),
+                left: Val::Px(SCOREBOARD_FONT_SIZE / 2.0),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(SpectatorText)
+        .insert(StateScoped(InRun));
+}
+
+/// Stands in for the score HUD, which would only count how long the
+/// spectator has been watching.
+fn update_spectator_text(
+    net_state: Res<NetState>,
+    locale: Res<Locale>,
+    mut query: Query<&mut Text, With<SpectatorText>>,
+    mut score_texts: Query<&mut Visibility, With<ScoreText>>,
+) {
+    if query.is_empty() {
+        return;
+    }
+
+    for mut visibility in score_texts.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+
+    let [first, second] = net_state.players;
+    let mut value = locale.format(
+        "online-scores",
+        &[
+            ("first", (first.score as i16).into()),
+            ("second", (second.score as i16).into()),
+        ],
+    );
+    if let Some(result) = net_state.result(None) {
+        value = format!("{}\n{}", value, locale.text(result));
+    }
+
+    for mut text in query.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn spawn_result(
+    mut commands: Commands,
+    online: Option<NonSend<OnlineSession>>,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+) {
+    if online.is_none() {
+        return;
+    }
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: SCOREBOARD_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(SCOREBOARD_FONT_SIZE * 2.0),
+                left: Val::Px(SCOREBOARD_FONT_SIZE),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(OnlineResultText)
+        .insert(StateScoped(GameState::GameOver));
+}
+
+/// The last one standing wins. Until the opponent is down too, the result
+/// isn't known yet.
+fn update_result(
+    online: Option<NonSend<OnlineSession>>,
+    net_state: Res<NetState>,
+    locale: Res<Locale>,
+    mut query: Query<&mut Text, With<OnlineResultText>>,
+) {
+    let online = match online {
+        Some(online) => online,
+        None => return,
+    };
+
+    let value = locale.text(net_state.result(online.local()).unwrap_or("online-waiting"));
+
+    for mut text in query.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use bevy::{color::palettes::css, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    enemy::{Collider, Tint},
+    settings::Settings,
+    states::GameState,
+};
+
+pub struct PalettePlugin;
+
+impl Plugin for PalettePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, apply_palette);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Palette {
+    #[default]
+    Classic,
+    Sunset,
+    Mono,
+    Neon,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+pub struct PaletteColors {
+    pub background: Color,
+    pub text: Color,
+    pub score: Color,
+    pub enemy: Color,
+    pub sky: [Color; 4],
+}
+
+impl Palette {
+    /// The message id of its name.
+    pub fn name_id(&self) -> &'static str {
+        match self {
+            Palette::Classic => "palette-classic",
+            Palette::Sunset => "palette-sunset",
+            Palette::Mono => "palette-mono",
+            Palette::Neon => "palette-neon",
+            Palette::Deuteranopia => "palette-deuteranopia",
+            Palette::Protanopia => "palette-protanopia",
+            Palette::Tritanopia => "palette-tritanopia",
+        }
+    }
+
+    pub fn next(&self) -> Palette {
+        match self {
+            Palette::Classic => Palette::Sunset,
+            Palette::Sunset => Palette::Mono,
+            Palette::Mono => Palette::Neon,
+            Palette::Neon => Palette::Deuteranopia,
+            Palette::Deuteranopia => Palette::Protanopia,
+            Palette::Protanopia => Palette::Tritanopia,
+            Palette::Tritanopia => Palette::Classic,
+        }
+    }
+
+    pub fn colors(&self) -> PaletteColors {
+        match self {
+            Palette::Classic => PaletteColors {
+                background: Color::srgb(0.2, 0.2, 0.2),
+                text: css::ANTIQUE_WHITE.into(),
+                score: css::YELLOW.into(),
+                enemy: Color::WHITE,
+                sky: [
+                    Color::srgb(0.35, 0.45, 0.6),
+                    Color::srgb(0.55, 0.3, 0.25),
+                    Color::srgb(0.2, 0.15, 0.3),
+                    Color::srgb(0.05, 0.05, 0.12),
+                ],
+            },
+            Palette::Sunset => PaletteColors {
+                background: Color::srgb(0.25, 0.12, 0.15),
+                text: Color::srgb(1.0, 0.85, 0.7),
+                score: Color::srgb(1.0, 0.55, 0.3),
+                enemy: Color::srgb(1.0, 0.6, 0.5),
+                sky: [
+                    Color::srgb(0.85, 0.5, 0.35),
+                    Color::srgb(0.7, 0.3, 0.3),
+                    Color::srgb(0.4, 0.15, 0.3),
+                    Color::srgb(0.15, 0.05, 0.15),
+                ],
+            },
+            Palette::Mono => PaletteColors {
+                background: Color::srgb(0.1, 0.1, 0.1),
+                text: Color::srgb(0.9, 0.9, 0.9),
+                score: Color::WHITE,
+                enemy: Color::srgb(0.7, 0.7, 0.7),
+                sky: [
+                    Color::srgb(0.45, 0.45, 0.45),
+                    Color::srgb(0.35, 0.35, 0.35),
+                    Color::srgb(0.2, 0.2, 0.2),
+                    Color::srgb(0.08, 0.08, 0.08),
+                ],
+            },
+            Palette::Neon => PaletteColors {
+                background: Color::srgb(0.05, 0.02, 0.1),
+                text: Color::srgb(0.3, 1.0, 0.9),
+                score: Color::srgb(1.0, 0.2, 0.8),
+                enemy: Color::srgb(0.4, 1.0, 0.4),
+                sky: [
+                    Color::srgb(0.1, 0.05, 0.25),
+                    Color::srgb(0.15, 0.0, 0.3),
+                    Color::srgb(0.05, 0.0, 0.2),
+                    Color::srgb(0.0, 0.0, 0.08),
+                ],
+            },
+            // The colorblind palettes keep to hues that stay apart for each
+            // kind of color blindness, and also keep enemies much brighter
+            // than the dark, muted sky so they stand out by lightness alone.
+            Palette::Deuteranopia => PaletteColors {
+                background: Color::srgb(0.06, 0.08, 0.16),
+                text: Color::srgb(0.95, 0.95, 0.95),
+                score: Color::srgb(0.35, 0.65, 1.0),
+                enemy: Color::srgb(1.0, 0.7, 0.1),
+                sky: [
+                    Color::srgb(0.2, 0.25, 0.4),
+                    Color::srgb(0.14, 0.18, 0.3),
+                    Color::srgb(0.08, 0.1, 0.2),
+                    Color::srgb(0.03, 0.04, 0.1),
+                ],
+            },
+            Palette::Protanopia => PaletteColors {
+                background: Color::srgb(0.06, 0.08, 0.16),
+                text: Color::srgb(0.95, 0.95, 0.95),
+                score: Color::srgb(0.3, 0.6, 1.0),
+                enemy: Color::srgb(1.0, 0.9, 0.3),
+                sky: [
+                    Color::srgb(0.2, 0.25, 0.4),
+                    Color::srgb(0.14, 0.18, 0.3),
+                    Color::srgb(0.08, 0.1, 0.2),
+                    Color::srgb(0.03, 0.04, 0.1),
+                ],
+            },
+            Palette::Tritanopia => PaletteColors {
+                background: Color::srgb(0.1, 0.1, 0.1),
+                text: Color::srgb(0.95, 0.95, 0.95),
+                score: Color::srgb(0.3, 0.85, 0.85),
+                enemy: Color::srgb(1.0, 0.4, 0.45),
+                sky: [
+                    Color::srgb(0.3, 0.3, 0.32),
+                    Color::srgb(0.22, 0.22, 0.24),
+                    Color::srgb(0.14, 0.14, 0.15),
+                    Color::srgb(0.06, 0.06, 0.06),
+                ],
+            },
+        }
+    }
+}
+
+/// Recolors whatever is already on screen when the palette setting changes.
+/// Text sections are matched against the previous palette's text and score
+/// colors so every screen is updated without knowing about its layout.
+fn apply_palette(
+    settings: Res<Settings>,
+    state: Res<State<GameState>>,
+    mut clear_color: ResMut<ClearColor>,
+    mut previous: Local<Option<Palette>>,
+    mut texts: Query<&mut Text>,
+    mut enemies: Query<(&mut Sprite, &Tint), With<Collider>>,
+) {
+    if !settings.is_changed() || *previous == Some(settings.palette) {
+        return;
+    }
+
+    let colors = settings.palette.colors();
+
+    if let Some(old) = previous.map(|palette| palette.colors()) {
+        for mut text in texts.iter_mut() {
+            for section in text.sections.iter_mut() {
+                if section.style.color == old.text {
+                    section.style.color = colors.text;
+                } else if section.style.color == old.score {
+                    section.style.color = colors.score;
+                }
+            }
+        }
+    }
+
+    for (mut sprite, tint) in enemies.iter_mut() {
+        sprite.color = tint.apply(colors.enemy);
+    }
+
+    match state.get() {
+        GameState::Playing | GameState::Dying => {}
+        _ => clear_color.0 = colors.background,
+    }
+
+    *previous = Some(settings.palette);
+}
@@ -0,0 +1,179 @@
+use std::ops::Range;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    bounds::DespawnOutOfBounds,
+    lifetime::Lifetime,
+    states::{InRun, RunActive, RunState},
+    time_scale::TimeScale,
+    SpriteSheet, SPRITE_SIZE,
+};
+
+const PARTICLE_SPRITE: usize = 879;
+const PARTICLE_SPEED: Range<f32> = 40.0..180.0;
+const PARTICLE_LIFETIME: Range<f32> = 0.4..0.9;
+const PARTICLE_GRAVITY: f32 = 240.0;
+const TRAIL_INTERVAL: f32 = 0.04;
+const TRAIL_LIFETIME: f32 = 0.25;
+const TRAIL_ALPHA: f32 = 0.4;
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Trail>()
+            .add_systems(Update, spawn_trails.run_if(in_state(RunState::Running)));
+
+        app.add_systems(
+            Update,
+            (update_particles, fade_ghosts).run_if(in_state(RunActive)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct Particle {
+    velocity: Vec3,
+}
+
+/// Leaves fading copies of the sprite behind it while `enabled`. The enemy
+/// prefab can change how long the copies last and how faint they start.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Trail {
+    pub enabled: bool,
+    /// Seconds each copy takes to fade out.
+    pub lifetime: f32,
+    pub alpha: f32,
+    #[reflect(ignore)]
+    timer: Timer,
+}
+
+impl Default for Trail {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl Trail {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            lifetime: TRAIL_LIFETIME,
+            alpha: TRAIL_ALPHA,
+            timer: Timer::from_seconds(TRAIL_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+/// A copy left by a [`Trail`], with the alpha it fades out from.
+#[derive(Component)]
+struct Ghost(f32);
+
+pub fn spawn_burst(
+    commands: &mut Commands,
+    sprite_sheet: &SpriteSheet,
+    position: Vec3,
+    color: Color,
+    count: usize,
+) {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..count {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let speed = rng.gen_range(PARTICLE_SPEED);
+        let scale = rng.gen_range(0.5..1.5);
+
+        commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite { color, ..default() },
+                    texture: sprite_sheet.texture.clone(),
+                    transform: Transform {
+                        translation: position + Vec3::Z,
+                        scale: Vec3::new(scale, scale, 1.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                sprite_sheet.atlas(PARTICLE_SPRITE),
+            ))
+            .insert(Particle {
+                velocity: Vec3::new(angle.cos(), angle.sin(), 0.0) * speed,
+            })
+            .insert(Lifetime::from_seconds(rng.gen_range(PARTICLE_LIFETIME)))
+            .insert(DespawnOutOfBounds {
+                margin: SPRITE_SIZE,
+            })
+            .insert(StateScoped(InRun));
+    }
+}
+
+fn update_particles(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut query: Query<(&mut Particle, &Lifetime, &mut Transform, &mut Sprite)>,
+) {
+    let delta_time = time_scale.delta_seconds(&time);
+
+    for (mut particle, lifetime, mut transform, mut sprite) in query.iter_mut() {
+        particle.velocity.y -= PARTICLE_GRAVITY * delta_time;
+        transform.translation += particle.velocity * delta_time;
+        sprite.color.set_alpha(1.0 - lifetime.0.fraction());
+    }
+}
+
+fn spawn_trails(
+    mut commands: Commands,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut query: Query<(
+        &mut Trail,
+        &Transform,
+        &Sprite,
+        &Handle<Image>,
+        &TextureAtlas,
+    )>,
+) {
+    for (mut trail, transform, sprite, texture, atlas) in query.iter_mut() {
+        if !trail.enabled || !trail.timer.tick(time_scale.delta(&time)).just_finished() {
+            continue;
+        }
+
+        let mut color = sprite.color;
+        color.set_alpha(trail.alpha);
+        // The lifetime can come from a prefab, and the timer panics on a
+        // negative or non-finite one.
+        let lifetime = if trail.lifetime.is_finite() {
+            trail.lifetime.max(0.0)
+        } else {
+            TRAIL_LIFETIME
+        };
+
+        commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite { color, ..default() },
+                    texture: texture.clone(),
+                    transform: Transform {
+                        translation: transform.translation - Vec3::Z * 0.1,
+                        ..*transform
+                    },
+                    ..default()
+                },
+                atlas.clone(),
+            ))
+            .insert(Ghost(trail.alpha))
+            .insert(Lifetime::from_seconds(lifetime))
+            .insert(StateScoped(InRun));
+    }
+}
+
+fn fade_ghosts(mut query: Query<(&Ghost, &Lifetime, &mut Sprite)>) {
+    for (ghost, lifetime, mut sprite) in query.iter_mut() {
+        let alpha = ghost.0 * (1.0 - lifetime.0.fraction());
+        sprite.color.set_alpha(alpha);
+    }
+}
@@ -0,0 +1,344 @@
+use bevy::{prelude::*, window::WindowFocused};
+
+use crate::{
+    locale::{Locale, LocalizedText},
+    narration::Announcement,
+    settings::Settings,
+    states::{self, MenuSelectEvent, RunState},
+    suspend::SuspendEvent,
+    ui::{TextFont, SCOREBOARD_FONT_SIZE, SUMMARY_FONT_SIZE},
+    video::Monitors,
+};
+
+const PAUSE_ITEMS: &[PauseItem] = &[
+    PauseItem::Resume,
+    PauseItem::SaveAndQuit,
+    PauseItem::Sound,
+    PauseItem::ScreenShake,
+    PauseItem::ReducedMotion,
+    PauseItem::GameSpeed,
+    PauseItem::AssistHitbox,
+    PauseItem::AssistLives,
+    PauseItem::Controls,
+    #[cfg(feature = "global-leaderboard")]
+    PauseItem::SubmitScores,
+    #[cfg(feature = "telemetry")]
+    PauseItem::Telemetry,
+    PauseItem::Crt,
+    PauseItem::Palette,
+    PauseItem::Language,
+    PauseItem::Fullscreen,
+    PauseItem::Monitor,
+    PauseItem::Vsync,
+    PauseItem::FpsCap,
+];
+/// How many items fit on screen below the title. The list scrolls to keep
+/// the selected one in view.
+const VISIBLE_ITEMS: usize = 10;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PauseSelection(0))
+            .add_systems(
+                Update,
+                (pause_game, pause_on_focus_lost).run_if(in_state(RunState::Running)),
+            )
+            .add_systems(OnEnter(RunState::Paused), setup_pause_menu)
+            .add_systems(
+                Update,
+                (navigate_pause_menu, update_pause_menu).run_if(in_state(RunState::Paused)),
+            )
+            .add_systems(OnExit(RunState::Paused), despawn_pause_menu);
+    }
+}
+
+#[derive(Clone, Copy)]
+enum PauseItem {
+    Resume,
+    SaveAndQuit,
+    Sound,
+    ScreenShake,
+    ReducedMotion,
+    GameSpeed,
+    AssistHitbox,
+    AssistLives,
+    Controls,
+    #[cfg(feature = "global-leaderboard")]
+    SubmitScores,
+    #[cfg(feature = "telemetry")]
+    Telemetry,
+    Crt,
+    Palette,
+    Language,
+    Fullscreen,
+    Monitor,
+    Vsync,
+    FpsCap,
+}
+
+impl PauseItem {
+    fn label(&self, settings: &Settings, locale: &Locale) -> String {
+        let on_off = |value: bool| {
+            if value {
+                locale.text("setting-on")
+            } else {
+                locale.text("setting-off")
+            }
+        };
+        let (id, value) = match self {
+            PauseItem::Resume => return locale.text("pause-resume"),
+            PauseItem::SaveAndQuit => return locale.text("pause-save-quit"),
+            PauseItem::Sound => ("pause-sound", on_off(!settings.muted)),
+            PauseItem::ScreenShake => ("pause-screen-shake", on_off(settings.screen_shake)),
+            PauseItem::ReducedMotion => ("pause-reduced-motion", on_off(settings.reduced_motion)),
+            PauseItem::GameSpeed => ("pause-game-speed", settings.game_speed.to_string()),
+            PauseItem::AssistHitbox => ("pause-assist-hitbox", on_off(settings.assist_hitbox)),
+            PauseItem::AssistLives => ("pause-assist-lives", on_off(settings.assist_lives)),
+            PauseItem::Controls => ("pause-controls", locale.text(settings.controls.name_id())),
+            #[cfg(feature = "global-leaderboard")]
+            PauseItem::SubmitScores => ("pause-submit-scores", on_off(settings.submit_scores)),
+            #[cfg(feature = "telemetry")]
+            PauseItem::Telemetry => ("pause-telemetry", on_off(settings.send_telemetry)),
+            PauseItem::Crt => ("pause-crt", on_off(settings.crt)),
+            PauseItem::Palette => ("pause-palette", locale.text(settings.palette.name_id())),
+            PauseItem::Language => ("pause-language", settings.language.name().to_string()),
+            PauseItem::Fullscreen => (
+                "pause-fullscreen",
+                locale.text(settings.fullscreen.name_id()),
+            ),
+            PauseItem::Monitor => ("pause-monitor", (settings.monitor + 1).to_string()),
+            PauseItem::Vsync => ("pause-vsync", on_off(settings.vsync)),
+            PauseItem::FpsCap => (
+                "pause-fps-cap",
+                settings
+                    .fps_cap
+                    .fps()
+                    .map_or_else(|| locale.text("setting-off"), |fps| fps.to_string()),
+            ),
+        };
+        locale.format(id, &[("value", value.into())])
+    }
+}
+
+/// The pause menu's root, despawned when the run is resumed or left.
+#[derive(Component)]
+struct PauseMenu;
+
+#[derive(Component)]
+struct PauseMenuItem(usize);
+
+#[derive(Resource)]
+struct PauseSelection(usize);
+
+/// Whether the item at `index` is in the scrolled window around the
+/// selection.
+fn is_visible(index: usize, selection: usize) -> bool {
+    let first = selection
+        .saturating_sub(VISIBLE_ITEMS / 2)
+        .min(PAUSE_ITEMS.len().saturating_sub(VISIBLE_ITEMS));
+    (first..first + VISIBLE_ITEMS).contains(&index)
+}
+
+fn item_display(index: usize, selection: usize) -> Display {
+    if is_visible(index, selection) {
+        Display::Flex
+    } else {
+        Display::None
+    }
+}
+
+fn pause_game(
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    settings: Res<Settings>,
+    mut next_state: ResMut<NextState<RunState>>,
+) {
+    let preset_pause = settings
+        .controls
+        .pause_pressed(&keyboard_input, &mouse_input);
+    if keyboard_input.just_pressed(KeyCode::Escape) || preset_pause {
+        keyboard_input.reset(KeyCode::Escape);
+        states::try_change(&mut next_state, RunState::Paused);
+    }
+}
+
+/// Pauses when the window loses focus, which is also what a phone reports
+/// when the game is sent to the background. It stays paused on return.
+fn pause_on_focus_lost(
+    mut ev_focused: EventReader<WindowFocused>,
+    mut next_state: ResMut<NextState<RunState>>,
+) {
+    if ev_focused.read().any(|event| !event.focused) {
+        states::try_change(&mut next_state, RunState::Paused);
+    }
+}
+
+fn setup_pause_menu(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut selection: ResMut<PauseSelection>,
+) {
+    selection.0 = 0;
+    let colors = settings.palette.colors();
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            ..default()
+        })
+        .insert(PauseMenu)
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        locale.text("pause-title"),
+                        TextStyle {
+                            font: font.0.clone(),
+                            font_size: SUMMARY_FONT_SIZE,
+                            color: colors.text,
+                        },
+                    ),
+                    ..default()
+                })
+                .insert(LocalizedText("pause-title"));
+
+            for (index, item) in PAUSE_ITEMS.iter().enumerate() {
+                parent
+                    .spawn(TextBundle {
+                        text: Text::from_section(
+                            item.label(&settings, &locale),
+                            TextStyle {
+                                font: font.0.clone(),
+                                font_size: SCOREBOARD_FONT_SIZE,
+                                color: if index == selection.0 {
+                                    colors.score
+                                } else {
+                                    colors.text
+                                },
+                            },
+                        ),
+                        style: Style {
+                            display: item_display(index, selection.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(PauseMenuItem(index));
+            }
+        });
+}
+
+/// The preset's pause button resumes too, which is all a mouse-only player
+/// can do here.
+#[allow(clippy::too_many_arguments)]
+fn navigate_pause_menu(
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut next_state: ResMut<NextState<RunState>>,
+    mut selection: ResMut<PauseSelection>,
+    mut settings: ResMut<Settings>,
+    monitors: Res<Monitors>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+    mut ev_suspend: EventWriter<SuspendEvent>,
+) {
+    let preset_resume = settings
+        .controls
+        .pause_pressed(&keyboard_input, &mouse_input);
+    if keyboard_input.just_pressed(KeyCode::Escape) || preset_resume {
+        keyboard_input.reset(KeyCode::Escape);
+        states::try_change(&mut next_state, RunState::Running);
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        selection.0 = (selection.0 + PAUSE_ITEMS.len() - 1) % PAUSE_ITEMS.len();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        selection.0 = (selection.0 + 1) % PAUSE_ITEMS.len();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Enter) || keyboard_input.just_pressed(KeyCode::Space) {
+        ev_menu.send(MenuSelectEvent);
+
+        match PAUSE_ITEMS[selection.0] {
+            PauseItem::Resume => {
+                keyboard_input.reset(KeyCode::Enter);
+                keyboard_input.reset(KeyCode::Space);
+                states::try_change(&mut next_state, RunState::Running);
+            }
+            PauseItem::SaveAndQuit => {
+                // Space also starts a run from the title.
+                keyboard_input.reset(KeyCode::Enter);
+                keyboard_input.reset(KeyCode::Space);
+                ev_suspend.send(SuspendEvent);
+            }
+            PauseItem::Sound => settings.muted = !settings.muted,
+            PauseItem::ScreenShake => settings.screen_shake = !settings.screen_shake,
+            PauseItem::ReducedMotion => settings.reduced_motion = !settings.reduced_motion,
+            PauseItem::GameSpeed => settings.game_speed = settings.next_game_speed(),
+            PauseItem::AssistHitbox => settings.assist_hitbox = !settings.assist_hitbox,
+            PauseItem::AssistLives => settings.assist_lives = !settings.assist_lives,
+            PauseItem::Controls => settings.controls = settings.controls.next(),
+            #[cfg(feature = "global-leaderboard")]
+            PauseItem::SubmitScores => settings.submit_scores = !settings.submit_scores,
+            #[cfg(feature = "telemetry")]
+            PauseItem::Telemetry => settings.send_telemetry = !settings.send_telemetry,
+            PauseItem::Crt => settings.crt = !settings.crt,
+            PauseItem::Palette => settings.palette = settings.palette.next(),
+            PauseItem::Language => settings.language = settings.language.next(),
+            PauseItem::Fullscreen => settings.fullscreen = settings.fullscreen.next(),
+            PauseItem::Monitor => settings.monitor = (settings.monitor + 1) % monitors.0.max(1),
+            PauseItem::Vsync => settings.vsync = !settings.vsync,
+            PauseItem::FpsCap => settings.fps_cap = settings.fps_cap.next(),
+        }
+    }
+}
+
+fn despawn_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseMenu>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// A new language is only ready once its file is, so labels are rewritten
+/// when the locale changes rather than with the setting.
+fn update_pause_menu(
+    selection: Res<PauseSelection>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut ev_announce: EventWriter<Announcement>,
+    mut query: Query<(&PauseMenuItem, &mut Text, &mut Style)>,
+) {
+    if !selection.is_changed() && !settings.is_changed() && !locale.is_changed() {
+        return;
+    }
+
+    let label = PAUSE_ITEMS[selection.0].label(&settings, &locale);
+    ev_announce.send(Announcement(label));
+
+    let colors = settings.palette.colors();
+    for (item, mut text, mut style) in query.iter_mut() {
+        style.display = item_display(item.0, selection.0);
+        let section = &mut text.sections[0];
+        section.value = PAUSE_ITEMS[item.0].label(&settings, &locale);
+        section.style.color = if item.0 == selection.0 {
+            colors.score
+        } else {
+            colors.text
+        };
+    }
+}
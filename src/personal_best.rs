@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bounds::PlayArea,
+    ghost,
+    player::PlayerCount,
+    replay::{ReplayFrame, ReplayRecorder},
+    save::Save,
+    score::{Ranked, Scoreboard},
+    states::{GameState, RunState},
+    versus::{self, Versus},
+    SpriteSheet,
+};
+
+const PERSONAL_BEST_KEY: &str = "personal-best.ron";
+const BEST_GHOST_COLOR: Color = Color::srgb(1.0, 0.9, 0.4);
+
+/// Keeps the recording of the best solo run and plays it back as a ghost
+/// during later solo runs, so there is a record to race. The ghost only
+/// shows where that run went; enemies come from the current run's seed.
+pub struct PersonalBestPlugin;
+
+impl Plugin for PersonalBestPlugin {
+    fn build(&self, app: &mut App) {
+        let best = PersonalBest::load(&app.world_mut().get_resource_or_insert_with(Save::default));
+        app.insert_resource(best)
+            .init_resource::<BestGhostPlayback>()
+            .add_systems(OnEnter(GameState::Playing), spawn_best_ghost)
+            .add_systems(Update, move_best_ghost.run_if(in_state(RunState::Running)))
+            .add_systems(OnEnter(GameState::GameOver), store_personal_best);
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PersonalBest {
+    pub score: f32,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl PersonalBest {
+    pub fn load(save: &Save) -> Self {
+        save.0
+            .load(PERSONAL_BEST_KEY)
+            .ok()
+            .flatten()
+            .and_then(|bytes| ron::de::from_bytes(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, save: &Save) {
+        let result = ron::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                save.0
+                    .store(PERSONAL_BEST_KEY, contents.as_bytes())
+                    .map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result {
+            warn!("failed to save personal best: {}", err);
+        }
+    }
+}
+
+/// How far the ghost has got through the best run.
+#[derive(Resource, Default)]
+struct BestGhostPlayback {
+    next: usize,
+    elapsed_nanos: u64,
+    /// When the frame at `next` ends, counted from the start of the run.
+    frame_end_nanos: u64,
+}
+
+#[derive(Component)]
+struct BestGhost;
+
+fn spawn_best_ghost(
+    mut commands: Commands,
+    best: Res<PersonalBest>,
+    player_count: Res<PlayerCount>,
+    versus: Res<Versus>,
+    sprite_sheet: Res<SpriteSheet>,
+    play_area: Res<PlayArea>,
+    mut playback: ResMut<BestGhostPlayback>,
+) {
+    *playback = BestGhostPlayback::default();
+    if best.frames.is_empty() || !versus::is_solo_run(&player_count, &versus) {
+        return;
+    }
+
+    let ghost = ghost::spawn_ghost(&mut commands, &sprite_sheet, &play_area, BEST_GHOST_COLOR);
+    commands.entity(ghost).insert(BestGhost);
+}
+
+/// Follows the recorded frame times rather than the fixed step, so the ghost
+/// keeps pace with the clock the run was recorded on. It leaves once the
+/// best run ended, which is the moment the record is beaten.
+fn move_best_ghost(
+    mut commands: Commands,
+    time: Res<Time>,
+    best: Res<PersonalBest>,
+    mut playback: ResMut<BestGhostPlayback>,
+    mut query: Query<(Entity, &mut Transform), With<BestGhost>>,
+) {
+    let (entity, mut transform) = match query.get_single_mut() {
+        Ok(ghost) => ghost,
+        Err(_) => return,
+    };
+
+    playback.elapsed_nanos += time.delta().as_nanos() as u64;
+    while playback.frame_end_nanos <= playback.elapsed_nanos {
+        let frame = match best.frames.get(playback.next) {
+            Some(frame) => frame,
+            None => {
+                commands.entity(entity).despawn_recursive();
+                return;
+            }
+        };
+
+        transform.translation.x = frame.x;
+        playback.frame_end_nanos += frame.delta_nanos;
+        playback.next += 1;
+    }
+}
+
+fn store_personal_best(
+    scoreboard: Res<Scoreboard>,
+    ranked: Res<Ranked>,
+    recorder: Res<ReplayRecorder>,
+    player_count: Res<PlayerCount>,
+    versus: Res<Versus>,
+    save: Res<Save>,
+    mut best: ResMut<PersonalBest>,
+) {
+    let solo = versus::is_solo_run(&player_count, &versus);
+    if !solo || !ranked.0 || scoreboard.score <= best.score {
+        return;
+    }
+
+    *best = PersonalBest {
+        score: scoreboard.score,
+        frames: recorder.frames().to_vec(),
+    };
+    best.save(&save);
+}
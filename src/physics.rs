@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{
+    self as rapier, NoUserData, RapierConfiguration, RapierPhysicsPlugin,
+};
+
+use crate::{
+    enemy::Collider,
+    player::{self, CollisionEvent, Player},
+    settings::Settings,
+    SPRITE_SIZE,
+};
+
+/// Detects player hits with rapier sensors instead of the built-in AABB
+/// check. Enabled by the `rapier` cargo feature.
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(
+            SPRITE_SIZE,
+        ))
+        .add_systems(Startup, disable_gravity)
+        .add_systems(Update, add_sensors)
+        .add_systems(Update, forward_collision_events);
+    }
+}
+
+/// The plugin sets up its configuration for the length unit it was given,
+/// so only the gravity is changed.
+fn disable_gravity(mut config: ResMut<RapierConfiguration>) {
+    config.gravity = Vec2::ZERO;
+}
+
+/// Gives every enemy a sensor the size of one sprite, and the player one the
+/// size of its hitbox. rapier scales them with the entity's transform, so
+/// they match the AABB check. A player's hitbox is fixed once it spawns.
+fn add_sensors(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    players: Query<Entity, Added<Player>>,
+    enemies: Query<Entity, Added<Collider>>,
+) {
+    let player_size = player::hitbox_size(&settings);
+    let sizes = players.iter().map(|entity| (entity, player_size)).chain(
+        enemies
+            .iter()
+            .map(|entity| (entity, Vec2::splat(SPRITE_SIZE))),
+    );
+
+    for (entity, size) in sizes {
+        commands
+            .entity(entity)
+            .insert(rapier::Collider::cuboid(size.x / 2.0, size.y / 2.0))
+            .insert(rapier::Sensor)
+            .insert(rapier::ActiveCollisionTypes::all());
+    }
+
+    for entity in players.iter() {
+        commands
+            .entity(entity)
+            .insert(rapier::ActiveEvents::COLLISION_EVENTS);
+    }
+}
+
+fn forward_collision_events(
+    mut rapier_events: EventReader<rapier::CollisionEvent>,
+    mut ev_collision: EventWriter<CollisionEvent>,
+    players: Query<Entity, With<Player>>,
+) {
+    for event in rapier_events.read() {
+        if let rapier::CollisionEvent::Started(a, b, _) = event {
+            if players.get(*a).is_ok() {
+                ev_collision.send(CollisionEvent(*a, *b));
+            } else if players.get(*b).is_ok() {
+                ev_collision.send(CollisionEvent(*b, *a));
+            }
+        }
+    }
+}
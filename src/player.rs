@@ -0,0 +1,616 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    animation::{DespawnAnim, FrameAnimation, SpawnAnim, Squash},
+    bounds::PlayArea,
+    config::GameConfig,
+    enemy::Collider,
+    flash::FlashEvent,
+    particles,
+    prefab::{ApplyPrefab, Prefab},
+    settings::Settings,
+    shop::{Upgrade, Upgrades},
+    skins::{Wardrobe, SKINS},
+    states::{self, GameState, InRun, RunState},
+    time_scale::{HitStopEvent, TimeScale},
+    versus::{self, Versus},
+    GameSystem, SpriteSheet, ACTOR_Z, SPRITE_SIZE,
+};
+#[cfg(not(feature = "rapier"))]
+use crate::{collision::Aabb, movement::Interpolated, spatial::SpatialGrid};
+
+pub const PLAYER_IDLE_FRAMES: &[usize] = &[1042, 1044];
+pub const PLAYER_MOVE_FRAMES: &[usize] = &[1043, 1042];
+const PLAYER_FRAME_SECONDS: f32 = 0.2;
+const PLAYER_MAX_LEAN: f32 = 0.25;
+const PLAYER_LEAN_EASING: f32 = 12.0;
+const PLAYER_STOP_SQUASH: Vec2 = Vec2::new(0.3, -0.25);
+const PLAYER_START_STRETCH: Vec2 = Vec2::new(-0.2, 0.3);
+const PLAYER_SHADOW_OFFSET: Vec3 = Vec3::new(0.0, -7.0, -0.5);
+const DEATH_DELAY: f32 = 1.0;
+const DEATH_PARTICLES: usize = 32;
+const DEATH_FLASH_SECONDS: f32 = 0.2;
+const DEATH_BLINK_SECONDS: f32 = 0.1;
+const DEATH_HIT_STOP_FRAMES: u32 = 4;
+/// How far apart, as a fraction of the play area's width, co-op players
+/// start.
+const CO_OP_SPACING: f32 = 0.25;
+const PLAYER_TWO_COLOR: Color = Color::srgb(0.6, 0.8, 1.0);
+/// Stick pushes smaller than this are ignored.
+const STICK_DEAD_ZONE: f32 = 0.2;
+/// How much of the sprite can be hit with the small hitbox assist.
+const ASSIST_HITBOX_SCALE: f32 = 0.5;
+const ASSIST_EXTRA_LIVES: u32 = 2;
+const INVULNERABLE_SECONDS: f32 = 2.0;
+const LIFE_LOST_FLASH_SECONDS: f32 = 0.1;
+const SHIELD_SIZE: f32 = SPRITE_SIZE * 1.5;
+const SHIELD_COLOR: Color = Color::srgba(0.5, 0.8, 1.0, 0.3);
+
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerCount>()
+            .add_event::<CollisionEvent>()
+            .add_event::<PlayerDeathEvent>()
+            .add_systems(OnEnter(GameState::Playing), spawn_player)
+            .add_systems(
+                Update,
+                (
+                    player_movement,
+                    end_on_collision.in_set(GameSystem::Death),
+                    blink_invulnerable,
+                )
+                    .run_if(in_state(RunState::Running)),
+            )
+            .add_systems(
+                Update,
+                (animate_death, finish_run).run_if(in_state(GameState::Dying)),
+            );
+
+        #[cfg(not(feature = "rapier"))]
+        app.add_systems(
+            FixedUpdate,
+            check_collisions
+                .in_set(GameSystem::Collision)
+                .after(GameSystem::SpatialGrid)
+                .run_if(in_state(RunState::Running)),
+        );
+    }
+}
+
+/// Players are numbered from 0. Each is steered with its own keys and the
+/// gamepad with the same number.
+#[derive(Component)]
+pub struct Player {
+    pub index: usize,
+}
+
+/// How many players the next run starts with, chosen on the title and game
+/// over screens. Co-op players share the score, and the run ends once
+/// everyone is down.
+#[derive(Resource)]
+pub struct PlayerCount(pub usize);
+
+/// The most players a run can start with, in co-op.
+pub const MAX_PLAYERS: usize = 2;
+
+impl Default for PlayerCount {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+#[derive(Component)]
+pub struct PlayerControls {
+    left: KeyCode,
+    right: KeyCode,
+    last_direction: f32,
+    /// Scales `player_speed`, for the speed upgrade.
+    speed_multiplier: f32,
+}
+
+impl PlayerControls {
+    fn new(index: usize) -> Self {
+        let (left, right) = match index {
+            0 => (KeyCode::ArrowLeft, KeyCode::ArrowRight),
+            _ => (KeyCode::KeyA, KeyCode::KeyD),
+        };
+
+        Self {
+            left,
+            right,
+            last_direction: 0.0,
+            speed_multiplier: 1.0,
+        }
+    }
+
+    /// Which way the player moved last frame: -1, 0 or 1.
+    pub fn direction(&self) -> f32 {
+        self.last_direction
+    }
+}
+
+/// The frames a player animates through, from their skin.
+#[derive(Component)]
+struct PlayerFrames {
+    idle: &'static [usize],
+    moving: &'static [usize],
+}
+
+/// Hits a player can still take before they are down, from the extra lives
+/// assist.
+#[derive(Component)]
+struct Lives(u32);
+
+/// Takes the next hit instead of a life, from the shield upgrade. Holds the
+/// bubble drawn around the player.
+#[derive(Component)]
+struct Shield(Entity);
+
+/// Briefly set after losing a life, while the player can't be hit.
+#[derive(Component)]
+struct Invulnerable(Timer);
+
+#[derive(Event)]
+pub struct CollisionEvent(pub Entity, pub Entity);
+/// A player went down for good, with the enemy that hit them.
+#[derive(Event)]
+pub struct PlayerDeathEvent {
+    pub enemy: Entity,
+}
+
+/// The part of a player that enemies can hit, centered on it.
+pub fn hitbox_size(settings: &Settings) -> Vec2 {
+    let scale = if settings.assist_hitbox {
+        ASSIST_HITBOX_SCALE
+    } else {
+        1.0
+    };
+    Vec2::splat(SPRITE_SIZE * scale)
+}
+
+/// Counts down the death sequence; the run ends when it finishes.
+#[derive(Resource)]
+pub struct DeathTimer(pub Timer);
+
+/// What a suspended run keeps of a player who is still in it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PlayerSnapshot {
+    pub index: usize,
+    pub x: f32,
+    pub lives: u32,
+    pub shield: bool,
+}
+
+pub fn snapshot_players(world: &mut World) -> Vec<PlayerSnapshot> {
+    world
+        .query::<(&Player, &Transform, &Lives, Option<&Shield>)>()
+        .iter(world)
+        .map(|(player, transform, lives, shield)| PlayerSnapshot {
+            index: player.index,
+            x: transform.translation.x,
+            lives: lives.0,
+            shield: shield.is_some(),
+        })
+        .collect()
+}
+
+/// Puts the players of a run that has just started back the way a suspended
+/// one left them. Players missing from `players` were already down, and are
+/// removed. A shield that was used up isn't given back, but one can't be
+/// added either, since it comes from the shop's upgrade. Everyone starts
+/// briefly invulnerable, to get their bearings.
+pub fn restore_players(world: &mut World, players: &[PlayerSnapshot]) {
+    let mut down = Vec::new();
+    let mut used_shields = Vec::new();
+    let mut query = world.query::<(Entity, &Player, &mut Transform, &mut Lives, Option<&Shield>)>();
+    for (entity, player, mut transform, mut lives, shield) in query.iter_mut(world) {
+        let snapshot = match players
+            .iter()
+            .find(|snapshot| snapshot.index == player.index)
+        {
+            Some(snapshot) => snapshot,
+            None => {
+                down.push(entity);
+                continue;
+            }
+        };
+
+        transform.translation.x = snapshot.x;
+        lives.0 = snapshot.lives;
+        if let (Some(shield), false) = (shield, snapshot.shield) {
+            used_shields.push((entity, shield.0));
+        }
+    }
+
+    for entity in down {
+        world.entity_mut(entity).despawn_recursive();
+    }
+    for (entity, bubble) in used_shields {
+        world.entity_mut(bubble).despawn_recursive();
+        world.entity_mut(entity).remove::<Shield>();
+    }
+
+    let players: Vec<Entity> = world
+        .query_filtered::<Entity, With<Player>>()
+        .iter(world)
+        .collect();
+    for entity in players {
+        world
+            .entity_mut(entity)
+            .insert(Invulnerable(Timer::from_seconds(
+                INVULNERABLE_SECONDS,
+                TimerMode::Once,
+            )));
+    }
+}
+
+/// Player one wears the equipped skin. Other players keep the first skin,
+/// tinted so they can be told apart. Upgrades apply to every player.
+#[allow(clippy::too_many_arguments)]
+fn spawn_player(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    sprite_sheet: Res<SpriteSheet>,
+    play_area: Res<PlayArea>,
+    player_count: Res<PlayerCount>,
+    settings: Res<Settings>,
+    wardrobe: Option<Res<Wardrobe>>,
+    upgrades: Option<Res<Upgrades>>,
+) {
+    // Neither skins nor upgrades are part of the headless simulation.
+    let upgrades = upgrades.as_deref().cloned().unwrap_or_default();
+    let assist_lives = if settings.assist_lives {
+        ASSIST_EXTRA_LIVES
+    } else {
+        0
+    };
+    let lives = assist_lives + upgrades.extra_lives();
+    let spacing = (play_area.x.end - play_area.x.start) * CO_OP_SPACING;
+    let first_x = -spacing * player_count.0.saturating_sub(1) as f32 / 2.0;
+
+    for index in 0..player_count.0 {
+        let skin = match &wardrobe {
+            Some(wardrobe) if index == 0 => wardrobe.skin(),
+            _ => &SKINS[0],
+        };
+        let mut sprite = Sprite::default();
+        if index > 0 {
+            sprite.color = PLAYER_TWO_COLOR;
+        } else if let Some(color) = skin.color {
+            sprite.color = color;
+        }
+
+        let mut controls = PlayerControls::new(index);
+        controls.speed_multiplier = upgrades.speed_multiplier();
+
+        let player = commands
+            .spawn((
+                SpriteBundle {
+                    texture: sprite_sheet.texture.clone(),
+                    transform: Transform {
+                        translation: Vec3::new(
+                            first_x + spacing * index as f32,
+                            play_area.y.start,
+                            ACTOR_Z,
+                        ),
+                        scale: Vec3::splat(1.0),
+                        ..default()
+                    },
+                    sprite,
+                    ..default()
+                },
+                sprite_sheet.atlas(skin.idle[0]),
+            ))
+            .insert(Player { index })
+            .insert(controls)
+            .insert(PlayerFrames {
+                idle: skin.idle,
+                moving: skin.moving,
+            })
+            .insert(Lives(lives))
+            .insert(StateScoped(InRun))
+            .insert(Squash::default())
+            .insert(FrameAnimation::new(skin.idle, PLAYER_FRAME_SECONDS))
+            .with_children(|parent| {
+                parent.spawn(SpriteBundle {
+                    texture: asset_server.load("shadow.png"),
+                    transform: Transform::from_translation(PLAYER_SHADOW_OFFSET),
+                    ..default()
+                });
+            })
+            .id();
+        commands.add(ApplyPrefab {
+            entity: player,
+            prefab: Prefab::Player,
+        });
+
+        if upgrades.is_active(Upgrade::Shield) {
+            let bubble = commands
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: SHIELD_COLOR,
+                        custom_size: Some(Vec2::splat(SHIELD_SIZE)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                    ..default()
+                })
+                .id();
+            commands
+                .entity(player)
+                .add_child(bubble)
+                .insert(Shield(bubble));
+        }
+    }
+}
+
+/// A solo player also gets the extra bindings of their control preset.
+#[allow(clippy::too_many_arguments)]
+fn player_movement(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    config: Res<GameConfig>,
+    settings: Res<Settings>,
+    player_count: Res<PlayerCount>,
+    versus: Option<Res<Versus>>,
+    mut query: Query<(
+        &Player,
+        &PlayerFrames,
+        &mut PlayerControls,
+        &mut Transform,
+        &mut FrameAnimation,
+        &mut Squash,
+    )>,
+) {
+    let delta_time = time_scale.delta_seconds(&time);
+    // Versus mode isn't part of the headless simulation.
+    let solo = versus.map_or(player_count.0 == 1, |versus| {
+        versus::is_solo_run(&player_count, &versus)
+    });
+
+    for (player, frames, mut controls, mut transform, mut animation, mut squash) in query.iter_mut()
+    {
+        let gamepad = Gamepad::new(player.index);
+        let stick = gamepad_axes
+            .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+            .unwrap_or_default();
+        let mut direction = 0.0;
+
+        if keyboard_input.pressed(controls.left)
+            || gamepad_input.pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadLeft))
+            || stick < -STICK_DEAD_ZONE
+        {
+            direction -= 1.0;
+        }
+
+        if keyboard_input.pressed(controls.right)
+            || gamepad_input.pressed(GamepadButton::new(gamepad, GamepadButtonType::DPadRight))
+            || stick > STICK_DEAD_ZONE
+        {
+            direction += 1.0;
+        }
+
+        if player.index == 0 && solo {
+            direction += settings.controls.direction(&keyboard_input, &mouse_input);
+            direction = direction.clamp(-1.0, 1.0);
+        }
+
+        let speed = config.player_speed * controls.speed_multiplier;
+        let new_position = transform.translation.x + direction * speed * delta_time;
+        transform.translation.x = new_position;
+
+        let lean = Quat::from_rotation_z(-direction * PLAYER_MAX_LEAN);
+        let easing = (PLAYER_LEAN_EASING * delta_time).min(1.0);
+        transform.rotation = transform.rotation.slerp(lean, easing);
+
+        if direction == 0.0 {
+            animation.play(frames.idle, false);
+        } else {
+            animation.play(frames.moving, direction < 0.0);
+        }
+
+        if direction != controls.last_direction {
+            squash.deform(if direction == 0.0 {
+                PLAYER_STOP_SQUASH
+            } else {
+                PLAYER_START_STRETCH
+            });
+        }
+        controls.last_direction = direction;
+    }
+}
+
+/// Sends a [`CollisionEvent`] for every enemy the player overlaps. Candidates
+/// come from the [`SpatialGrid`], which must be rebuilt first.
+#[cfg(not(feature = "rapier"))]
+pub fn check_collisions(
+    mut ev_collision: EventWriter<CollisionEvent>,
+    grid: Res<SpatialGrid>,
+    settings: Res<Settings>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    projectile_query: Query<(&Interpolated, &Transform), With<Collider>>,
+) {
+    let player_size = hitbox_size(&settings);
+
+    for (player, player_transform) in player_query.iter() {
+        let player_center = player_transform.translation.truncate();
+        let player_box = Aabb::new(player_center.into(), player_size.into());
+        for projectile in grid.query(player_center, player_size) {
+            let (position, transform) = match projectile_query.get(projectile) {
+                Ok(projectile) => projectile,
+                Err(_) => continue,
+            };
+
+            let enemy_box = Aabb::new(
+                position.current.truncate().into(),
+                (transform.scale.truncate() * SPRITE_SIZE).into(),
+            );
+
+            if player_box.overlaps(&enemy_box) {
+                ev_collision.send(CollisionEvent(player, projectile));
+            }
+        }
+    }
+}
+
+type HitPlayer<'a> = (
+    &'a Transform,
+    &'a mut Lives,
+    Option<&'a Shield>,
+    Option<&'a Invulnerable>,
+);
+
+/// A player that is hit loses their shield or a life if they have one left,
+/// and is out of the run otherwise. The last one standing plays the death sequence;
+/// anyone hit before that bursts and is removed straight away, so the
+/// others can keep going.
+#[allow(clippy::too_many_arguments)]
+fn end_on_collision(
+    mut commands: Commands,
+    mut ev_collision: EventReader<CollisionEvent>,
+    mut ev_death: EventWriter<PlayerDeathEvent>,
+    mut ev_flash: EventWriter<FlashEvent>,
+    mut ev_hit_stop: EventWriter<HitStopEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    sprite_sheet: Res<SpriteSheet>,
+    settings: Res<Settings>,
+    mut player_query: Query<HitPlayer, With<Player>>,
+    enemy_query: Query<&Transform, With<Collider>>,
+) {
+    let mut alive = player_query.iter().count();
+    let mut hit = Vec::new();
+
+    for collision in ev_collision.read() {
+        if alive == 0 || hit.contains(&collision.0) {
+            continue;
+        }
+
+        let (transform, mut lives, shield, invulnerable) = match player_query.get_mut(collision.0) {
+            Ok(player) => player,
+            Err(_) => continue,
+        };
+        if invulnerable.is_some() {
+            continue;
+        }
+        hit.push(collision.0);
+
+        if let Ok(enemy_transform) = enemy_query.get(collision.1) {
+            commands
+                .entity(collision.1)
+                .remove::<Collider>()
+                .remove::<SpawnAnim>()
+                .insert(DespawnAnim::new(enemy_transform.scale));
+        }
+
+        let saved = if let Some(shield) = shield {
+            commands.entity(shield.0).despawn_recursive();
+            commands.entity(collision.0).remove::<Shield>();
+            true
+        } else if lives.0 > 0 {
+            lives.0 -= 1;
+            true
+        } else {
+            false
+        };
+        if saved {
+            commands
+                .entity(collision.0)
+                .insert(Invulnerable(Timer::from_seconds(
+                    INVULNERABLE_SECONDS,
+                    TimerMode::Once,
+                )));
+            ev_flash.send(FlashEvent {
+                color: Color::srgba(1.0, 1.0, 1.0, 0.4),
+                duration: LIFE_LOST_FLASH_SECONDS,
+            });
+            continue;
+        }
+
+        if alive == 1 {
+            if !states::try_change(&mut next_state, GameState::Dying) {
+                return;
+            }
+            commands.insert_resource(DeathTimer(Timer::from_seconds(
+                DEATH_DELAY,
+                TimerMode::Once,
+            )));
+        } else {
+            commands.entity(collision.0).despawn_recursive();
+        }
+        alive -= 1;
+
+        particles::spawn_burst(
+            &mut commands,
+            &sprite_sheet,
+            transform.translation,
+            settings.palette.colors().text,
+            DEATH_PARTICLES,
+        );
+        ev_death.send(PlayerDeathEvent { enemy: collision.1 });
+        ev_flash.send(FlashEvent {
+            color: Color::srgba(1.0, 1.0, 1.0, 0.8),
+            duration: DEATH_FLASH_SECONDS,
+        });
+        ev_hit_stop.send(HitStopEvent(DEATH_HIT_STOP_FRAMES));
+    }
+}
+
+fn blink_invulnerable(
+    mut commands: Commands,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut query: Query<(Entity, &mut Invulnerable, &mut Sprite)>,
+) {
+    for (entity, mut invulnerable, mut sprite) in query.iter_mut() {
+        let timer = &mut invulnerable.0;
+        if timer.tick(time_scale.delta(&time)).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+            sprite.color.set_alpha(1.0);
+        } else {
+            let blink = ((timer.elapsed_secs() / DEATH_BLINK_SECONDS) as u32).is_multiple_of(2);
+            sprite.color.set_alpha(if blink { 1.0 } else { 0.25 });
+        }
+    }
+}
+
+fn animate_death(
+    death_timer: Option<Res<DeathTimer>>,
+    mut query: Query<(&mut Transform, &mut Sprite), With<Player>>,
+) {
+    let death_timer = match death_timer {
+        Some(death_timer) => death_timer,
+        None => return,
+    };
+    let timer = &death_timer.0;
+
+    let blink = ((timer.elapsed_secs() / DEATH_BLINK_SECONDS) as u32).is_multiple_of(2);
+    for (mut transform, mut sprite) in query.iter_mut() {
+        transform.scale = Vec3::splat(1.0 - timer.fraction());
+        sprite.color.set_alpha(if blink { 1.0 } else { 0.25 });
+    }
+}
+
+fn finish_run(
+    mut commands: Commands,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    death_timer: Option<ResMut<DeathTimer>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let Some(mut death_timer) = death_timer {
+        // Kept until the change is queued, so another change queued in the
+        // same frame only puts the game over screen off until the next one.
+        if death_timer.0.tick(time_scale.delta(&time)).finished()
+            && states::try_change(&mut next_state, GameState::GameOver)
+        {
+            commands.remove_resource::<DeathTimer>();
+        }
+    }
+}
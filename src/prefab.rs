@@ -0,0 +1,127 @@
+use bevy::{
+    ecs::{reflect::ReflectComponent, world::Command},
+    prelude::*,
+    utils::HashMap,
+};
+
+use crate::loading::LoadingAssets;
+
+/// Lets the player, enemies and the HUD be tweaked from scene files in
+/// `assets/prefabs`, without rebuilding the game. The spawners still build
+/// each entity in code, then the matching [`Prefab`] is applied over it:
+/// every component in the file replaces the fields it lists, or is added if
+/// the entity doesn't have it yet. With the asset folder watched, an edited
+/// prefab is used by the next entity spawned from it.
+///
+/// The scenes are an overlay rather than the whole entity. Gameplay
+/// components like colliders and controls have to be there in the headless
+/// simulation, which loads no assets, and sprites point into the sprite
+/// sheet through a handle a scene file can't name.
+///
+/// Only components registered with [`App::register_type`] can be used in a
+/// prefab. The ones the game registers only change how things look, so
+/// prefabs don't change how a run plays out and replays stay in step.
+pub struct PrefabPlugin;
+
+impl Plugin for PrefabPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_prefabs);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Prefab {
+    Player,
+    Enemy,
+    /// The score text.
+    Hud,
+}
+
+impl Prefab {
+    const ALL: [Prefab; 3] = [Prefab::Player, Prefab::Enemy, Prefab::Hud];
+
+    fn path(&self) -> &'static str {
+        match self {
+            Prefab::Player => "prefabs/player.scn.ron",
+            Prefab::Enemy => "prefabs/enemy.scn.ron",
+            Prefab::Hud => "prefabs/hud.scn.ron",
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Prefabs(HashMap<Prefab, Handle<DynamicScene>>);
+
+fn load_prefabs(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    let handles = Prefab::ALL
+        .into_iter()
+        .map(|prefab| {
+            let handle = asset_server.load(prefab.path());
+            loading.add(&handle);
+            (prefab, handle)
+        })
+        .collect();
+    commands.insert_resource(Prefabs(handles));
+}
+
+/// Applies a prefab to an entity once the commands before it have spawned
+/// it. See [`apply`].
+pub struct ApplyPrefab {
+    pub entity: Entity,
+    pub prefab: Prefab,
+}
+
+impl Command for ApplyPrefab {
+    fn apply(self, world: &mut World) {
+        apply(world, self.entity, self.prefab);
+    }
+}
+
+/// Applies the components of every entity in the prefab's scene to `entity`.
+/// Does nothing in the headless simulation, which has no prefabs, or if the
+/// prefab failed to load.
+pub fn apply(world: &mut World, entity: Entity, prefab: Prefab) {
+    let handle = match world
+        .get_resource::<Prefabs>()
+        .and_then(|prefabs| prefabs.0.get(&prefab))
+    {
+        Some(handle) => handle.clone(),
+        None => return,
+    };
+    if world.get_entity(entity).is_none() {
+        return;
+    }
+
+    world.resource_scope(|world, scenes: Mut<Assets<DynamicScene>>| {
+        let scene = match scenes.get(&handle) {
+            Some(scene) => scene,
+            None => return,
+        };
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+        let mut target = world.entity_mut(entity);
+
+        for component in scene.entities.iter().flat_map(|entity| &entity.components) {
+            let reflect = match component
+                .get_represented_type_info()
+                .and_then(|info| registry.get(info.type_id()))
+                .and_then(|registration| registration.data::<ReflectComponent>())
+            {
+                Some(reflect) => reflect,
+                None => {
+                    warn!(
+                        "{} can't be used in a prefab, it isn't a registered component",
+                        component.reflect_type_path()
+                    );
+                    continue;
+                }
+            };
+
+            reflect.apply_or_insert(&mut target, &**component, &registry);
+        }
+    });
+}
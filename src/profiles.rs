@@ -0,0 +1,486 @@
+use std::sync::Arc;
+
+use bevy::{
+    ecs::world::Command,
+    input::{
+        keyboard::{Key, KeyboardInput},
+        InputSystem,
+    },
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    console::{self, AddConsoleCommand},
+    daily::DailyResults,
+    history::RunHistory,
+    launch::LaunchOptions,
+    leaderboard::Leaderboard,
+    locale::Locale,
+    personal_best::PersonalBest,
+    replay::ReplayCount,
+    save::{PrefixBackend, Save, SaveBackend},
+    settings::Settings,
+    shop::Upgrades,
+    skins::Wardrobe,
+    states::{self, GameState, MenuSelectEvent},
+    suspend::SuspendedRun,
+    ui::TextFont,
+    GameSystem,
+};
+
+/// Kept outside every profile, since it lists them.
+const PROFILES_KEY: &str = "profiles.ron";
+const DEFAULT_PROFILE_NAME: &str = "Player";
+const MAX_NAME_LENGTH: usize = 12;
+const PROFILES_FONT_SIZE: f32 = 16.0;
+const PROFILES_PADDING: f32 = 16.0;
+/// The keys the profile screen still answers to while a name is typed.
+const NAME_KEYS: [KeyCode; 5] = [
+    KeyCode::Escape,
+    KeyCode::ArrowUp,
+    KeyCode::ArrowDown,
+    KeyCode::Backspace,
+    KeyCode::Enter,
+];
+
+/// Named profiles, each with its own settings, scores, unlocks, coins and
+/// run history. Everything that goes through [`Save`] is kept per profile,
+/// by prefixing its keys with the profile's.
+///
+/// The profile is picked with `--profile <name>`, from the profile screen
+/// opened with P on the title, or with the `profile` console command, and
+/// the last one used is picked again at the next start. The first profile
+/// uses the keys from before there were profiles, so existing saves become
+/// it. Has to be added before any plugin that loads from [`Save`].
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        let backend: Arc<dyn SaveBackend> = Arc::from(
+            app.world_mut()
+                .remove_resource::<Save>()
+                .unwrap_or_default()
+                .0,
+        );
+        let mut profiles = Profiles::load(&*backend);
+        let launch_profile = app
+            .world()
+            .get_resource::<LaunchOptions>()
+            .and_then(|options| options.profile.clone());
+        if let Some(name) = launch_profile {
+            profiles.active = profiles.find_or_add(&name);
+            profiles.save(&*backend);
+        }
+        info!("playing as {}", profiles.active().name);
+
+        app.insert_resource(profiles.backend(&backend))
+            .insert_resource(ProfileStore(backend))
+            .insert_resource(profiles)
+            .init_resource::<ProfileSelection>()
+            .add_console_command("profile", profile_command)
+            .add_systems(OnEnter(GameState::Title), spawn_profile_name)
+            .add_systems(Update, open_profiles.run_if(in_state(GameState::Title)))
+            .add_systems(
+                PreUpdate,
+                take_name_keys
+                    .in_set(GameSystem::TextInput)
+                    .after(InputSystem),
+            )
+            .add_systems(OnEnter(GameState::Profiles), spawn_profiles)
+            .add_systems(
+                Update,
+                (browse_profiles, update_profiles.after(browse_profiles))
+                    .run_if(in_state(GameState::Profiles)),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Debug, Deserialize, Serialize)]
+pub struct Profiles {
+    pub profiles: Vec<Profile>,
+    /// Index of the profile in use, which is also the one picked at the next
+    /// start.
+    pub active: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Profile {
+    pub name: String,
+    /// Put in front of every key the profile saves under.
+    pub prefix: String,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Self {
+            profiles: vec![Profile {
+                name: DEFAULT_PROFILE_NAME.to_string(),
+                prefix: String::new(),
+            }],
+            active: 0,
+        }
+    }
+}
+
+impl Profiles {
+    fn load(backend: &dyn SaveBackend) -> Self {
+        let profiles: Self = backend
+            .load(PROFILES_KEY)
+            .ok()
+            .flatten()
+            .and_then(|bytes| ron::de::from_bytes(&bytes).ok())
+            .unwrap_or_default();
+
+        if profiles.profiles.is_empty() {
+            return Self::default();
+        }
+        let active = profiles.active.min(profiles.profiles.len() - 1);
+        Self { active, ..profiles }
+    }
+
+    fn save(&self, backend: &dyn SaveBackend) {
+        let result = ron::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                backend
+                    .store(PROFILES_KEY, contents.as_bytes())
+                    .map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result {
+            warn!("failed to save profiles: {}", err);
+        }
+    }
+
+    pub fn active(&self) -> &Profile {
+        &self.profiles[self.active]
+    }
+
+    /// Names are matched ignoring case, so "alice" and "Alice" are the same
+    /// profile.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.profiles
+            .iter()
+            .position(|profile| profile.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Profiles are never removed, so their index keeps prefixes unique.
+    fn find_or_add(&mut self, name: &str) -> usize {
+        if let Some(index) = self.find(name) {
+            return index;
+        }
+
+        let index = self.profiles.len();
+        self.profiles.push(Profile {
+            name: name.to_string(),
+            prefix: format!("profile{}-", index),
+        });
+        index
+    }
+
+    /// The [`Save`] for the active profile.
+    fn backend(&self, backend: &Arc<dyn SaveBackend>) -> Save {
+        Save(Box::new(PrefixBackend {
+            backend: backend.clone(),
+            prefix: self.active().prefix.clone(),
+        }))
+    }
+}
+
+/// The backend every profile is saved in, unprefixed.
+#[derive(Resource)]
+struct ProfileStore(Arc<dyn SaveBackend>);
+
+/// Switches to the profile with the given name, creating it if there is
+/// none, and reloads everything saved per profile.
+pub struct SelectProfile(pub String);
+
+impl Command for SelectProfile {
+    fn apply(self, world: &mut World) {
+        let backend = world.get_resource::<ProfileStore>().unwrap().0.clone();
+        let mut profiles = world.get_resource_mut::<Profiles>().unwrap();
+        let index = profiles.find_or_add(&self.0);
+        profiles.active = index;
+        profiles.save(&*backend);
+        info!("switched to profile {}", profiles.active().name);
+        let save = profiles.backend(&backend);
+        world.insert_resource(save);
+
+        reload(world, Settings::load);
+        reload(world, Leaderboard::load);
+        reload(world, PersonalBest::load);
+        reload(world, RunHistory::load);
+        reload(world, Wardrobe::load);
+        reload(world, Upgrades::load);
+        reload(world, DailyResults::load);
+        reload(world, SuspendedRun::load);
+        reload(world, ReplayCount::load);
+    }
+}
+
+/// Plugins can be left out of the group, so only what is there is reloaded.
+fn reload<T: Resource>(world: &mut World, load: fn(&Save) -> T) {
+    if world.contains_resource::<T>() {
+        let value = load(world.get_resource::<Save>().unwrap());
+        world.insert_resource(value);
+    }
+}
+
+/// What the profile screen has selected. One past the last profile is the
+/// row for making a new one, with the name typed so far.
+#[derive(Resource, Default)]
+struct ProfileSelection {
+    selected: usize,
+    name: String,
+}
+
+#[derive(Component)]
+struct ProfilesText;
+
+fn spawn_profile_name(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    profiles: Res<Profiles>,
+) {
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                locale.format(
+                    "profile-current",
+                    &[("name", profiles.active().name.clone().into())],
+                ),
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: PROFILES_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(PROFILES_PADDING),
+                left: Val::Px(PROFILES_PADDING),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(StateScoped(GameState::Title));
+}
+
+fn open_profiles(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyP)
+        && states::try_change(&mut next_state, GameState::Profiles)
+    {
+        ev_menu.send(MenuSelectEvent);
+    }
+}
+
+fn spawn_profiles(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mut selection: ResMut<ProfileSelection>,
+) {
+    *selection = ProfileSelection {
+        selected: profiles.active,
+        name: String::new(),
+    };
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: PROFILES_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(PROFILES_PADDING),
+                left: Val::Px(PROFILES_PADDING),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(ProfilesText)
+        .insert(StateScoped(GameState::Profiles));
+}
+
+/// Typing a new profile's name doesn't reach the rest of the game, so an M
+/// in it doesn't toggle mute.
+fn take_name_keys(
+    state: Res<State<GameState>>,
+    profiles: Res<Profiles>,
+    selection: Res<ProfileSelection>,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+) {
+    if state.get() == &GameState::Profiles && selection.selected == profiles.profiles.len() {
+        console::take_typed_keys(&mut keyboard_input, &NAME_KEYS);
+    }
+}
+
+/// Up and down pick a profile, and Enter plays as it. On the last row, typing
+/// names a new profile and Enter creates it. Escape goes back to the title.
+fn browse_profiles(
+    mut commands: Commands,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut ev_keyboard: EventReader<KeyboardInput>,
+    profiles: Res<Profiles>,
+    mut selection: ResMut<ProfileSelection>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        keyboard_input.reset(KeyCode::Escape);
+        states::try_change(&mut next_state, GameState::Title);
+        return;
+    }
+
+    let new_row = profiles.profiles.len();
+    if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        selection.selected = (selection.selected + 1).min(new_row);
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        selection.selected = selection.selected.saturating_sub(1);
+    }
+
+    let typed: Vec<char> = ev_keyboard
+        .read()
+        .filter(|event| event.state.is_pressed())
+        .filter_map(|event| match &event.logical_key {
+            Key::Character(text) => Some(text.as_str()),
+            Key::Space => Some(" "),
+            _ => None,
+        })
+        .flat_map(str::chars)
+        .collect();
+    if selection.selected == new_row {
+        for char in typed {
+            if is_name_char(char) && selection.name.len() < MAX_NAME_LENGTH {
+                selection.name.push(char);
+            }
+        }
+        if keyboard_input.just_pressed(KeyCode::Backspace) {
+            selection.name.pop();
+        }
+    }
+
+    if !keyboard_input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+    let name = match profiles.profiles.get(selection.selected) {
+        Some(profile) => profile.name.clone(),
+        None => selection.name.trim().to_string(),
+    };
+    if name.is_empty() {
+        return;
+    }
+
+    keyboard_input.reset(KeyCode::Enter);
+    if states::try_change(&mut next_state, GameState::Title) {
+        if selection.selected != profiles.active {
+            commands.add(SelectProfile(name));
+        }
+        ev_menu.send(MenuSelectEvent);
+    }
+}
+
+fn update_profiles(
+    profiles: Res<Profiles>,
+    selection: Res<ProfileSelection>,
+    locale: Res<Locale>,
+    mut texts: Query<&mut Text, With<ProfilesText>>,
+) {
+    let marker = |index: usize| {
+        if index == selection.selected {
+            ">"
+        } else {
+            " "
+        }
+    };
+
+    let mut value = format!("{}\n\n", locale.text("profiles-title"));
+    for (index, profile) in profiles.profiles.iter().enumerate() {
+        value.push_str(&format!(
+            "{} {}{}\n",
+            marker(index),
+            profile.name,
+            if index == profiles.active {
+                format!("  {}", locale.text("profiles-playing"))
+            } else {
+                String::new()
+            }
+        ));
+    }
+    value.push_str(&format!(
+        "{} {}\n",
+        marker(profiles.profiles.len()),
+        locale.format("profiles-new", &[("name", selection.name.clone().into())])
+    ));
+    value.push_str(&format!("\n{}", locale.text("profiles-note")));
+    value.push_str(&format!("\n\n\n{}", locale.text("profiles-help")));
+
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+/// Whether `char` can be typed into a profile name on the profile screen.
+fn is_name_char(char: char) -> bool {
+    char.is_ascii_alphanumeric() || char == ' '
+}
+
+/// Checks a profile name given as text rather than typed on the profile
+/// screen, holding it to what the screen would let be typed.
+pub fn check_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("names can't be empty".to_string());
+    }
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(format!("names are at most {} letters", MAX_NAME_LENGTH));
+    }
+    if !name.chars().all(is_name_char) {
+        return Err("names can only have letters, digits and spaces".to_string());
+    }
+    Ok(())
+}
+
+/// `profile` lists the profiles, `profile <name>` switches to one, creating
+/// it if needed. Profiles are only switched from the title, so a run never
+/// changes hands.
+fn profile_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    if args.is_empty() {
+        let profiles = world.get_resource::<Profiles>().unwrap();
+        let names: Vec<String> = profiles
+            .profiles
+            .iter()
+            .enumerate()
+            .map(|(index, profile)| {
+                if index == profiles.active {
+                    format!("*{}", profile.name)
+                } else {
+                    profile.name.clone()
+                }
+            })
+            .collect();
+        return Ok(names.join(", "));
+    }
+
+    let name = args.join(" ");
+    check_name(&name)?;
+    if world.resource::<State<GameState>>().get() != &GameState::Title {
+        return Err("profiles can only be switched from the title".to_string());
+    }
+
+    SelectProfile(name.clone()).apply(world);
+    Ok(format!("playing as {}", name))
+}
@@ -0,0 +1,143 @@
+use bevy::{
+    input::{gamepad::GamepadButtonChangedEvent, keyboard::KeyboardInput, touch::TouchInput},
+    prelude::*,
+};
+
+use crate::locale::Locale;
+
+/// How far a gamepad button has to be pushed to count as using the gamepad.
+const GAMEPAD_PRESS_THRESHOLD: f32 = 0.5;
+
+/// Keeps every prompt that names a control in line with the device the
+/// player last touched, and with the language.
+pub struct PromptPlugin;
+
+impl Plugin for PromptPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputDevice>()
+            .add_systems(Update, detect_input_device)
+            .add_systems(Update, update_prompts.after(detect_input_device));
+    }
+}
+
+/// The kind of device the player used most recently.
+///
+/// Gamepads aren't told apart by brand, so they all get the same face-button
+/// names.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputDevice {
+    Keyboard,
+    Gamepad,
+    Touch,
+}
+
+impl Default for InputDevice {
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    fn default() -> Self {
+        InputDevice::Keyboard
+    }
+
+    #[cfg(any(target_os = "android", target_os = "ios"))]
+    fn default() -> Self {
+        InputDevice::Touch
+    }
+}
+
+/// Something the player is asked to do. Prompts are written per device
+/// rather than by swapping a button name into one sentence, since "Tap to
+/// start" doesn't fit a "Press ..." pattern.
+#[derive(Clone, Copy, Debug)]
+pub enum Prompt {
+    Start,
+    Modes,
+    /// The other screens the title leads to.
+    Screens,
+    /// Shown on the title while a suspended run is waiting.
+    Continue,
+    SaveReplay,
+    SaveGhost,
+}
+
+impl Prompt {
+    /// The pixel font has no button glyphs, so gamepad buttons are named
+    /// with plain letters. Prompts with no touch control are left empty.
+    pub fn text(&self, device: InputDevice, locale: &Locale) -> String {
+        match self.message_id(device) {
+            Some(id) => locale.text(id),
+            None => String::new(),
+        }
+    }
+
+    fn message_id(&self, device: InputDevice) -> Option<&'static str> {
+        match (self, device) {
+            (Prompt::Start, InputDevice::Keyboard) => Some("prompt-start-keyboard"),
+            (Prompt::Start, InputDevice::Gamepad) => Some("prompt-start-gamepad"),
+            (Prompt::Start, InputDevice::Touch) => Some("prompt-start-touch"),
+            (Prompt::Modes, InputDevice::Keyboard) => Some("prompt-modes-keyboard"),
+            (Prompt::Modes, InputDevice::Gamepad | InputDevice::Touch) => None,
+            (Prompt::Screens, InputDevice::Keyboard) => Some("prompt-screens-keyboard"),
+            (Prompt::Screens, InputDevice::Gamepad | InputDevice::Touch) => None,
+            (Prompt::Continue, InputDevice::Keyboard) => Some("prompt-continue-keyboard"),
+            (Prompt::Continue, InputDevice::Gamepad | InputDevice::Touch) => None,
+            (Prompt::SaveReplay, InputDevice::Keyboard) => Some("prompt-save-replay-keyboard"),
+            (Prompt::SaveReplay, InputDevice::Gamepad) => Some("prompt-save-replay-gamepad"),
+            (Prompt::SaveReplay, InputDevice::Touch) => None,
+            (Prompt::SaveGhost, InputDevice::Keyboard) => Some("prompt-save-ghost-keyboard"),
+            (Prompt::SaveGhost, InputDevice::Gamepad) => Some("prompt-save-ghost-gamepad"),
+            (Prompt::SaveGhost, InputDevice::Touch) => None,
+        }
+    }
+}
+
+/// Text whose first section shows the prompt for the current device.
+#[derive(Component)]
+pub struct PromptText(pub Prompt);
+
+/// Filters for the text marked with `T`, while it still shows its prompt.
+pub type ShownPrompt<T> = (With<T>, With<PromptText>);
+
+fn detect_input_device(
+    mut device: ResMut<InputDevice>,
+    mut ev_keyboard: EventReader<KeyboardInput>,
+    mut ev_gamepad: EventReader<GamepadButtonChangedEvent>,
+    mut ev_touch: EventReader<TouchInput>,
+) {
+    let mut latest = None;
+
+    if ev_keyboard.read().count() > 0 {
+        latest = Some(InputDevice::Keyboard);
+    }
+
+    let gamepad_pressed = ev_gamepad
+        .read()
+        .any(|event| event.value > GAMEPAD_PRESS_THRESHOLD);
+    if gamepad_pressed {
+        latest = Some(InputDevice::Gamepad);
+    }
+
+    if ev_touch.read().count() > 0 {
+        latest = Some(InputDevice::Touch);
+    }
+
+    if let Some(latest) = latest {
+        if *device != latest {
+            *device = latest;
+        }
+    }
+}
+
+/// Prompts are spawned with the right text, so only a change of device or
+/// language needs them rewritten.
+fn update_prompts(
+    device: Res<InputDevice>,
+    locale: Res<Locale>,
+    mut query: Query<(&PromptText, &mut Text)>,
+) {
+    if !device.is_changed() && !locale.is_changed() {
+        return;
+    }
+
+    for (prompt, mut text) in query.iter_mut() {
+        text.sections[0].value = prompt.0.text(*device, &locale);
+    }
+}
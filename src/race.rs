@@ -0,0 +1,293 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bounds::PlayArea,
+    config::GameConfig,
+    console::AddConsoleCommand,
+    ghost,
+    locale::Locale,
+    movement::FIXED_STEP,
+    player::{Player, PlayerControls, PlayerCount},
+    prompts::{InputDevice, Prompt, PromptText, ShownPrompt},
+    rng::{GameRng, RunSeed},
+    save::Save,
+    score::Scoreboard,
+    settings::Settings,
+    states::{GameState, RunState},
+    time_scale::TimeScale,
+    ui::{TextFont, SCOREBOARD_FONT_SIZE},
+    versus::{self, Versus},
+    SpriteSheet,
+};
+
+const RIVAL_COLOR: Color = Color::srgb(0.6, 1.0, 0.6);
+
+/// Races against a friend's run without being online. A finished solo run
+/// can be exported from the game over screen as a small ghost file, and
+/// `race <file>` loads one back. Runs after that use the ghost's seed, and
+/// its recorded keys steer a ghost player through the same enemies.
+pub struct RacePlugin;
+
+impl Plugin for RacePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostRecorder>()
+            .add_console_command("race", race_command)
+            .add_systems(OnEnter(GameState::Playing), start_race)
+            .add_systems(
+                FixedUpdate,
+                (record_ghost_step, steer_rival).run_if(in_state(RunState::Running)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), spawn_ghost_prompt)
+            .add_systems(Update, save_ghost.run_if(in_state(GameState::GameOver)));
+    }
+}
+
+/// A run reduced to its seed and the keys held on each fixed step, with
+/// repeated steps merged. A few minutes of dodging fits in a few kilobytes.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct GhostFile {
+    pub seed: u64,
+    pub score: f32,
+    /// How many steps in a row held these keys.
+    pub steps: Vec<(u32, GhostKeys)>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct GhostKeys {
+    pub left: bool,
+    pub right: bool,
+}
+
+impl GhostKeys {
+    fn direction(&self) -> f32 {
+        let mut direction = 0.0;
+        if self.left {
+            direction -= 1.0;
+        }
+        if self.right {
+            direction += 1.0;
+        }
+        direction
+    }
+}
+
+impl GhostFile {
+    fn push(&mut self, keys: GhostKeys) {
+        match self.steps.last_mut() {
+            Some((count, last)) if *last == keys => *count += 1,
+            _ => self.steps.push((1, keys)),
+        }
+    }
+}
+
+/// The friend's ghost being raced, if one has been loaded.
+#[derive(Resource)]
+struct Rival {
+    ghost: GhostFile,
+    /// Index into `ghost.steps`, and how many of that entry's steps are done.
+    entry: usize,
+    step: u32,
+}
+
+#[derive(Resource, Default)]
+struct GhostRecorder(GhostFile);
+
+#[derive(Component)]
+struct RivalGhost;
+
+#[derive(Component)]
+struct GhostPrompt;
+
+/// Spawns the rival when this run uses its seed; a seed changed from the
+/// console since would make for a different run.
+fn start_race(
+    mut commands: Commands,
+    rng: Res<GameRng>,
+    rival: Option<ResMut<Rival>>,
+    sprite_sheet: Res<SpriteSheet>,
+    play_area: Res<PlayArea>,
+    mut recorder: ResMut<GhostRecorder>,
+) {
+    recorder.0 = GhostFile::default();
+
+    let mut rival = match rival {
+        Some(rival) if rival.ghost.seed == rng.seed => rival,
+        _ => return,
+    };
+    rival.entry = 0;
+    rival.step = 0;
+
+    let ghost = ghost::spawn_ghost(&mut commands, &sprite_sheet, &play_area, RIVAL_COLOR);
+    commands.entity(ghost).insert(RivalGhost);
+}
+
+fn record_ghost_step(
+    mut recorder: ResMut<GhostRecorder>,
+    players: Query<(&Player, &PlayerControls)>,
+) {
+    let direction = players
+        .iter()
+        .find(|(player, _)| player.index == 0)
+        .map_or(0.0, |(_, controls)| controls.direction());
+
+    recorder.0.push(GhostKeys {
+        left: direction < 0.0,
+        right: direction > 0.0,
+    });
+}
+
+/// Moves the rival like a player holding the recorded keys, one fixed step
+/// at a time. It leaves when the recording runs out, where the friend's run
+/// ended.
+fn steer_rival(
+    mut commands: Commands,
+    time_scale: Res<TimeScale>,
+    config: Res<GameConfig>,
+    play_area: Res<PlayArea>,
+    rival: Option<ResMut<Rival>>,
+    mut query: Query<(Entity, &mut Transform), With<RivalGhost>>,
+) {
+    let (mut rival, (entity, mut transform)) = match (rival, query.get_single_mut()) {
+        (Some(rival), Ok(ghost)) => (rival, ghost),
+        _ => return,
+    };
+
+    let keys = match rival.ghost.steps.get(rival.entry) {
+        Some(&(_, keys)) => keys,
+        None => {
+            commands.entity(entity).despawn_recursive();
+            return;
+        }
+    };
+
+    rival.step += 1;
+    if rival.step >= rival.ghost.steps[rival.entry].0 {
+        rival.entry += 1;
+        rival.step = 0;
+    }
+
+    let x = transform.translation.x
+        + keys.direction() * config.player_speed * FIXED_STEP * time_scale.0;
+    transform.translation.x = x.clamp(play_area.x.start, play_area.x.end);
+}
+
+fn spawn_ghost_prompt(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    device: Res<InputDevice>,
+    locale: Res<Locale>,
+    player_count: Res<PlayerCount>,
+    versus: Res<Versus>,
+) {
+    if !versus::is_solo_run(&player_count, &versus) {
+        return;
+    }
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                Prompt::SaveGhost.text(*device, &locale),
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: SCOREBOARD_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(SCOREBOARD_FONT_SIZE),
+                right: Val::Px(SCOREBOARD_FONT_SIZE),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(GhostPrompt)
+        .insert(PromptText(Prompt::SaveGhost))
+        .insert(StateScoped(GameState::GameOver));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_ghost(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    rng: Res<GameRng>,
+    scoreboard: Res<Scoreboard>,
+    recorder: Res<GhostRecorder>,
+    save: Res<Save>,
+    locale: Res<Locale>,
+    mut prompts: Query<(Entity, &mut Text), ShownPrompt<GhostPrompt>>,
+) {
+    let gamepad_save = gamepads.iter().any(|gamepad| {
+        gamepad_input.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::West))
+    });
+    if !keyboard_input.just_pressed(KeyCode::KeyG) && !gamepad_save {
+        return;
+    }
+
+    let (entity, mut text) = match prompts.get_single_mut() {
+        Ok(prompt) => prompt,
+        Err(_) => return,
+    };
+
+    let ghost = GhostFile {
+        seed: rng.seed,
+        score: scoreboard.score,
+        steps: recorder.0.steps.clone(),
+    };
+    let key = format!("ghost-{}.ron", ghost.seed);
+    let result = ron::to_string(&ghost)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| {
+            save.0
+                .store(&key, contents.as_bytes())
+                .map_err(|err| err.to_string())
+        });
+
+    text.sections[0].value = match result {
+        Ok(()) => locale.format("ghost-saved", &[("key", key.into())]),
+        Err(err) => {
+            warn!("failed to save ghost: {}", err);
+            locale.text("ghost-save-failed")
+        }
+    };
+    commands.entity(entity).remove::<PromptText>();
+}
+
+/// `race <file>` loads a ghost and switches the following runs to its seed,
+/// `race off` stops racing.
+fn race_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let key = match args {
+        ["off"] => {
+            world.remove_resource::<Rival>();
+            world.insert_resource(RunSeed(None));
+            return Ok("stopped racing".to_string());
+        }
+        [key] => *key,
+        _ => return Err("usage: race <file|off>".to_string()),
+    };
+
+    let bytes = world
+        .get_resource::<Save>()
+        .ok_or("no save backend")?
+        .0
+        .load(key)
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| format!("no ghost named '{}'", key))?;
+    let ghost: GhostFile = ron::de::from_bytes(&bytes).map_err(|err| err.to_string())?;
+
+    let message = format!(
+        "racing a ghost that scored {} on seed {}",
+        ghost.score as i16, ghost.seed
+    );
+    world.insert_resource(RunSeed(Some(ghost.seed)));
+    world.insert_resource(Rival {
+        ghost,
+        entry: 0,
+        step: 0,
+    });
+    Ok(message)
+}
@@ -0,0 +1,570 @@
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::{
+    input::InputSystem,
+    prelude::*,
+    time::{TimeSystem, TimeUpdateStrategy},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    locale::Locale,
+    player::{Player, PlayerControls, PlayerCount},
+    prompts::{InputDevice, Prompt, PromptText, ShownPrompt},
+    rng::{GameRng, RunSeed},
+    save::Save,
+    settings::Settings,
+    shop::{Upgrade, Upgrades},
+    snapshot::{self, RunSnapshot},
+    states::{self, GameState, InRun, RunState},
+    suspend::ResumedRun,
+    ui::{TextFont, SCOREBOARD_FONT_SIZE},
+    GameSystem,
+};
+
+const REPLAY_COUNT_KEY: &str = "replay-count.ron";
+/// Half an hour at 60 frames per second. The whole run, which ghosts are
+/// drawn from, stops being recorded there, so a run left running can't grow
+/// it forever. Saved replays only keep its last seconds anyway.
+pub const MAX_REPLAY_FRAMES: usize = 60 * 60 * 30;
+/// How much of the end of a run a saved replay keeps, give or take
+/// [`CLIP_POINT_NANOS`].
+const CLIP_SECONDS: u64 = 10;
+/// How often the run is captured while it is played, for a saved replay to
+/// start from.
+const CLIP_POINT_NANOS: u64 = 1_000_000_000;
+
+/// Records every run so its last [`CLIP_SECONDS`] can be saved from the game
+/// over screen and played back with `headless --replay <file>`, or watched
+/// in the game by inserting a [`WatchedReplay`].
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        let count = ReplayCount::load(&app.world_mut().get_resource_or_insert_with(Save::default));
+        app.insert_resource(count)
+            .init_resource::<ReplayRecorder>()
+            .add_event::<ReplaySavedEvent>()
+            .add_systems(First, step_watched_time.before(TimeSystem))
+            .add_systems(First, record_frame.after(TimeSystem))
+            .add_systems(
+                PreUpdate,
+                press_watched_keys
+                    .after(InputSystem)
+                    .after(GameSystem::TextInput),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    record_keys,
+                    mark_clip_point.after(record_keys),
+                    start_watched_clip,
+                ),
+            )
+            .add_systems(OnEnter(GameState::Playing), start_recording)
+            .add_systems(OnEnter(GameState::GameOver), spawn_replay_prompt)
+            .add_systems(Update, save_replay.run_if(in_state(GameState::GameOver)))
+            .add_systems(OnExit(GameState::GameOver), stop_watching)
+            .add_systems(OnExit(InRun), stop_watching.run_if(states::run_abandoned));
+    }
+}
+
+/// Everything needed to play a run back: its seed, and how long each frame
+/// took and which keys were held while it was `Playing`. Frame times are
+/// kept exactly, since fixed steps and timers add them up.
+///
+/// A replay of a short run starts from its beginning. One of a longer run
+/// only keeps its end, and starts by putting the run back the way it was
+/// then, as a suspended run is. The settings and upgrades that change how
+/// a run plays out are kept with it. Replays saved before they were are
+/// solo runs played without any.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Replay {
+    pub seed: u64,
+    /// The run as it was before the first frame, or `None` if the frames
+    /// start with the run.
+    #[serde(default)]
+    pub start: Option<RunSnapshot>,
+    pub frames: Vec<ReplayFrame>,
+    #[serde(default = "default_player_count")]
+    pub player_count: usize,
+    #[serde(default)]
+    pub upgrades: Vec<Upgrade>,
+    #[serde(default)]
+    pub assist_hitbox: bool,
+    #[serde(default)]
+    pub assist_lives: bool,
+    /// In percent, like [`Settings::game_speed`].
+    #[serde(default = "default_game_speed")]
+    pub game_speed: u32,
+}
+
+fn default_player_count() -> usize {
+    1
+}
+
+fn default_game_speed() -> u32 {
+    100
+}
+
+impl Replay {
+    /// Puts the replay's settings over `settings`, to play it back with.
+    pub fn apply_settings(&self, settings: &mut Settings) {
+        settings.assist_hitbox = self.assist_hitbox;
+        settings.assist_lives = self.assist_lives;
+        settings.game_speed = self.game_speed;
+    }
+}
+
+/// Which way each player moved in a frame. Player one's moves are played
+/// back with the arrows and player two's with A and D.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReplayFrame {
+    pub delta_nanos: u64,
+    pub left: bool,
+    pub right: bool,
+    #[serde(default)]
+    pub left_two: bool,
+    #[serde(default)]
+    pub right_two: bool,
+    /// Where player one ended up, for drawing the run as a ghost. Playback
+    /// goes by the keys instead.
+    #[serde(default)]
+    pub x: f32,
+}
+
+/// The run in progress, or the last one once it is over. Only the first
+/// [`MAX_REPLAY_FRAMES`] of the whole run are kept, but its last frames
+/// always are, from the newest clip point at least [`CLIP_SECONDS`] back.
+#[derive(Resource, Default)]
+pub struct ReplayRecorder {
+    frames: Vec<ReplayFrame>,
+    clip: VecDeque<ReplayFrame>,
+    /// Where a saved replay can start, oldest first. The first one is the
+    /// start of the clip.
+    clip_points: VecDeque<ClipPoint>,
+    /// Every frame recorded, including the ones past the cap.
+    recorded: usize,
+    /// Every frame's time, including the ones past the cap.
+    elapsed_nanos: u64,
+    /// Whether a frame was started this frame, for `record_keys` to fill in.
+    recording: bool,
+}
+
+struct ClipPoint {
+    /// The number of frames recorded before it.
+    frame: usize,
+    elapsed_nanos: u64,
+    /// `None` for the start of the run, which needs no snapshot.
+    snapshot: Option<RunSnapshot>,
+}
+
+/// How many replays have been saved, which numbers the next one so runs
+/// with the same seed, like every daily run, don't overwrite each other's
+/// replays.
+#[derive(Resource, Default, Deserialize, Serialize)]
+pub struct ReplayCount(u64);
+
+impl ReplayCount {
+    pub fn load(save: &Save) -> Self {
+        save.0
+            .load(REPLAY_COUNT_KEY)
+            .ok()
+            .flatten()
+            .and_then(|bytes| ron::de::from_bytes(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, save: &Save) -> Result<(), String> {
+        ron::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                save.0
+                    .store(REPLAY_COUNT_KEY, contents.as_bytes())
+                    .map_err(|err| err.to_string())
+            })
+    }
+}
+
+#[derive(Component)]
+struct ReplayPrompt;
+
+/// Sent with the key a replay was saved under and the seed of its run.
+#[derive(Event)]
+pub struct ReplaySavedEvent {
+    pub key: String,
+    pub seed: u64,
+}
+
+/// A saved run being watched in the game. The next run uses its seed,
+/// player count, settings and upgrades, and is put back the way the replay
+/// starts once it is under way. While it is `Playing` each frame takes as
+/// long, and holds the same keys, as it did when it was recorded. It is
+/// removed when the run is over, and the player's own seed, settings and
+/// upgrades are put back. Settings aren't saved while it is there, so the
+/// replay's never end up in the player's.
+#[derive(Resource)]
+pub struct WatchedReplay {
+    start: Option<RunSnapshot>,
+    frames: Vec<ReplayFrame>,
+    next: usize,
+    current: Option<ReplayFrame>,
+    previous_seed: Option<u64>,
+    /// The player's own, in a replay with nothing else in it.
+    previous: Replay,
+}
+
+impl WatchedReplay {
+    /// Points the next run at the replay's seed, puts its settings and
+    /// upgrades in place, and remembers the ones they replace.
+    pub fn new(
+        replay: Replay,
+        run_seed: &mut RunSeed,
+        settings: &mut Settings,
+        upgrades: &mut Upgrades,
+    ) -> Self {
+        let previous = Replay {
+            seed: 0,
+            start: None,
+            frames: Vec::new(),
+            player_count: 1,
+            upgrades: std::mem::replace(&mut upgrades.active, replay.upgrades.clone()),
+            assist_hitbox: settings.assist_hitbox,
+            assist_lives: settings.assist_lives,
+            game_speed: settings.game_speed,
+        };
+        replay.apply_settings(settings);
+        let previous_seed = run_seed.0.replace(replay.seed);
+        Self {
+            start: replay.start,
+            frames: replay.frames,
+            next: 0,
+            current: None,
+            previous_seed,
+            previous,
+        }
+    }
+}
+
+impl ReplayRecorder {
+    pub fn frames(&self) -> &[ReplayFrame] {
+        &self.frames
+    }
+
+    /// Seconds played so far, including any past the recorded frames.
+    pub fn duration(&self) -> f32 {
+        self.elapsed_nanos as f32 / 1e9
+    }
+
+    /// The end of the run, and the state it starts from.
+    fn clip(&self) -> (Option<RunSnapshot>, Vec<ReplayFrame>) {
+        let start = self
+            .clip_points
+            .front()
+            .and_then(|point| point.snapshot.clone());
+        (start, self.clip.iter().cloned().collect())
+    }
+}
+
+fn start_recording(mut recorder: ResMut<ReplayRecorder>) {
+    *recorder = ReplayRecorder::default();
+    recorder.clip_points.push_back(ClipPoint {
+        frame: 0,
+        elapsed_nanos: 0,
+        snapshot: None,
+    });
+}
+
+/// Does for a watched replay what `headless` does for one played back
+/// without a window, except that the real clock keeps running outside
+/// `Playing`, so menus and the game over screen aren't affected.
+fn step_watched_time(
+    watched: Option<ResMut<WatchedReplay>>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+    run_state: Option<Res<State<RunState>>>,
+) {
+    let mut watched = match watched {
+        Some(watched) => watched,
+        None => return,
+    };
+
+    watched.current = None;
+    if run_state.is_some_and(|state| state.get() == &RunState::Running) {
+        watched.current = watched.frames.get(watched.next).cloned();
+        watched.next += 1;
+    }
+
+    *strategy = match &watched.current {
+        Some(frame) => TimeUpdateStrategy::ManualDuration(Duration::from_nanos(frame.delta_nanos)),
+        None => TimeUpdateStrategy::Automatic,
+    };
+}
+
+fn press_watched_keys(
+    watched: Option<Res<WatchedReplay>>,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+) {
+    let frame = match watched
+        .as_ref()
+        .and_then(|watched| watched.current.as_ref())
+    {
+        Some(frame) => frame,
+        None => return,
+    };
+
+    press_frame_keys(frame, &mut keyboard_input);
+}
+
+/// Holds the keys that move each player the way they moved in `frame`, and
+/// releases the rest.
+pub fn press_frame_keys(frame: &ReplayFrame, keyboard_input: &mut ButtonInput<KeyCode>) {
+    for (key, held) in [
+        (KeyCode::ArrowLeft, frame.left),
+        (KeyCode::ArrowRight, frame.right),
+        (KeyCode::KeyA, frame.left_two),
+        (KeyCode::KeyD, frame.right_two),
+    ] {
+        if held && !keyboard_input.pressed(key) {
+            keyboard_input.press(key);
+        } else if !held && keyboard_input.pressed(key) {
+            keyboard_input.release(key);
+        }
+    }
+}
+
+fn stop_watching(
+    mut commands: Commands,
+    watched: Option<Res<WatchedReplay>>,
+    mut run_seed: ResMut<RunSeed>,
+    mut settings: ResMut<Settings>,
+    mut upgrades: ResMut<Upgrades>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+) {
+    if let Some(watched) = watched {
+        run_seed.0 = watched.previous_seed;
+        watched.previous.apply_settings(&mut settings);
+        upgrades.active.clone_from(&watched.previous.upgrades);
+        *strategy = TimeUpdateStrategy::Automatic;
+        commands.remove_resource::<WatchedReplay>();
+    }
+}
+
+/// Runs right after the clock ticks, where `headless` feeds the recorded
+/// frame time back in, so both see the same state.
+fn record_frame(
+    time: Res<Time>,
+    run_state: Option<Res<State<RunState>>>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    let running = run_state.is_some_and(|state| state.get() == &RunState::Running);
+    let delta_nanos = time.delta().as_nanos() as u64;
+    if running {
+        recorder.elapsed_nanos += delta_nanos;
+    }
+
+    recorder.recording = running;
+    if recorder.recording {
+        recorder.recorded += 1;
+        recorder.clip.push_back(ReplayFrame {
+            delta_nanos,
+            left: false,
+            right: false,
+            left_two: false,
+            right_two: false,
+            x: 0.0,
+        });
+    }
+}
+
+/// Records the way each player moved rather than the keys, so whatever
+/// control preset was in use, playback can press the same keys.
+fn record_keys(
+    mut recorder: ResMut<ReplayRecorder>,
+    players: Query<(&Player, &PlayerControls, &Transform)>,
+) {
+    if !recorder.recording {
+        return;
+    }
+    let recorder = &mut *recorder;
+    let Some(frame) = recorder.clip.back_mut() else {
+        return;
+    };
+
+    for (player, controls, transform) in players.iter() {
+        let (left, right) = (controls.direction() < 0.0, controls.direction() > 0.0);
+        match player.index {
+            0 => {
+                (frame.left, frame.right) = (left, right);
+                frame.x = transform.translation.x;
+            }
+            1 => (frame.left_two, frame.right_two) = (left, right),
+            _ => {}
+        }
+    }
+
+    if recorder.frames.len() < MAX_REPLAY_FRAMES {
+        let frame = frame.clone();
+        recorder.frames.push(frame);
+    }
+}
+
+/// Captures the run every [`CLIP_POINT_NANOS`], and lets go of the points,
+/// and the frames before them, that a saved replay no longer needs: every
+/// one older than the newest that is at least [`CLIP_SECONDS`] back.
+fn mark_clip_point(world: &mut World) {
+    let recorder = world.resource::<ReplayRecorder>();
+    let due = recorder.recording
+        && recorder.clip_points.back().is_some_and(|point| {
+            recorder.elapsed_nanos / CLIP_POINT_NANOS > point.elapsed_nanos / CLIP_POINT_NANOS
+        });
+    if !due {
+        return;
+    }
+
+    let snapshot = RunSnapshot::capture(world);
+    let mut recorder = world.resource_mut::<ReplayRecorder>();
+    let point = ClipPoint {
+        frame: recorder.recorded,
+        elapsed_nanos: recorder.elapsed_nanos,
+        snapshot: Some(snapshot),
+    };
+    recorder.clip_points.push_back(point);
+
+    let clip_nanos = CLIP_SECONDS * 1_000_000_000;
+    while recorder
+        .clip_points
+        .get(1)
+        .is_some_and(|point| recorder.elapsed_nanos - point.elapsed_nanos >= clip_nanos)
+    {
+        recorder.clip_points.pop_front();
+    }
+    let first = recorder.clip_points[0].frame;
+    while recorder.recorded - recorder.clip.len() < first {
+        recorder.clip.pop_front();
+    }
+}
+
+/// Puts the run back the way a watched replay starts, before its first
+/// frame is played.
+fn start_watched_clip(world: &mut World) {
+    let pending = world
+        .get_resource::<WatchedReplay>()
+        .is_some_and(|watched| watched.start.is_some());
+    if !pending || !snapshot::run_started(world) {
+        return;
+    }
+
+    let start = world.resource_mut::<WatchedReplay>().start.take();
+    if let Some(start) = start {
+        start.restore(world);
+    }
+}
+
+/// A resumed run was only recorded from where it was resumed, which can't be
+/// played back.
+fn spawn_replay_prompt(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    device: Res<InputDevice>,
+    locale: Res<Locale>,
+    resumed: Option<Res<ResumedRun>>,
+) {
+    if resumed.is_some() {
+        return;
+    }
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                Prompt::SaveReplay.text(*device, &locale),
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: SCOREBOARD_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(SCOREBOARD_FONT_SIZE),
+                left: Val::Px(SCOREBOARD_FONT_SIZE),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(ReplayPrompt)
+        .insert(PromptText(Prompt::SaveReplay))
+        .insert(StateScoped(GameState::GameOver));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_replay(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    rng: Res<GameRng>,
+    recorder: Res<ReplayRecorder>,
+    player_count: Res<PlayerCount>,
+    settings: Res<Settings>,
+    upgrades: Res<Upgrades>,
+    save: Res<Save>,
+    locale: Res<Locale>,
+    mut count: ResMut<ReplayCount>,
+    mut prompts: Query<(Entity, &mut Text), ShownPrompt<ReplayPrompt>>,
+    mut ev_saved: EventWriter<ReplaySavedEvent>,
+) {
+    let gamepad_save = gamepads.iter().any(|gamepad| {
+        gamepad_input.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::North))
+    });
+    if !keyboard_input.just_pressed(KeyCode::KeyS) && !gamepad_save {
+        return;
+    }
+
+    let (entity, mut text) = match prompts.get_single_mut() {
+        Ok(prompt) => prompt,
+        Err(_) => return,
+    };
+
+    // The game's RNG isn't reseeded until the next run starts.
+    let (start, frames) = recorder.clip();
+    let replay = Replay {
+        seed: rng.seed,
+        start,
+        frames,
+        player_count: player_count.0,
+        upgrades: upgrades.active.clone(),
+        assist_hitbox: settings.assist_hitbox,
+        assist_lives: settings.assist_lives,
+        game_speed: settings.game_speed,
+    };
+    // The count is saved first, so a replay is never written under a key
+    // that a later one could be given again.
+    count.0 += 1;
+    let key = format!("replay-{}-{}.ron", replay.seed, count.0);
+    let result = count.save(&save).and_then(|()| {
+        ron::to_string(&replay)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                save.0
+                    .store(&key, contents.as_bytes())
+                    .map_err(|err| err.to_string())
+            })
+    });
+
+    text.sections[0].value = match result {
+        Ok(()) => {
+            let value = locale.format("replay-saved", &[("key", key.as_str().into())]);
+            ev_saved.send(ReplaySavedEvent {
+                key,
+                seed: replay.seed,
+            });
+            value
+        }
+        Err(err) => {
+            warn!("failed to save replay: {}", err);
+            locale.text("replay-save-failed")
+        }
+    };
+    commands.entity(entity).remove::<PromptText>();
+}
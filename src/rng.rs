@@ -0,0 +1,97 @@
+use std::ops::{Deref, DerefMut};
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+use crate::{console::AddConsoleCommand, states::GameState};
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunSeed>()
+            .insert_resource(GameRng::new(rand::thread_rng().gen()))
+            .add_systems(OnEnter(GameState::Playing), seed_run)
+            .add_cheat_command("seed", seed_command);
+    }
+}
+
+/// The seed the next run starts from. When `None`, every run picks a fresh
+/// random seed.
+#[derive(Resource, Default)]
+pub struct RunSeed(pub Option<u64>);
+
+/// Random numbers for everything that affects gameplay. It is reseeded at the
+/// start of each run, so a run can be reproduced from its seed. Purely
+/// cosmetic randomness keeps using `rand::thread_rng`.
+///
+/// It is ChaCha12, which is what `StdRng` uses too, so old seeds still play
+/// the same. Naming it keeps that from changing under us, and lets a
+/// suspended run save how far into its stream it got.
+#[derive(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    rng: ChaCha12Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Picks the numbers of `seed` back up where [`GameRng::word_pos`] said
+    /// they had got to.
+    pub fn resume(seed: u64, word_pos: u128) -> Self {
+        let mut rng = Self::new(seed);
+        rng.rng.set_word_pos(word_pos);
+        rng
+    }
+
+    /// How many 32-bit words of its stream have been used.
+    pub fn word_pos(&self) -> u128 {
+        self.rng.get_word_pos()
+    }
+}
+
+impl Deref for GameRng {
+    type Target = ChaCha12Rng;
+
+    fn deref(&self) -> &ChaCha12Rng {
+        &self.rng
+    }
+}
+
+impl DerefMut for GameRng {
+    fn deref_mut(&mut self) -> &mut ChaCha12Rng {
+        &mut self.rng
+    }
+}
+
+fn seed_run(run_seed: Res<RunSeed>, mut rng: ResMut<GameRng>) {
+    let seed = run_seed.0.unwrap_or_else(|| rand::thread_rng().gen());
+    info!("starting run with seed {}", seed);
+    *rng = GameRng::new(seed);
+}
+
+/// `seed <number>` fixes the seed of the following runs, `seed random` goes
+/// back to a fresh seed each run.
+fn seed_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let seed = match args {
+        ["random"] => None,
+        [seed] => Some(
+            seed.parse()
+                .map_err(|_| format!("invalid seed '{}'", seed))?,
+        ),
+        _ => return Err("usage: seed <number|random>".to_string()),
+    };
+
+    world.insert_resource(RunSeed(seed));
+    Ok(match seed {
+        Some(seed) => format!("next run uses seed {}", seed),
+        None => "next run uses a random seed".to_string(),
+    })
+}
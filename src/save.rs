@@ -0,0 +1,175 @@
+use bevy::prelude::Resource;
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+/// Somewhere to keep small blobs of data between sessions, such as settings.
+pub trait SaveBackend: Send + Sync + 'static {
+    /// Returns `None` when nothing has been stored under `key` yet.
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+    fn store(&self, key: &str, bytes: &[u8]) -> io::Result<()>;
+}
+
+/// The backend everything that persists data goes through. Plugins fall back
+/// to the platform default; insert one before adding them to replace it.
+#[derive(Resource)]
+pub struct Save(pub Box<dyn SaveBackend>);
+
+/// Files on native builds, like [`crate::config`], and `localStorage` in the
+/// browser. A wasm build without the `web` feature has neither, so nothing
+/// outlives the page.
+impl Default for Save {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn default() -> Self {
+        Self(Box::new(FileBackend {
+            root: PathBuf::from("."),
+        }))
+    }
+
+    #[cfg(all(target_arch = "wasm32", feature = "web"))]
+    fn default() -> Self {
+        Self(Box::new(LocalStorageBackend))
+    }
+
+    #[cfg(all(target_arch = "wasm32", not(feature = "web")))]
+    fn default() -> Self {
+        Self(Box::new(MemoryBackend::default()))
+    }
+}
+
+/// Puts `prefix` in front of every key, so several sets of data can share
+/// one backend without overwriting each other.
+pub struct PrefixBackend {
+    pub backend: Arc<dyn SaveBackend>,
+    pub prefix: String,
+}
+
+impl SaveBackend for PrefixBackend {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        self.backend.load(&format!("{}{}", self.prefix, key))
+    }
+
+    fn store(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        self.backend
+            .store(&format!("{}{}", self.prefix, key), bytes)
+    }
+}
+
+/// Stores each key as a file under `root`. Files are replaced whole: the new
+/// contents are written and flushed to a temporary file next to the old one,
+/// which is then renamed over it, so a crash part way through leaves either
+/// the old file or the new one, never a mix. On unix the directory is synced
+/// too, or the rename itself could be lost.
+pub struct FileBackend {
+    pub root: PathBuf,
+}
+
+impl SaveBackend for FileBackend {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.root.join(key)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn store(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.root.join(key);
+        let temp = self.root.join(format!("{}.tmp", key));
+
+        let mut file = File::create(&temp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        fs::rename(&temp, &path)?;
+        #[cfg(unix)]
+        File::open(&self.root)?.sync_all()?;
+        Ok(())
+    }
+}
+
+/// Keeps everything in memory, for tests and tools that shouldn't read or
+/// write the player's files.
+#[derive(Default)]
+pub struct MemoryBackend(Mutex<HashMap<String, Vec<u8>>>);
+
+impl SaveBackend for MemoryBackend {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.0.lock().unwrap().get(key).cloned())
+    }
+
+    fn store(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), bytes.to_vec());
+        Ok(())
+    }
+}
+
+/// Stores each key in the browser's `localStorage`. Values have to be UTF-8
+/// text, which RON always is.
+#[cfg(feature = "web")]
+pub struct LocalStorageBackend;
+
+#[cfg(feature = "web")]
+impl LocalStorageBackend {
+    fn storage() -> io::Result<web_sys::Storage> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "localStorage is unavailable"))
+    }
+}
+
+#[cfg(feature = "web")]
+impl SaveBackend for LocalStorageBackend {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Self::storage()?
+            .get_item(key)
+            .map(|value| value.map(String::into_bytes))
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "failed to read localStorage"))
+    }
+
+    fn store(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let value = std::str::from_utf8(bytes)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Self::storage()?
+            .set_item(key, value)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "failed to write localStorage"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_backend_replaces_files_whole() {
+        let root = std::env::temp_dir().join(format!("dodger-save-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let backend = FileBackend { root: root.clone() };
+
+        assert_eq!(backend.load("settings.ron").unwrap(), None);
+        backend.store("settings.ron", b"first").unwrap();
+        backend.store("settings.ron", b"second").unwrap();
+
+        // A fresh backend reads what was stored, like the next session would.
+        let reloaded = FileBackend { root: root.clone() };
+        assert_eq!(
+            reloaded.load("settings.ron").unwrap(),
+            Some(b"second".to_vec())
+        );
+        // The temporary file was renamed away both times.
+        let files: Vec<String> = fs::read_dir(&root)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(files, ["settings.ron"]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
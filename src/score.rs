@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+
+use crate::{
+    replay::WatchedReplay,
+    settings::Settings,
+    shop::Upgrades,
+    states::{GameState, RunState},
+    time_scale::{GameSpeed, TimeScale},
+    GameSystem,
+};
+
+const SCORE_ROLL_EASING: f32 = 8.0;
+const SCORE_MILESTONE: f32 = 10.0;
+const SCORE_PULSE_SECONDS: f32 = 0.3;
+const SCORE_PULSE_SCALE: f32 = 0.4;
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ScoreText>()
+            .insert_resource(Scoreboard { score: 0.0 })
+            .init_resource::<ScoreDisplay>()
+            .insert_resource(Ranked(true))
+            .insert_resource(Tampered(false))
+            .add_event::<ScoreEvent>()
+            .add_systems(OnEnter(GameState::Playing), (reset_score, reset_ranked))
+            .add_systems(Update, unrank_run)
+            .add_systems(
+                Update,
+                (
+                    unrank_slowed_run,
+                    accrue_time_score
+                        .after(GameSystem::Death)
+                        .before(GameSystem::Score),
+                    apply_score_events.in_set(GameSystem::Score),
+                    roll_score_display.after(GameSystem::Score),
+                )
+                    .run_if(in_state(RunState::Running)),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct Scoreboard {
+    pub score: f32,
+}
+
+/// Points awarded to the player. Everything that changes the score sends one
+/// of these rather than touching [`Scoreboard`] directly.
+#[derive(Event)]
+pub struct ScoreEvent {
+    pub amount: f32,
+    pub source: ScoreSource,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScoreSource {
+    /// One point per second survived.
+    Time,
+    /// An enemy fell past the bottom of the screen.
+    Dodge,
+    /// Awarded by a mod script, see [`crate::mods`].
+    Mod,
+}
+
+/// Whether the current or last run counts for high scores and leaderboards.
+/// A run stops counting as soon as an unranked setting is used, even if it
+/// is turned off again.
+#[derive(Resource)]
+pub struct Ranked(pub bool);
+
+/// Whether the current or last run was changed from outside of play, by a
+/// mod or a console command. Assists and upgrades unrank a run but don't
+/// set this, so those runs still earn coins.
+#[derive(Resource)]
+pub struct Tampered(pub bool);
+
+/// Takes the run in progress off the leaderboards and out of coins, for
+/// anything that changes it from outside of play.
+pub fn tamper(world: &mut World) {
+    world.insert_resource(Ranked(false));
+    world.insert_resource(Tampered(true));
+}
+
+/// The HUD text that shows the running score. The HUD prefab can change how
+/// much it grows when a milestone is reached.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ScoreText {
+    pub pulse_scale: f32,
+}
+
+impl Default for ScoreText {
+    fn default() -> Self {
+        Self {
+            pulse_scale: SCORE_PULSE_SCALE,
+        }
+    }
+}
+
+/// What the HUD currently shows. `value` rolls toward the real score and
+/// `pulse` runs from 1.0 down to 0.0 after a milestone is reached.
+#[derive(Resource, Default)]
+struct ScoreDisplay {
+    value: f32,
+    pulse: f32,
+}
+
+fn reset_score(mut scoreboard: ResMut<Scoreboard>, mut display: ResMut<ScoreDisplay>) {
+    scoreboard.score = 0.0;
+    *display = ScoreDisplay::default();
+}
+
+/// A watched replay is a run that already happened, so it never counts again.
+/// Upgrades from the shop count as assists.
+fn reset_ranked(
+    settings: Res<Settings>,
+    upgrades: Option<Res<Upgrades>>,
+    watched: Option<Res<WatchedReplay>>,
+    mut ranked: ResMut<Ranked>,
+    mut tampered: ResMut<Tampered>,
+) {
+    let upgraded = upgrades.is_some_and(|upgrades| !upgrades.active.is_empty());
+    ranked.0 = settings.ranked() && !upgraded && watched.is_none();
+    tampered.0 = false;
+}
+
+fn unrank_run(settings: Res<Settings>, mut ranked: ResMut<Ranked>) {
+    if settings.is_changed() && !settings.ranked() {
+        ranked.0 = false;
+    }
+}
+
+/// Slowing the game from the console or the debug keys counts the same as
+/// the slower speed settings.
+fn unrank_slowed_run(game_speed: Res<GameSpeed>, mut ranked: ResMut<Ranked>) {
+    if game_speed.0 < 1.0 && ranked.0 {
+        ranked.0 = false;
+    }
+}
+
+fn accrue_time_score(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut ev_score: EventWriter<ScoreEvent>,
+) {
+    ev_score.send(ScoreEvent {
+        amount: time_scale.delta_seconds(&time),
+        source: ScoreSource::Time,
+    });
+}
+
+fn apply_score_events(
+    mut ev_score: EventReader<ScoreEvent>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut display: ResMut<ScoreDisplay>,
+) {
+    let previous = scoreboard.score;
+    for event in ev_score.read() {
+        scoreboard.score += event.amount;
+    }
+
+    if (scoreboard.score / SCORE_MILESTONE).floor() > (previous / SCORE_MILESTONE).floor() {
+        display.pulse = 1.0;
+    }
+}
+
+fn roll_score_display(
+    time: Res<Time>,
+    scoreboard: Res<Scoreboard>,
+    mut display: ResMut<ScoreDisplay>,
+    mut query: Query<(&ScoreText, &mut Text, &mut Transform)>,
+) {
+    let delta_time = time.delta_seconds();
+    let easing = (SCORE_ROLL_EASING * delta_time).min(1.0);
+    display.value += (scoreboard.score - display.value) * easing;
+    display.pulse = (display.pulse - delta_time / SCORE_PULSE_SECONDS).max(0.0);
+
+    for (score_text, mut text, mut transform) in query.iter_mut() {
+        text.sections[1].value = format!("{}", display.value as i16);
+        transform.scale = Vec3::splat(1.0 + score_text.pulse_scale * display.pulse * display.pulse);
+    }
+}
@@ -0,0 +1,121 @@
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+
+use crate::{history, settings::Settings};
+
+const SCREENSHOT_KEY: KeyCode = KeyCode::F12;
+const SCREENSHOT_DIR: &str = "screenshots";
+const FLASH_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.8);
+const FLASH_SECONDS: f32 = 0.25;
+
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (take_screenshot, fade_screenshot_flash));
+    }
+}
+
+/// The white flash that confirms a screenshot was taken. It fades on real
+/// time, since screenshots can be taken while the game is paused.
+#[derive(Component)]
+struct ScreenshotFlash(Timer);
+
+/// F12 saves the next frame to a PNG in [`SCREENSHOT_DIR`]. In the browser
+/// the PNG is downloaded instead.
+///
+/// The frame is read back while it renders, so the flash is only spawned on
+/// the frame after, to keep it out of the picture. With reduced motion there
+/// is no flash.
+fn take_screenshot(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+    mut taken: Local<u32>,
+    mut flash_next: Local<bool>,
+) {
+    if std::mem::take(&mut *flash_next) {
+        spawn_flash(&mut commands);
+    }
+
+    if !keyboard_input.just_pressed(SCREENSHOT_KEY) {
+        return;
+    }
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Err(err) = std::fs::create_dir_all(SCREENSHOT_DIR) {
+        warn!("couldn't create {}: {}", SCREENSHOT_DIR, err);
+        return;
+    }
+
+    *taken += 1;
+    let path = format!("{}/{}", SCREENSHOT_DIR, file_name(history::now(), *taken));
+    match screenshot_manager.save_screenshot_to_disk(window, &path) {
+        Ok(()) => {
+            info!("saving a screenshot to {}", path);
+            *flash_next = !settings.reduced_motion;
+        }
+        Err(err) => warn!("couldn't take a screenshot: {}", err),
+    }
+}
+
+/// Names the file after the UTC time it was taken, and how many were taken
+/// this session so several in the same second don't overwrite each other.
+/// Without a clock, only the count is used.
+fn file_name(now: u64, taken: u32) -> String {
+    if now == 0 {
+        return format!("screenshot-{}.png", taken);
+    }
+
+    let seconds = now % history::SECONDS_PER_DAY;
+    format!(
+        "screenshot-{}-{:02}{:02}{:02}-{}.png",
+        history::format_date(now),
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        taken
+    )
+}
+
+fn spawn_flash(commands: &mut Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: BackgroundColor(FLASH_COLOR),
+            z_index: ZIndex::Global(i32::MAX),
+            ..default()
+        })
+        .insert(ScreenshotFlash(Timer::from_seconds(
+            FLASH_SECONDS,
+            TimerMode::Once,
+        )));
+}
+
+fn fade_screenshot_flash(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut query: Query<(Entity, &mut ScreenshotFlash, &mut BackgroundColor)>,
+) {
+    for (entity, mut flash, mut color) in query.iter_mut() {
+        if flash.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let remaining = 1.0 - flash.0.fraction();
+        color.0 = FLASH_COLOR;
+        color
+            .0
+            .set_alpha(FLASH_COLOR.alpha() * remaining * remaining);
+    }
+}
@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    controls::ControlPreset,
+    locale::{Language, Locale},
+    palette::Palette,
+    replay::WatchedReplay,
+    save::Save,
+    video::{FpsCap, FullscreenMode},
+};
+
+const SETTINGS_KEY: &str = "settings.ron";
+/// Bump when a field is renamed or changes meaning, and migrate older files
+/// in `Settings::migrate`.
+const SETTINGS_VERSION: u32 = 1;
+const MIN_GAME_SPEED: u32 = 60;
+const GAME_SPEED_STEP: u32 = 10;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = Settings::load(&app.world_mut().get_resource_or_insert_with(Save::default));
+        app.insert_resource(settings)
+            // Last, so a replay starting to be watched is already there.
+            .add_systems(Last, save_settings);
+    }
+}
+
+#[derive(Resource, Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Files saved before settings were versioned have none, and load as 0.
+    #[serde(default)]
+    pub version: u32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub muted: bool,
+    pub screen_shake: bool,
+    pub crt: bool,
+    /// Turns off screen shake, hit stop, flashes and the scrolling
+    /// background. Gameplay is unchanged.
+    pub reduced_motion: bool,
+    /// How fast the game runs, in percent, from 60 to 100. Runs played below
+    /// full speed aren't ranked.
+    pub game_speed: u32,
+    pub controls: ControlPreset,
+    /// Opts in to sending ranked scores to the online leaderboard, in builds
+    /// that have one.
+    pub submit_scores: bool,
+    /// Opts in to sending anonymous reports of finished runs, in builds that
+    /// collect them.
+    pub send_telemetry: bool,
+    /// Assists make runs unranked. Each can be turned on by itself.
+    pub assist_hitbox: bool,
+    pub assist_lives: bool,
+    pub palette: Palette,
+    pub language: Language,
+    pub fullscreen: FullscreenMode,
+    /// Index into the connected monitors, in the order the OS lists them.
+    pub monitor: usize,
+    pub vsync: bool,
+    pub fps_cap: FpsCap,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            music_volume: 0.6,
+            sfx_volume: 0.8,
+            muted: false,
+            screen_shake: true,
+            crt: false,
+            reduced_motion: false,
+            game_speed: 100,
+            controls: ControlPreset::Standard,
+            submit_scores: false,
+            send_telemetry: false,
+            assist_hitbox: false,
+            assist_lives: false,
+            palette: Palette::Classic,
+            language: Language::English,
+            fullscreen: FullscreenMode::Windowed,
+            monitor: 0,
+            vsync: true,
+            fps_cap: FpsCap::Uncapped,
+        }
+    }
+}
+
+impl Settings {
+    /// Falls back to the defaults when there is no settings file yet, or
+    /// when it can't be read.
+    pub fn load(save: &Save) -> Self {
+        let result = save
+            .0
+            .load(SETTINGS_KEY)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| match bytes {
+                Some(bytes) => ron::de::from_bytes(&bytes)
+                    .map(Some)
+                    .map_err(|err| err.to_string()),
+                None => Ok(None),
+            });
+
+        match result {
+            Ok(Some(settings)) => Self::migrate(settings),
+            Ok(None) => Self::default(),
+            Err(err) => {
+                warn!("failed to load settings, using defaults: {}", err);
+                Self::default()
+            }
+        }
+    }
+
+    /// Brings settings saved by an older version up to date, and clamps
+    /// anything out of range.
+    fn migrate(mut settings: Self) -> Self {
+        if settings.version > SETTINGS_VERSION {
+            warn!(
+                "settings were saved by a newer version ({}), unknown options are dropped",
+                settings.version
+            );
+        }
+        // Version 0 only predates the version field, every option in it
+        // still means the same thing.
+        settings.version = SETTINGS_VERSION;
+        settings.game_speed = settings.game_speed.clamp(MIN_GAME_SPEED, 100);
+        settings
+    }
+
+    /// Whether runs played with these settings count for high scores and
+    /// leaderboards.
+    pub fn ranked(&self) -> bool {
+        self.game_speed >= 100 && !self.assist_hitbox && !self.assist_lives
+    }
+
+    /// Short descriptions of the assists and slowdowns in use, for flagging
+    /// unranked runs.
+    pub fn assists(&self, locale: &Locale) -> Vec<String> {
+        let mut assists = Vec::new();
+        if self.assist_hitbox {
+            assists.push(locale.text("unranked-small-hitbox"));
+        }
+        if self.assist_lives {
+            assists.push(locale.text("unranked-extra-lives"));
+        }
+        if self.game_speed < 100 {
+            assists
+                .push(locale.format("unranked-game-speed", &[("speed", self.game_speed.into())]));
+        }
+        assists
+    }
+
+    /// Steps down from full speed, then wraps back around.
+    pub fn next_game_speed(&self) -> u32 {
+        if self.game_speed <= MIN_GAME_SPEED {
+            100
+        } else {
+            self.game_speed - GAME_SPEED_STEP
+        }
+    }
+
+    fn save(&self, save: &Save) {
+        let result = ron::ser::to_string_pretty(self, PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                save.0
+                    .store(SETTINGS_KEY, contents.as_bytes())
+                    .map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result {
+            warn!("failed to save settings: {}", err);
+        }
+    }
+}
+
+/// Settings aren't saved while a replay is watched, as it puts its own in
+/// place. Any changes are saved once the player's are back.
+fn save_settings(settings: Res<Settings>, save: Res<Save>, watched: Option<Res<WatchedReplay>>) {
+    if settings.is_changed() && !settings.is_added() && watched.is_none() {
+        settings.save(&save);
+    }
+}
@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+
+use crate::{
+    camera::CameraFocus, player::PlayerDeathEvent, settings::Settings, states::RunActive,
+    time_scale::TimeScale,
+};
+
+const MAX_OFFSET: f32 = 12.0;
+const MAX_ROLL: f32 = 0.05;
+const TRAUMA_DECAY: f32 = 1.5;
+const NOISE_SPEED: f32 = 30.0;
+const DEATH_TRAUMA: f32 = 0.8;
+
+pub struct ShakePlugin;
+
+impl Plugin for ShakePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (add_trauma, shake_camera).run_if(in_state(RunActive)),
+        );
+    }
+}
+
+/// Shakes the camera it is attached to by an amount proportional to the
+/// square of its trauma, which decays linearly back to zero.
+#[derive(Component, Default)]
+pub struct ScreenShake {
+    trauma: f32,
+}
+
+impl ScreenShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+}
+
+fn add_trauma(mut ev_death: EventReader<PlayerDeathEvent>, mut query: Query<&mut ScreenShake>) {
+    for _death in ev_death.read() {
+        for mut shake in query.iter_mut() {
+            shake.add_trauma(DEATH_TRAUMA);
+        }
+    }
+}
+
+fn shake_camera(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    settings: Res<Settings>,
+    mut query: Query<(&mut ScreenShake, &CameraFocus, &mut Transform)>,
+) {
+    let t = time.elapsed_seconds() * NOISE_SPEED;
+
+    for (mut shake, focus, mut transform) in query.iter_mut() {
+        shake.trauma = (shake.trauma - TRAUMA_DECAY * time_scale.delta_seconds(&time)).max(0.0);
+
+        let amount = if settings.screen_shake && !settings.reduced_motion {
+            shake.trauma * shake.trauma
+        } else {
+            0.0
+        };
+
+        transform.translation.x = focus.0.x + MAX_OFFSET * amount * noise(t, 0.0);
+        transform.translation.y = focus.0.y + MAX_OFFSET * amount * noise(t, 17.0);
+        transform.rotation = Quat::from_rotation_z(MAX_ROLL * amount * noise(t, 43.0));
+    }
+}
+
+fn noise(t: f32, seed: f32) -> f32 {
+    (t + seed).sin() * 0.6 + (t * 2.3 + seed * 1.7).sin() * 0.4
+}
@@ -0,0 +1,297 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    locale::Locale,
+    player::PlayerCount,
+    replay::WatchedReplay,
+    save::Save,
+    score::{Scoreboard, Tampered},
+    settings::Settings,
+    states::{self, GameState, MenuSelectEvent},
+    ui::TextFont,
+};
+
+const UPGRADES_KEY: &str = "upgrades.ron";
+/// Coins earned per point scored.
+const COINS_PER_POINT: f32 = 0.1;
+const SPEED_BONUS: f32 = 0.05;
+const SHOP_FONT_SIZE: f32 = 16.0;
+const SHOP_PADDING: f32 = 16.0;
+
+/// Every upgrade, in the order the shop lists them.
+pub const UPGRADES: [Upgrade; 3] = [Upgrade::Shield, Upgrade::Speed, Upgrade::ExtraLife];
+
+/// Every run earns coins, which B on the title spends on permanent upgrades.
+/// Owned upgrades can be switched off again in the shop, and like assists,
+/// a run with any of them on isn't ranked.
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        let upgrades = Upgrades::load(&app.world_mut().get_resource_or_insert_with(Save::default));
+        app.insert_resource(upgrades)
+            .init_resource::<ShopSelection>()
+            .add_systems(Update, open_shop.run_if(in_state(GameState::Title)))
+            .add_systems(OnEnter(GameState::GameOver), award_coins)
+            .add_systems(OnEnter(GameState::Shop), spawn_shop)
+            .add_systems(
+                Update,
+                (browse_shop, update_shop.after(browse_shop)).run_if(in_state(GameState::Shop)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Upgrade {
+    /// Absorbs the first hit of a run.
+    Shield,
+    Speed,
+    ExtraLife,
+}
+
+impl Upgrade {
+    /// The message id of its name.
+    pub fn name_id(&self) -> &'static str {
+        match self {
+            Upgrade::Shield => "upgrade-shield",
+            Upgrade::Speed => "upgrade-speed",
+            Upgrade::ExtraLife => "upgrade-extra-life",
+        }
+    }
+
+    pub fn price(&self) -> u32 {
+        match self {
+            Upgrade::Shield => 100,
+            Upgrade::Speed => 150,
+            Upgrade::ExtraLife => 250,
+        }
+    }
+}
+
+/// Coins and upgrades, kept between sessions.
+#[derive(Resource, Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Upgrades {
+    pub coins: u32,
+    pub owned: Vec<Upgrade>,
+    /// The owned upgrades that are switched on.
+    pub active: Vec<Upgrade>,
+}
+
+impl Upgrades {
+    pub fn load(save: &Save) -> Self {
+        save.0
+            .load(UPGRADES_KEY)
+            .ok()
+            .flatten()
+            .and_then(|bytes| ron::de::from_bytes(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, save: &Save) {
+        let result = ron::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                save.0
+                    .store(UPGRADES_KEY, contents.as_bytes())
+                    .map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result {
+            warn!("failed to save upgrades: {}", err);
+        }
+    }
+
+    pub fn is_active(&self, upgrade: Upgrade) -> bool {
+        self.active.contains(&upgrade)
+    }
+
+    /// How much faster than `player_speed` players move.
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.is_active(Upgrade::Speed) {
+            1.0 + SPEED_BONUS
+        } else {
+            1.0
+        }
+    }
+
+    pub fn extra_lives(&self) -> u32 {
+        self.is_active(Upgrade::ExtraLife) as u32
+    }
+}
+
+/// The upgrade the shop has selected.
+#[derive(Resource, Default)]
+struct ShopSelection(usize);
+
+#[derive(Component)]
+struct ShopText;
+
+/// Watched replays, spectating and runs changed by a mod or the console
+/// don't earn anything.
+#[allow(clippy::too_many_arguments)]
+fn award_coins(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    scoreboard: Res<Scoreboard>,
+    player_count: Res<PlayerCount>,
+    watched: Option<Res<WatchedReplay>>,
+    tampered: Res<Tampered>,
+    save: Res<Save>,
+    locale: Res<Locale>,
+    mut upgrades: ResMut<Upgrades>,
+) {
+    let coins = (scoreboard.score * COINS_PER_POINT) as u32;
+    if coins == 0 || watched.is_some() || player_count.0 == 0 || tampered.0 {
+        return;
+    }
+
+    upgrades.coins = upgrades.coins.saturating_add(coins);
+    upgrades.save(&save);
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                locale.format(
+                    "shop-coins-earned",
+                    &[("coins", coins.into()), ("total", upgrades.coins.into())],
+                ),
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: SHOP_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(SHOP_PADDING + SHOP_FONT_SIZE * 1.5),
+                left: Val::Px(SHOP_PADDING),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(StateScoped(GameState::GameOver));
+}
+
+fn open_shop(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyB)
+        && states::try_change(&mut next_state, GameState::Shop)
+    {
+        ev_menu.send(MenuSelectEvent);
+    }
+}
+
+fn spawn_shop(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    mut selection: ResMut<ShopSelection>,
+) {
+    selection.0 = 0;
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: SHOP_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(SHOP_PADDING),
+                left: Val::Px(SHOP_PADDING),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(ShopText)
+        .insert(StateScoped(GameState::Shop));
+}
+
+/// Up and down pick an upgrade. Enter buys it, or switches it on and off
+/// once owned. Escape goes back to the title.
+fn browse_shop(
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    save: Res<Save>,
+    mut upgrades: ResMut<Upgrades>,
+    mut selection: ResMut<ShopSelection>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        keyboard_input.reset(KeyCode::Escape);
+        states::try_change(&mut next_state, GameState::Title);
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        selection.0 = (selection.0 + 1) % UPGRADES.len();
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        selection.0 = (selection.0 + UPGRADES.len() - 1) % UPGRADES.len();
+    }
+
+    if !keyboard_input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+
+    let upgrade = UPGRADES[selection.0];
+    if upgrades.owned.contains(&upgrade) {
+        if upgrades.is_active(upgrade) {
+            upgrades.active.retain(|active| *active != upgrade);
+        } else {
+            upgrades.active.push(upgrade);
+        }
+    } else if upgrades.coins >= upgrade.price() {
+        upgrades.coins -= upgrade.price();
+        upgrades.owned.push(upgrade);
+        upgrades.active.push(upgrade);
+    } else {
+        return;
+    }
+
+    upgrades.save(&save);
+    ev_menu.send(MenuSelectEvent);
+}
+
+fn update_shop(
+    upgrades: Res<Upgrades>,
+    selection: Res<ShopSelection>,
+    locale: Res<Locale>,
+    mut texts: Query<&mut Text, With<ShopText>>,
+) {
+    let mut value = format!(
+        "{}\n\n",
+        locale.format("shop-title", &[("coins", upgrades.coins.into())])
+    );
+    for (index, upgrade) in UPGRADES.iter().enumerate() {
+        let status = if upgrades.is_active(*upgrade) {
+            locale.text("setting-on")
+        } else if upgrades.owned.contains(upgrade) {
+            locale.text("setting-off")
+        } else {
+            locale.format("shop-price", &[("price", upgrade.price().into())])
+        };
+        value.push_str(&format!(
+            "{} {:<12} {}\n",
+            if index == selection.0 { ">" } else { " " },
+            locale.text(upgrade.name_id()),
+            status
+        ));
+    }
+    value.push_str(&format!("\n{}", locale.text("shop-note")));
+    value.push_str(&format!("\n\n\n{}", locale.text("shop-help")));
+
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
@@ -0,0 +1,326 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    locale::Locale,
+    player::{PLAYER_IDLE_FRAMES, PLAYER_MOVE_FRAMES},
+    save::Save,
+    score::{Ranked, Scoreboard},
+    settings::Settings,
+    states::{self, GameState, MenuSelectEvent},
+    ui::TextFont,
+    SpriteSheet,
+};
+
+const WARDROBE_KEY: &str = "skins.ron";
+const SKINS_FONT_SIZE: f32 = 16.0;
+const SKINS_PADDING: f32 = 16.0;
+const PREVIEW_SCALE: f32 = 6.0;
+const LOCKED_PREVIEW_ALPHA: f32 = 0.2;
+
+/// Every skin, in the order the skin screen shows them. The first one is
+/// always unlocked.
+pub const SKINS: &[Skin] = &[
+    Skin {
+        name_id: "skin-robot",
+        idle: PLAYER_IDLE_FRAMES,
+        moving: PLAYER_MOVE_FRAMES,
+        color: None,
+        unlock_score: 0,
+    },
+    Skin {
+        name_id: "skin-gold-robot",
+        idle: PLAYER_IDLE_FRAMES,
+        moving: PLAYER_MOVE_FRAMES,
+        color: Some(Color::srgb(1.0, 0.8, 0.3)),
+        unlock_score: 50,
+    },
+    Skin {
+        name_id: "skin-knight",
+        idle: &[28],
+        moving: &[28],
+        color: None,
+        unlock_score: 100,
+    },
+    Skin {
+        name_id: "skin-ember-knight",
+        idle: &[28],
+        moving: &[28],
+        color: Some(Color::srgb(1.0, 0.45, 0.3)),
+        unlock_score: 200,
+    },
+    Skin {
+        name_id: "skin-king",
+        idle: &[175],
+        moving: &[175],
+        color: Some(Color::srgb(1.0, 0.85, 0.4)),
+        unlock_score: 400,
+    },
+];
+
+/// Lets the player change how they look, with skins unlocked by reaching
+/// score milestones in ranked runs. Skins are only cosmetic. K on the title
+/// opens the skin screen, where left and right browse and Enter equips.
+pub struct SkinsPlugin;
+
+impl Plugin for SkinsPlugin {
+    fn build(&self, app: &mut App) {
+        let wardrobe = Wardrobe::load(&app.world_mut().get_resource_or_insert_with(Save::default));
+        app.insert_resource(wardrobe)
+            .init_resource::<SkinSelection>()
+            .add_systems(Update, open_skins.run_if(in_state(GameState::Title)))
+            .add_systems(OnEnter(GameState::GameOver), unlock_skins)
+            .add_systems(OnEnter(GameState::Skins), spawn_skins)
+            .add_systems(
+                Update,
+                (browse_skins, update_skins.after(browse_skins)).run_if(in_state(GameState::Skins)),
+            );
+    }
+}
+
+pub struct Skin {
+    /// The message id of its name.
+    pub name_id: &'static str,
+    pub idle: &'static [usize],
+    pub moving: &'static [usize],
+    /// A tint over the sprite, which is drawn in its own colors without one.
+    pub color: Option<Color>,
+    /// The ranked score that unlocks it.
+    pub unlock_score: u32,
+}
+
+/// Which skin is worn, and what has been unlocked so far.
+#[derive(Resource, Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Wardrobe {
+    /// Index into [`SKINS`].
+    pub equipped: usize,
+    /// The best ranked score so far, which decides what is unlocked.
+    pub best_score: u32,
+}
+
+impl Wardrobe {
+    pub fn load(save: &Save) -> Self {
+        save.0
+            .load(WARDROBE_KEY)
+            .ok()
+            .flatten()
+            .and_then(|bytes| ron::de::from_bytes(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, save: &Save) {
+        let result = ron::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                save.0
+                    .store(WARDROBE_KEY, contents.as_bytes())
+                    .map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result {
+            warn!("failed to save skins: {}", err);
+        }
+    }
+
+    pub fn unlocked(&self, skin: &Skin) -> bool {
+        self.best_score >= skin.unlock_score
+    }
+
+    /// The skin being worn, falling back to the first one if the saved
+    /// choice no longer exists or isn't unlocked.
+    pub fn skin(&self) -> &'static Skin {
+        SKINS
+            .get(self.equipped)
+            .filter(|skin| self.unlocked(skin))
+            .unwrap_or(&SKINS[0])
+    }
+}
+
+/// The skin the skin screen is showing.
+#[derive(Resource, Default)]
+struct SkinSelection(usize);
+
+#[derive(Component)]
+struct SkinsText;
+
+#[derive(Component)]
+struct SkinPreview;
+
+/// Tells the player about skins a run has just unlocked.
+#[allow(clippy::too_many_arguments)]
+fn unlock_skins(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    scoreboard: Res<Scoreboard>,
+    ranked: Res<Ranked>,
+    save: Res<Save>,
+    locale: Res<Locale>,
+    mut wardrobe: ResMut<Wardrobe>,
+) {
+    let score = scoreboard.score as u32;
+    if !ranked.0 || score <= wardrobe.best_score {
+        return;
+    }
+
+    let unlocked: Vec<String> = SKINS
+        .iter()
+        .filter(|skin| skin.unlock_score > wardrobe.best_score && skin.unlock_score <= score)
+        .map(|skin| locale.text(skin.name_id))
+        .collect();
+    wardrobe.best_score = score;
+    wardrobe.save(&save);
+
+    if unlocked.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                locale.format("skins-unlocked", &[("skins", unlocked.join(", ").into())]),
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: SKINS_FONT_SIZE,
+                    color: settings.palette.colors().score,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(SKINS_PADDING),
+                left: Val::Px(SKINS_PADDING),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(StateScoped(GameState::GameOver));
+}
+
+fn open_skins(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyK)
+        && states::try_change(&mut next_state, GameState::Skins)
+    {
+        ev_menu.send(MenuSelectEvent);
+    }
+}
+
+fn spawn_skins(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    sprite_sheet: Res<SpriteSheet>,
+    wardrobe: Res<Wardrobe>,
+    mut selection: ResMut<SkinSelection>,
+) {
+    selection.0 = wardrobe.equipped.min(SKINS.len() - 1);
+
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: sprite_sheet.texture.clone(),
+                transform: Transform::from_scale(Vec3::splat(PREVIEW_SCALE)),
+                ..default()
+            },
+            sprite_sheet.atlas(0),
+        ))
+        .insert(SkinPreview)
+        .insert(StateScoped(GameState::Skins));
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: SKINS_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(SKINS_PADDING),
+                left: Val::Px(SKINS_PADDING),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(SkinsText)
+        .insert(StateScoped(GameState::Skins));
+}
+
+/// Left and right pick a skin, Enter wears it if it is unlocked and Escape
+/// goes back to the title.
+fn browse_skins(
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    save: Res<Save>,
+    mut wardrobe: ResMut<Wardrobe>,
+    mut selection: ResMut<SkinSelection>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        keyboard_input.reset(KeyCode::Escape);
+        states::try_change(&mut next_state, GameState::Title);
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+        selection.0 = (selection.0 + 1) % SKINS.len();
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        selection.0 = (selection.0 + SKINS.len() - 1) % SKINS.len();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Enter)
+        && wardrobe.unlocked(&SKINS[selection.0])
+        && wardrobe.equipped != selection.0
+    {
+        wardrobe.equipped = selection.0;
+        wardrobe.save(&save);
+        ev_menu.send(MenuSelectEvent);
+    }
+}
+
+fn update_skins(
+    wardrobe: Res<Wardrobe>,
+    selection: Res<SkinSelection>,
+    locale: Res<Locale>,
+    mut previews: Query<(&mut TextureAtlas, &mut Sprite), With<SkinPreview>>,
+    mut texts: Query<&mut Text, With<SkinsText>>,
+) {
+    let skin = &SKINS[selection.0];
+    let unlocked = wardrobe.unlocked(skin);
+
+    for (mut atlas, mut sprite) in previews.iter_mut() {
+        atlas.index = skin.idle[0];
+        sprite.color = skin.color.unwrap_or(Color::WHITE);
+        if !unlocked {
+            sprite.color.set_alpha(LOCKED_PREVIEW_ALPHA);
+        }
+    }
+
+    let status = if !unlocked {
+        locale.format("skins-locked", &[("score", skin.unlock_score.into())])
+    } else if wardrobe.equipped == selection.0 {
+        locale.text("skins-equipped")
+    } else {
+        locale.text("skins-wear")
+    };
+    let value = format!(
+        "{}\n\n< {} >  {}/{}\n{}\n\n\n{}",
+        locale.text("skins-title"),
+        locale.text(skin.name_id),
+        selection.0 + 1,
+        SKINS.len(),
+        status,
+        locale.text("skins-help")
+    );
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    console::AddConsoleCommand,
+    enemy::{self, SpawnerSnapshot},
+    player::{self, Player, PlayerCount, PlayerSnapshot},
+    replay::ReplayRecorder,
+    rng::GameRng,
+    score::{self, Ranked, Scoreboard},
+    states::GameState,
+    suspend::ResumedRun,
+    waves::{self, WavesSnapshot},
+};
+
+/// Keeps a checkpoint of the run in progress for debugging: `checkpoint` in
+/// the console takes one, and `checkpoint load` puts the run back to it. The
+/// checkpoint is kept as a [`RunSnapshot`] blob, the same one suspended runs
+/// are saved as, and outlives the run it was taken in, so a new run can be
+/// started from it.
+pub struct SnapshotPlugin;
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Checkpoint>()
+            .add_console_command("checkpoint", checkpoint_command);
+    }
+}
+
+/// Everything needed to carry on with a run from where it was left.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RunSnapshot {
+    pub score: f32,
+    /// Seconds played before the run was suspended, over all its sessions.
+    pub elapsed: f32,
+    pub player_count: usize,
+    pub seed: u64,
+    /// See [`GameRng::word_pos`]. No run gets anywhere near needing more than
+    /// 64 bits of it.
+    pub word_pos: u64,
+    pub players: Vec<PlayerSnapshot>,
+    pub spawner: SpawnerSnapshot,
+    /// Runs suspended before waves were kept start them over.
+    #[serde(default)]
+    pub waves: WavesSnapshot,
+}
+
+impl RunSnapshot {
+    pub fn capture(world: &mut World) -> Self {
+        let rng = world.get_resource::<GameRng>().unwrap();
+        let (seed, word_pos) = (rng.seed, rng.word_pos() as u64);
+        let elapsed = world
+            .get_resource::<ResumedRun>()
+            .map_or(0.0, |resumed| resumed.elapsed)
+            + world
+                .get_resource::<ReplayRecorder>()
+                .map_or(0.0, |recorder| recorder.duration());
+
+        Self {
+            score: world.get_resource::<Scoreboard>().unwrap().score,
+            elapsed,
+            player_count: world.get_resource::<PlayerCount>().unwrap().0,
+            seed,
+            word_pos,
+            players: player::snapshot_players(world),
+            spawner: enemy::snapshot_spawner(world),
+            waves: waves::snapshot_waves(world),
+        }
+    }
+
+    /// Puts the run in progress back the way it was captured. Players that
+    /// have gone down since aren't brought back. The run stops being ranked,
+    /// since its replay can't follow the jump.
+    pub fn restore(&self, world: &mut World) {
+        world.insert_resource(GameRng::resume(self.seed, self.word_pos as u128));
+        world.get_resource_mut::<Scoreboard>().unwrap().score = self.score;
+        world.insert_resource(Ranked(false));
+        player::restore_players(world, &self.players);
+        enemy::restore_spawner(world, &self.spawner);
+        waves::restore_waves(world, &self.waves);
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        ron::to_string(self)
+            .map(String::into_bytes)
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        ron::de::from_bytes(bytes).map_err(|err| err.to_string())
+    }
+}
+
+/// Whether a run that was just started can have a snapshot put over it:
+/// it is `Playing`, and its players, which are spawned with commands, are
+/// there.
+pub fn run_started(world: &mut World) -> bool {
+    world.resource::<State<GameState>>().get() == &GameState::Playing
+        && world
+            .query_filtered::<(), With<Player>>()
+            .iter(world)
+            .next()
+            .is_some()
+}
+
+#[derive(Resource, Default)]
+struct Checkpoint(Option<Vec<u8>>);
+
+/// `checkpoint` takes a checkpoint of the run in progress, and
+/// `checkpoint load` goes back to it.
+fn checkpoint_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    if world.resource::<State<GameState>>().get() != &GameState::Playing {
+        return Err("no run in progress".to_string());
+    }
+
+    match args {
+        [] => {
+            let bytes = RunSnapshot::capture(world).to_bytes()?;
+            let message = format!("took a checkpoint ({} bytes)", bytes.len());
+            world.get_resource_mut::<Checkpoint>().unwrap().0 = Some(bytes);
+            Ok(message)
+        }
+        ["load"] => {
+            let bytes = match &world.get_resource::<Checkpoint>().unwrap().0 {
+                Some(bytes) => bytes.clone(),
+                None => return Err("no checkpoint taken".to_string()),
+            };
+            RunSnapshot::from_bytes(&bytes)?.restore(world);
+            score::tamper(world);
+            Ok("back to the checkpoint".to_string())
+        }
+        _ => Err("usage: checkpoint [load]".to_string()),
+    }
+}
@@ -0,0 +1,100 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{enemy::Collider, movement::Interpolated, states::RunState, GameSystem, SPRITE_SIZE};
+
+const GRID_CELL_SIZE: f32 = 64.0;
+
+pub struct SpatialPlugin;
+
+impl Plugin for SpatialPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SpatialGrid::new(GRID_CELL_SIZE))
+            .add_systems(
+                FixedUpdate,
+                rebuild_spatial_grid
+                    .in_set(GameSystem::SpatialGrid)
+                    .after(GameSystem::Movement)
+                    .run_if(in_state(RunState::Running)),
+            );
+    }
+}
+
+/// A uniform grid of square cells used as a collision broad phase. Each
+/// entity is filed under every cell its bounding box overlaps, so a query
+/// only has to look at the cells around the area of interest.
+#[derive(Resource)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<Entity>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+        }
+    }
+
+    /// Empties every cell while keeping their allocations for the next rebuild.
+    pub fn clear(&mut self) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+    }
+
+    pub fn insert(&mut self, entity: Entity, center: Vec2, size: Vec2) {
+        let (min, max) = self.cell_range(center, size);
+
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                self.cells.entry((x, y)).or_default().push(entity);
+            }
+        }
+    }
+
+    /// Returns every entity filed under a cell overlapped by the given box,
+    /// each exactly once. These are only candidates and still need an exact
+    /// overlap test.
+    pub fn query(&self, center: Vec2, size: Vec2) -> Vec<Entity> {
+        let (min, max) = self.cell_range(center, size);
+        let mut found = Vec::new();
+
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    found.extend_from_slice(cell);
+                }
+            }
+        }
+
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    fn cell_range(&self, center: Vec2, size: Vec2) -> ((i32, i32), (i32, i32)) {
+        let min = (center - size / 2.0) / self.cell_size;
+        let max = (center + size / 2.0) / self.cell_size;
+
+        (
+            (min.x.floor() as i32, min.y.floor() as i32),
+            (max.x.floor() as i32, max.y.floor() as i32),
+        )
+    }
+}
+
+fn rebuild_spatial_grid(
+    mut grid: ResMut<SpatialGrid>,
+    query: Query<(Entity, &Interpolated, &Transform), With<Collider>>,
+) {
+    grid.clear();
+
+    for (entity, position, transform) in query.iter() {
+        grid.insert(
+            entity,
+            position.current.truncate(),
+            transform.scale.truncate() * SPRITE_SIZE,
+        );
+    }
+}
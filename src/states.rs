@@ -0,0 +1,175 @@
+use bevy::{prelude::*, state::state::FreelyMutableState};
+
+use crate::{console::AddConsoleCommand, player::PlayerCount, settings::Settings, GameSystem};
+
+#[derive(States, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub enum GameState {
+    #[default]
+    Loading,
+    Title,
+    Playing,
+    Dying,
+    GameOver,
+    /// The online leaderboard, opened from the title screen.
+    Leaderboards,
+    /// The list of recent runs, opened from the title screen.
+    History,
+    /// Picks the player's skin, opened from the title screen.
+    Skins,
+    /// Spends coins on upgrades, opened from the title screen.
+    Shop,
+    /// Today's daily challenge, opened from the title screen.
+    Daily,
+    /// Switches between and creates profiles, opened from the title screen.
+    Profiles,
+}
+
+/// Whether the run being played is paused. Only there while `Playing`, and
+/// back to `Running` every time a run starts, so pausing doesn't leave or
+/// re-enter `Playing`.
+#[derive(SubStates, Clone, Copy, Eq, PartialEq, Debug, Hash, Default)]
+#[source(GameState = GameState::Playing)]
+pub enum RunState {
+    #[default]
+    Running,
+    Paused,
+}
+
+/// Present while a run is on screen and moving: being played and not
+/// paused, or its player dying. Effects and animations run in it.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Hash)]
+pub struct RunActive;
+
+impl ComputedStates for RunActive {
+    type SourceStates = (GameState, Option<RunState>);
+
+    fn compute((game, run): (GameState, Option<RunState>)) -> Option<Self> {
+        match (game, run) {
+            (GameState::Playing, Some(RunState::Running)) | (GameState::Dying, _) => {
+                Some(RunActive)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Present from the moment a run starts until it is over, whether it ends
+/// on the game over screen, is suspended to the title, or is left with the
+/// `state` command. Run entities are scoped to it, and whatever a run sets
+/// up for itself is undone when it exits.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Hash)]
+pub struct InRun;
+
+impl ComputedStates for InRun {
+    type SourceStates = GameState;
+
+    fn compute(game: GameState) -> Option<Self> {
+        match game {
+            GameState::Playing | GameState::Dying => Some(InRun),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct MenuSelectEvent;
+
+pub struct StatesPlugin;
+
+impl Plugin for StatesPlugin {
+    fn build(&self, app: &mut App) {
+        // Entities spawned with `StateScoped(state)` are despawned, along
+        // with their children, when `state` exits.
+        app.init_state::<GameState>()
+            .enable_state_scoped_entities::<GameState>()
+            .add_sub_state::<RunState>()
+            .add_computed_state::<RunActive>()
+            .add_computed_state::<InRun>()
+            .enable_state_scoped_entities::<InRun>()
+            .add_event::<MenuSelectEvent>()
+            .add_systems(
+                Update,
+                (
+                    start_game.run_if(in_state(GameState::Title)),
+                    start_game
+                        .after(GameSystem::Initials)
+                        .run_if(in_state(GameState::GameOver)),
+                ),
+            )
+            .add_cheat_command("state", state_command);
+    }
+}
+
+/// Queues a change to `state`, unless another one was queued earlier in the
+/// same frame, such as starting a run from the title and the game over
+/// screen at once. The first change wins rather than the last. Returns
+/// whether the change was queued.
+pub fn try_change<S: FreelyMutableState>(next: &mut NextState<S>, state: S) -> bool {
+    if let NextState::Pending(pending) = next {
+        debug!(
+            "ignored change to {:?}, {:?} is already queued",
+            state, pending
+        );
+        return false;
+    }
+    next.set(state);
+    true
+}
+
+/// Run condition for [`InRun`]'s exit: whether the run was left without
+/// reaching the game over screen, such as by suspending it. Whatever the
+/// game over screen still needs from a run is cleaned up when it exits
+/// instead, or here when it is skipped.
+pub fn run_abandoned(state: Res<State<GameState>>) -> bool {
+    state.get() != &GameState::GameOver
+}
+
+/// Space, a gamepad's A button or the control preset's start button starts
+/// a run alone, and 2 starts one in co-op.
+#[allow(clippy::too_many_arguments)]
+fn start_game(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    settings: Res<Settings>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut player_count: ResMut<PlayerCount>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+) {
+    let gamepad_start = gamepads.iter().any(|gamepad| {
+        gamepad_input.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
+    });
+    let preset_start = settings
+        .controls
+        .start_pressed(&keyboard_input, &mouse_input);
+
+    let players = if keyboard_input.just_pressed(KeyCode::Digit2) {
+        2
+    } else if keyboard_input.just_pressed(KeyCode::Space) || gamepad_start || preset_start {
+        1
+    } else {
+        return;
+    };
+
+    if try_change(&mut next_state, GameState::Playing) {
+        player_count.0 = players;
+        ev_menu.send(MenuSelectEvent);
+    }
+}
+
+/// `state <title|playing|gameover>` jumps straight to a state.
+fn state_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let next = match args {
+        ["title"] => GameState::Title,
+        ["playing"] => GameState::Playing,
+        ["gameover"] => GameState::GameOver,
+        _ => return Err("usage: state <title|playing|gameover>".to_string()),
+    };
+
+    let mut next_state = world.resource_mut::<NextState<GameState>>();
+    if !try_change(&mut next_state, next.clone()) {
+        return Err("another state change is already queued".to_string());
+    }
+    Ok(format!("switching to {:?}", next))
+}
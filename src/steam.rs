@@ -0,0 +1,82 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use steamworks::{Client, ClientManager, Leaderboard, SingleClient, UploadScoreMethod};
+
+use crate::{
+    score::{Ranked, Scoreboard},
+    states::GameState,
+};
+
+const LEADERBOARD_NAME: &str = "High Scores";
+
+/// Submits each run's final score to the Steam leaderboard. Does nothing,
+/// apart from a warning, when the game wasn't started through Steam.
+pub struct SteamPlugin;
+
+impl Plugin for SteamPlugin {
+    fn build(&self, app: &mut App) {
+        let (client, single) = match Client::init() {
+            Ok(clients) => clients,
+            Err(err) => {
+                warn!("steam is unavailable: {}", err);
+                return;
+            }
+        };
+
+        let leaderboard = SteamLeaderboard::default();
+        let found = leaderboard.0.clone();
+        client
+            .user_stats()
+            .find_leaderboard(LEADERBOARD_NAME, move |result| match result {
+                Ok(Some(leaderboard)) => *found.lock().unwrap() = Some(leaderboard),
+                Ok(None) => warn!("steam leaderboard '{}' doesn't exist", LEADERBOARD_NAME),
+                Err(err) => warn!("failed to find steam leaderboard: {}", err),
+            });
+
+        app.insert_resource(SteamClient(client))
+            .insert_resource(leaderboard)
+            .insert_non_send_resource(single)
+            .add_systems(Update, run_steam_callbacks)
+            .add_systems(OnEnter(GameState::GameOver), submit_score);
+    }
+}
+
+#[derive(Resource)]
+pub struct SteamClient(pub Client<ClientManager>);
+
+/// Filled in by a Steam callback once the leaderboard has been looked up.
+#[derive(Resource, Default)]
+struct SteamLeaderboard(Arc<Mutex<Option<Leaderboard>>>);
+
+fn run_steam_callbacks(single: NonSend<SingleClient<ClientManager>>) {
+    single.run_callbacks();
+}
+
+fn submit_score(
+    client: Res<SteamClient>,
+    leaderboard: Res<SteamLeaderboard>,
+    scoreboard: Res<Scoreboard>,
+    ranked: Res<Ranked>,
+) {
+    if !ranked.0 {
+        return;
+    }
+
+    let leaderboard = match leaderboard.0.lock().unwrap().clone() {
+        Some(leaderboard) => leaderboard,
+        None => return,
+    };
+
+    client.0.user_stats().upload_leaderboard_score(
+        &leaderboard,
+        UploadScoreMethod::KeepBest,
+        scoreboard.score as i32,
+        &[],
+        |result| {
+            if let Err(err) = result {
+                warn!("failed to submit score to steam: {}", err);
+            }
+        },
+    );
+}
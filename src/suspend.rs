@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+
+#[cfg(feature = "online")]
+use crate::online;
+use crate::{
+    daily::DailyRun,
+    locale::Locale,
+    player::{PlayerCount, MAX_PLAYERS},
+    prompts::{InputDevice, Prompt, PromptText},
+    replay::WatchedReplay,
+    save::Save,
+    score,
+    settings::Settings,
+    snapshot::{self, RunSnapshot},
+    states::{self, GameState, InRun, MenuSelectEvent},
+    ui::{TextFont, SCOREBOARD_FONT_SIZE},
+    versus::Versus,
+};
+
+const SUSPENDED_RUN_KEY: &str = "suspended-run.ron";
+const CONTINUE_FONT_SIZE: f32 = 16.0;
+
+/// Lets a run be put aside with "Save & Quit" from the pause menu, and picked
+/// up again with C on the title. Only one run is kept, and it is cleared as
+/// it is resumed, so a run can't be replayed from the same point.
+///
+/// A resumed run isn't ranked and earns no coins: its replay would only
+/// start where it was resumed, and the saved run is a file anyone can edit.
+/// Versus rounds, daily challenges, watched replays and online matches can't
+/// be suspended.
+///
+/// Going back to the title leaves [`InRun`], which despawns the run and
+/// undoes whatever it set up, as it would on the game over screen.
+pub struct SuspendPlugin;
+
+impl Plugin for SuspendPlugin {
+    fn build(&self, app: &mut App) {
+        let suspended =
+            SuspendedRun::load(&app.world_mut().get_resource_or_insert_with(Save::default));
+        app.insert_resource(suspended)
+            .add_event::<SuspendEvent>()
+            .add_systems(PostUpdate, (suspend_run, restore_run))
+            .add_systems(OnEnter(GameState::Title), spawn_continue)
+            .add_systems(Update, continue_run.run_if(in_state(GameState::Title)))
+            .add_systems(OnExit(GameState::GameOver), end_resumed_run)
+            .add_systems(OnExit(InRun), end_resumed_run.run_if(states::run_abandoned));
+    }
+}
+
+/// Asks for the run in progress to be saved, and for the game to go back to
+/// the title.
+#[derive(Event)]
+pub struct SuspendEvent;
+
+/// The run waiting to be continued from the title, if there is one.
+#[derive(Resource, Default)]
+pub struct SuspendedRun(pub Option<RunSnapshot>);
+
+impl SuspendedRun {
+    /// A saved run with no players, or more than a run can have, was edited
+    /// and is dropped.
+    pub fn load(save: &Save) -> Self {
+        let snapshot = save
+            .0
+            .load(SUSPENDED_RUN_KEY)
+            .ok()
+            .flatten()
+            .and_then(|bytes| ron::de::from_bytes::<Option<RunSnapshot>>(&bytes).ok())
+            .flatten()
+            .filter(|snapshot| {
+                let valid = (1..=MAX_PLAYERS).contains(&snapshot.player_count);
+                if !valid {
+                    warn!(
+                        "ignoring suspended run with {} players",
+                        snapshot.player_count
+                    );
+                }
+                valid
+            });
+        Self(snapshot)
+    }
+
+    fn save(&self, save: &Save) -> Result<(), String> {
+        ron::to_string(&self.0)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                save.0
+                    .store(SUSPENDED_RUN_KEY, contents.as_bytes())
+                    .map_err(|err| err.to_string())
+            })
+    }
+}
+
+/// Present while a resumed run is played, until its game over screen is left.
+#[derive(Resource)]
+pub struct ResumedRun {
+    /// Seconds played before it was last resumed.
+    pub elapsed: f32,
+}
+
+/// The run being resumed, until the freshly started run it goes into is
+/// ready for it.
+#[derive(Resource)]
+struct ResumingRun(RunSnapshot);
+
+fn can_suspend(world: &World) -> Result<(), &'static str> {
+    let versus = world
+        .get_resource::<Versus>()
+        .is_some_and(|versus| versus.active);
+    if versus {
+        return Err("versus rounds can't be suspended");
+    }
+    if world.contains_resource::<DailyRun>() {
+        return Err("the daily challenge can't be suspended");
+    }
+    if world.contains_resource::<WatchedReplay>() {
+        return Err("replays can't be suspended");
+    }
+    #[cfg(feature = "online")]
+    if online::in_session(world) {
+        return Err("online matches can't be suspended");
+    }
+    if world.get_resource::<PlayerCount>().unwrap().0 == 0 {
+        return Err("there is no run to suspend");
+    }
+    Ok(())
+}
+
+fn suspend_run(world: &mut World) {
+    let requested = world
+        .get_resource_mut::<Events<SuspendEvent>>()
+        .is_some_and(|mut events| events.drain().count() > 0);
+    if !requested {
+        return;
+    }
+    if let Err(reason) = can_suspend(world) {
+        info!("not suspending: {}", reason);
+        return;
+    }
+
+    let snapshot = RunSnapshot::capture(world);
+    let seed = snapshot.seed;
+
+    let suspended = SuspendedRun(Some(snapshot));
+    if let Err(err) = suspended.save(world.get_resource::<Save>().unwrap()) {
+        warn!("failed to suspend run: {}", err);
+        return;
+    }
+    info!("suspended run with seed {}", seed);
+    world.insert_resource(suspended);
+
+    let mut next_state = world.resource_mut::<NextState<GameState>>();
+    states::try_change(&mut next_state, GameState::Title);
+}
+
+/// Waits for the new run to spawn its players and spawner, which happens
+/// with commands, before putting the suspended run's state over them.
+fn restore_run(world: &mut World) {
+    if !world.contains_resource::<ResumingRun>() || !snapshot::run_started(world) {
+        return;
+    }
+
+    let snapshot = world.remove_resource::<ResumingRun>().unwrap().0;
+    info!("resuming run with seed {}", snapshot.seed);
+    snapshot.restore(world);
+    score::tamper(world);
+    world.insert_resource(ResumedRun {
+        elapsed: snapshot.elapsed,
+    });
+}
+
+fn spawn_continue(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    device: Res<InputDevice>,
+    locale: Res<Locale>,
+    suspended: Res<SuspendedRun>,
+) {
+    if suspended.0.is_none() {
+        return;
+    }
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                Prompt::Continue.text(*device, &locale),
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: CONTINUE_FONT_SIZE,
+                    color: settings.palette.colors().score,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(320.0 - SCOREBOARD_FONT_SIZE),
+                top: Val::Px(220.0 + SCOREBOARD_FONT_SIZE * 6.5),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(PromptText(Prompt::Continue))
+        .insert(StateScoped(GameState::Title));
+}
+
+/// The saved run is cleared before it starts, so it can't be resumed twice.
+#[allow(clippy::too_many_arguments)]
+fn continue_run(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    save: Res<Save>,
+    mut suspended: ResMut<SuspendedRun>,
+    mut player_count: ResMut<PlayerCount>,
+    mut versus: ResMut<Versus>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyC) || suspended.0.is_none() {
+        return;
+    }
+    if !states::try_change(&mut next_state, GameState::Playing) {
+        return;
+    }
+
+    let snapshot = suspended.0.take().unwrap();
+    if let Err(err) = suspended.save(&save) {
+        warn!("failed to clear suspended run: {}", err);
+    }
+    player_count.0 = snapshot.player_count;
+    versus.active = false;
+    commands.insert_resource(ResumingRun(snapshot));
+    ev_menu.send(MenuSelectEvent);
+}
+
+fn end_resumed_run(mut commands: Commands) {
+    commands.remove_resource::<ResumedRun>();
+}
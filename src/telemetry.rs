@@ -0,0 +1,188 @@
+use bevy::{prelude::*, tasks::IoTaskPool};
+use serde::Serialize;
+
+use crate::{
+    config::GameConfig,
+    controls::ControlPreset,
+    enemy::{Enemy, EnemyTableHandle},
+    enemy_table::EnemyTable,
+    history::RunMode,
+    locale::Language,
+    palette::Palette,
+    player::{PlayerCount, PlayerDeathEvent},
+    replay::{ReplayRecorder, WatchedReplay},
+    settings::Settings,
+    states::GameState,
+    suspend::ResumedRun,
+    versus::Versus,
+};
+
+/// How many runs are sent together.
+const BATCH_SIZE: usize = 5;
+
+/// Sends anonymous reports of finished runs to the server at
+/// `telemetry_url` in `config.ron`, once the player has opted in from the
+/// pause menu. Nothing is collected while the setting is off, and runs
+/// already waiting are dropped when it is turned off.
+///
+/// A report has how long the run lasted, its mode, the enemy kind that ended
+/// it and the settings that change how it plays, but no name, seed or
+/// anything else that could tell players apart. Reports are posted in
+/// batches of five to `POST /runs` as `{ version, runs }`, on the IO task
+/// pool. A batch that fails to send isn't retried, and runs not sent yet
+/// when the game closes are lost.
+pub struct TelemetryPlugin;
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        let url = app
+            .world()
+            .get_resource::<GameConfig>()
+            .and_then(|config| config.telemetry_url.clone());
+        let url = match url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => {
+                info!("telemetry_url is not set, telemetry is disabled");
+                return;
+            }
+        };
+
+        app.insert_resource(TelemetryServer { url })
+            .init_resource::<PendingRuns>()
+            .init_resource::<DeathCause>()
+            .add_systems(Update, record_death_cause)
+            .add_systems(Update, drop_pending_runs)
+            .add_systems(OnEnter(GameState::Playing), reset_death_cause)
+            .add_systems(OnEnter(GameState::GameOver), report_run);
+    }
+}
+
+#[derive(Resource)]
+struct TelemetryServer {
+    url: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct RunReport {
+    /// Seconds spent `Playing`.
+    duration: f32,
+    mode: RunMode,
+    /// The name of the enemy kind that hit the last player standing.
+    death_cause: Option<String>,
+    settings: SettingsUsed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct SettingsUsed {
+    game_speed: u32,
+    assist_hitbox: bool,
+    assist_lives: bool,
+    reduced_motion: bool,
+    controls: ControlPreset,
+    palette: Palette,
+    language: Language,
+}
+
+impl From<&Settings> for SettingsUsed {
+    fn from(settings: &Settings) -> Self {
+        Self {
+            game_speed: settings.game_speed,
+            assist_hitbox: settings.assist_hitbox,
+            assist_lives: settings.assist_lives,
+            reduced_motion: settings.reduced_motion,
+            controls: settings.controls,
+            palette: settings.palette,
+            language: settings.language,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Batch {
+    version: &'static str,
+    runs: Vec<RunReport>,
+}
+
+/// Finished runs waiting for a full batch.
+#[derive(Resource, Default)]
+struct PendingRuns(Vec<RunReport>);
+
+/// The kind of the enemy that last took a player down in the current run.
+#[derive(Resource, Default)]
+struct DeathCause(Option<String>);
+
+fn reset_death_cause(mut cause: ResMut<DeathCause>) {
+    cause.0 = None;
+}
+
+/// Runs outside of the states, since the run is already `Dying` by the time
+/// the death that ended it is read.
+fn record_death_cause(
+    mut ev_death: EventReader<PlayerDeathEvent>,
+    tables: Res<Assets<EnemyTable>>,
+    handle: Res<EnemyTableHandle>,
+    enemies: Query<&Enemy>,
+    mut cause: ResMut<DeathCause>,
+) {
+    for death in ev_death.read() {
+        let name = enemies.get(death.enemy).ok().and_then(|enemy| {
+            let table = tables.get(&handle.0)?;
+            Some(table.kinds.get(enemy.kind())?.name.clone())
+        });
+        cause.0 = name;
+    }
+}
+
+fn drop_pending_runs(settings: Res<Settings>, mut pending: ResMut<PendingRuns>) {
+    if settings.is_changed() && !settings.send_telemetry && !pending.0.is_empty() {
+        pending.0.clear();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn report_run(
+    server: Res<TelemetryServer>,
+    settings: Res<Settings>,
+    recorder: Res<ReplayRecorder>,
+    player_count: Res<PlayerCount>,
+    versus: Res<Versus>,
+    cause: Res<DeathCause>,
+    watched: Option<Res<WatchedReplay>>,
+    resumed: Option<Res<ResumedRun>>,
+    mut pending: ResMut<PendingRuns>,
+) {
+    if !settings.send_telemetry || watched.is_some() || player_count.0 == 0 {
+        return;
+    }
+
+    let mode = if versus.active {
+        RunMode::Versus
+    } else if player_count.0 > 1 {
+        RunMode::CoOp
+    } else {
+        RunMode::Solo
+    };
+    pending.0.push(RunReport {
+        duration: resumed.map_or(0.0, |resumed| resumed.elapsed) + recorder.duration(),
+        mode,
+        death_cause: cause.0.clone(),
+        settings: SettingsUsed::from(&*settings),
+    });
+    if pending.0.len() < BATCH_SIZE {
+        return;
+    }
+
+    let batch = Batch {
+        version: env!("CARGO_PKG_VERSION"),
+        runs: std::mem::take(&mut pending.0),
+    };
+    let url = format!("{}/runs", server.url);
+    IoTaskPool::get()
+        .spawn(async move {
+            match ureq::post(&url).send_json(&batch) {
+                Ok(_) => debug!("sent {} runs of telemetry", batch.runs.len()),
+                Err(err) => warn!("failed to send telemetry: {}", err),
+            }
+        })
+        .detach();
+}
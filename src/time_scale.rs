@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{console::AddConsoleCommand, settings::Settings, states::GameState};
+
+/// The fastest `set speed` allows, the same as the debug speed keys.
+const MAX_SPEED: f32 = 4.0;
+
+pub struct TimeScalePlugin;
+
+impl Plugin for TimeScalePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TimeScale(1.0))
+            .insert_resource(GameSpeed(1.0))
+            .insert_resource(HitStop { frames: 0 })
+            .add_event::<HitStopEvent>()
+            .add_cheat_command("set", set_command)
+            .add_systems(Update, apply_game_speed_setting)
+            .add_systems(OnEnter(GameState::Playing), reset_game_speed)
+            .add_systems(PreUpdate, update_hit_stop);
+    }
+}
+
+/// Multiplier applied to the frame delta by every system that advances gameplay:
+/// movement, timers, animation and scoring. It is recomputed every frame from
+/// [`GameSpeed`] and any hit stop, so change those rather than this.
+#[derive(Resource)]
+pub struct TimeScale(pub f32);
+
+impl TimeScale {
+    pub fn delta(&self, time: &Time) -> Duration {
+        time.delta().mul_f32(self.0)
+    }
+
+    pub fn delta_seconds(&self, time: &Time) -> f32 {
+        time.delta_seconds() * self.0
+    }
+}
+
+/// How fast the game runs while it isn't frozen by a hit stop. Slow motion,
+/// the console's `set speed` and the debug speed keys all go through this.
+#[derive(Resource)]
+pub struct GameSpeed(pub f32);
+
+/// Freezes the simulation for the given number of frames, unless reduced
+/// motion is on.
+#[derive(Event)]
+pub struct HitStopEvent(pub u32);
+
+#[derive(Resource)]
+struct HitStop {
+    frames: u32,
+}
+
+fn update_hit_stop(
+    mut ev_hit_stop: EventReader<HitStopEvent>,
+    game_speed: Res<GameSpeed>,
+    settings: Res<Settings>,
+    mut hit_stop: ResMut<HitStop>,
+    mut time_scale: ResMut<TimeScale>,
+) {
+    for event in ev_hit_stop.read() {
+        if !settings.reduced_motion {
+            hit_stop.frames = hit_stop.frames.max(event.0);
+        }
+    }
+
+    if hit_stop.frames > 0 {
+        hit_stop.frames -= 1;
+        time_scale.0 = 0.0;
+    } else {
+        time_scale.0 = game_speed.0;
+    }
+}
+
+/// Applies the accessibility speed setting. The console and debug keys can
+/// still change the speed afterwards.
+fn apply_game_speed_setting(settings: Res<Settings>, mut game_speed: ResMut<GameSpeed>) {
+    if settings.is_changed() {
+        game_speed.0 = settings.game_speed as f32 / 100.0;
+    }
+}
+
+/// Every run starts at the speed in the settings, whatever the console or
+/// debug keys left it at.
+fn reset_game_speed(settings: Res<Settings>, mut game_speed: ResMut<GameSpeed>) {
+    game_speed.0 = settings.game_speed as f32 / 100.0;
+}
+
+/// `set speed <scale>` changes how fast the game runs, between 0 and 4.
+fn set_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    match args {
+        ["speed", speed] => {
+            let speed = match speed.parse::<f32>() {
+                Ok(speed) if speed.is_finite() => speed.clamp(0.0, MAX_SPEED),
+                _ => return Err(format!("invalid speed '{}'", speed)),
+            };
+            world.insert_resource(GameSpeed(speed));
+            Ok(format!("speed set to {}", speed))
+        }
+        _ => Err("usage: set speed <scale>".to_string()),
+    }
+}
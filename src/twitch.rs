@@ -0,0 +1,123 @@
+use std::{
+    collections::HashSet,
+    env,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Mutex,
+    },
+    thread,
+};
+
+use bevy::prelude::*;
+
+use crate::{enemy::EnemyBurstEvent, states::RunState};
+
+const TWITCH_CHANNEL_VAR: &str = "DODGER_TWITCH_CHANNEL";
+const TWITCH_CHAT_ADDRESS: &str = "irc.chat.twitch.tv:6667";
+/// Anonymous, read-only login that Twitch chat accepts without a token.
+const TWITCH_NICK: &str = "justinfan31415";
+/// Chat gets one vote per window, so viewers can't flood the game.
+const VOTE_SECONDS: f32 = 30.0;
+const VOTES_NEEDED: usize = 3;
+const METEOR_SHOWER_ENEMIES: u32 = 12;
+
+/// Lets a Twitch channel's chat vote on events during a run. Set
+/// `DODGER_TWITCH_CHANNEL` to the channel to read; chat is only read, never
+/// written to.
+pub struct TwitchPlugin;
+
+impl Plugin for TwitchPlugin {
+    fn build(&self, app: &mut App) {
+        let channel = match env::var(TWITCH_CHANNEL_VAR) {
+            Ok(channel) => channel.trim_start_matches('#').to_lowercase(),
+            Err(_) => {
+                info!("{} is not set, twitch chat is disabled", TWITCH_CHANNEL_VAR);
+                return;
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            if let Err(err) = read_chat(&channel, sender) {
+                warn!("twitch chat disconnected: {}", err);
+            }
+        });
+
+        app.insert_resource(ChatMessages(Mutex::new(receiver)))
+            .insert_resource(ChatVote {
+                voters: HashSet::new(),
+                timer: Timer::from_seconds(VOTE_SECONDS, TimerMode::Repeating),
+            })
+            .add_systems(Update, run_chat_vote.run_if(in_state(RunState::Running)));
+    }
+}
+
+/// A chat message as `(user, text)`.
+#[derive(Resource)]
+struct ChatMessages(Mutex<Receiver<(String, String)>>);
+
+#[derive(Resource)]
+struct ChatVote {
+    voters: HashSet<String>,
+    timer: Timer,
+}
+
+/// Viewers vote by typing `!meteors`. Each viewer counts once per window,
+/// and enough votes by the end of it start a meteor shower.
+fn run_chat_vote(
+    time: Res<Time>,
+    messages: Res<ChatMessages>,
+    mut vote: ResMut<ChatVote>,
+    mut ev_burst: EventWriter<EnemyBurstEvent>,
+) {
+    for (user, text) in messages.0.lock().unwrap().try_iter() {
+        if text.trim() == "!meteors" {
+            vote.voters.insert(user);
+        }
+    }
+
+    if vote.timer.tick(time.delta()).just_finished() {
+        if vote.voters.len() >= VOTES_NEEDED {
+            info!("chat voted for a meteor shower");
+            ev_burst.send(EnemyBurstEvent(METEOR_SHOWER_ENEMIES));
+        }
+        vote.voters.clear();
+    }
+}
+
+/// Runs on its own thread until the connection drops.
+fn read_chat(channel: &str, sender: Sender<(String, String)>) -> io::Result<()> {
+    let mut stream = TcpStream::connect(TWITCH_CHAT_ADDRESS)?;
+    write!(stream, "NICK {}\r\nJOIN #{}\r\n", TWITCH_NICK, channel)?;
+    info!("reading twitch chat for #{}", channel);
+
+    let reader = BufReader::new(stream.try_clone()?);
+    for line in reader.lines() {
+        let line = line?;
+
+        if let Some(server) = line.strip_prefix("PING ") {
+            write!(stream, "PONG {}\r\n", server)?;
+        } else if let Some(message) = parse_message(&line) {
+            if sender.send(message).is_err() {
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Picks the user and text out of `:user!user@host PRIVMSG #channel :text`.
+fn parse_message(line: &str) -> Option<(String, String)> {
+    let (prefix, rest) = line.strip_prefix(':')?.split_once(' ')?;
+    let (command, rest) = rest.split_once(' ')?;
+    if command != "PRIVMSG" {
+        return None;
+    }
+
+    let user = prefix.split('!').next()?;
+    let (_, text) = rest.split_once(" :")?;
+    Some((user.to_string(), text.to_string()))
+}
@@ -0,0 +1,402 @@
+use bevy::{asset::LoadState, color::palettes::css, prelude::*};
+
+use crate::{
+    loading::{FailedAssets, LoadingAssets},
+    locale::{Locale, LocalizedText},
+    mobile::SafeArea,
+    player::{Player, PlayerCount},
+    prefab::{ApplyPrefab, Prefab},
+    prompts::{InputDevice, Prompt, PromptText},
+    score::{Ranked, ScoreText, Scoreboard},
+    settings::Settings,
+    shop::Upgrades,
+    skins::Wardrobe,
+    states::{GameState, InRun, RunState},
+    suspend::ResumedRun,
+};
+
+pub const SCOREBOARD_FONT_SIZE: f32 = 32.0;
+pub const SUMMARY_FONT_SIZE: f32 = 64.0;
+const SCOREBOARD_TEXT_PADDING: f32 = 16.0;
+const ERROR_FONT_SIZE: f32 = 12.0;
+const HINT_FONT_SIZE: f32 = 16.0;
+/// How visible a player's HUD marker stays once they are down.
+const DOWN_PLAYER_ALPHA: f32 = 0.25;
+/// Built into the binary so there is always something to draw text with.
+const FALLBACK_FONT: &[u8] = include_bytes!("../assets/pixeled.ttf");
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_font)
+            .add_systems(OnExit(GameState::Loading), use_fallback_font)
+            .add_systems(OnEnter(GameState::Title), (setup_title, show_load_errors))
+            .add_systems(OnEnter(GameState::Playing), spawn_hud)
+            .add_systems(
+                Update,
+                update_player_status.run_if(in_state(RunState::Running)),
+            )
+            .add_systems(
+                Update,
+                update_player_status.run_if(in_state(GameState::Dying)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), show_summary);
+    }
+}
+
+#[derive(Resource)]
+pub struct TextFont(pub Handle<Font>);
+
+fn load_font(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    let font = asset_server.load("pixeled.ttf");
+    loading.add(&font);
+    commands.insert_resource(TextFont(font));
+}
+
+fn use_fallback_font(
+    asset_server: Res<AssetServer>,
+    font: Res<TextFont>,
+    mut fonts: ResMut<Assets<Font>>,
+) {
+    if let Some(LoadState::Failed(_)) = asset_server.get_load_state(&font.0) {
+        let fallback =
+            Font::try_from_bytes(FALLBACK_FONT.to_vec()).expect("embedded font is valid");
+        fonts.insert(&font.0, fallback);
+    }
+}
+
+fn setup_title(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    device: Res<InputDevice>,
+    locale: Res<Locale>,
+) {
+    let colors = settings.palette.colors();
+
+    commands
+        .spawn(TextBundle {
+            text: Text {
+                sections: vec![TextSection {
+                    value: "Dodger".to_string(),
+                    style: TextStyle {
+                        font: font.0.clone(),
+                        font_size: SUMMARY_FONT_SIZE,
+                        color: colors.text,
+                    },
+                }],
+                justify: JustifyText::Center,
+                ..default()
+            },
+            style: Style {
+                align_self: AlignSelf::Center,
+                justify_content: JustifyContent::Center,
+                position_type: PositionType::Absolute,
+                left: Val::Px(320.0 - SUMMARY_FONT_SIZE),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(StateScoped(GameState::Title));
+
+    commands
+        .spawn(TextBundle {
+            text: Text {
+                sections: vec![TextSection {
+                    value: Prompt::Start.text(*device, &locale),
+                    style: TextStyle {
+                        font: font.0.clone(),
+                        font_size: SCOREBOARD_FONT_SIZE,
+                        color: colors.text,
+                    },
+                }],
+                justify: JustifyText::Center,
+                ..default()
+            },
+            style: Style {
+                align_self: AlignSelf::Center,
+                justify_content: JustifyContent::Center,
+                position_type: PositionType::Absolute,
+                left: Val::Px(320.0 - SCOREBOARD_FONT_SIZE),
+                top: Val::Px(220.0 + SCOREBOARD_FONT_SIZE),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(PromptText(Prompt::Start))
+        .insert(StateScoped(GameState::Title));
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                Prompt::Modes.text(*device, &locale),
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: HINT_FONT_SIZE,
+                    color: colors.text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(320.0 - SCOREBOARD_FONT_SIZE),
+                top: Val::Px(220.0 + SCOREBOARD_FONT_SIZE * 2.5),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(PromptText(Prompt::Modes))
+        .insert(StateScoped(GameState::Title));
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                Prompt::Screens.text(*device, &locale),
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: HINT_FONT_SIZE,
+                    color: colors.text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(320.0 - SCOREBOARD_FONT_SIZE),
+                top: Val::Px(220.0 + SCOREBOARD_FONT_SIZE * 3.25),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(PromptText(Prompt::Screens))
+        .insert(StateScoped(GameState::Title));
+}
+
+/// Lists the assets that failed to load along the bottom of the title screen.
+fn show_load_errors(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    failed: Res<FailedAssets>,
+    safe_area: Res<SafeArea>,
+    locale: Res<Locale>,
+) {
+    if failed.0.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                locale.format("load-errors", &[("files", failed.0.join(", ").into())]),
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: ERROR_FONT_SIZE,
+                    color: css::RED.into(),
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(SCOREBOARD_TEXT_PADDING + safe_area.bottom),
+                left: Val::Px(SCOREBOARD_TEXT_PADDING + safe_area.left),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(StateScoped(GameState::Title));
+}
+
+/// One marker per player in a co-op run, dimmed once that player is down.
+#[derive(Component)]
+struct PlayerStatusText;
+
+fn spawn_hud(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    safe_area: Res<SafeArea>,
+    player_count: Res<PlayerCount>,
+    wardrobe: Res<Wardrobe>,
+    locale: Res<Locale>,
+) {
+    let colors = settings.palette.colors();
+
+    let score_text = commands
+        .spawn(TextBundle {
+            text: Text {
+                sections: vec![
+                    TextSection {
+                        value: locale.text("hud-score"),
+                        style: TextStyle {
+                            font: font.0.clone(),
+                            font_size: SCOREBOARD_FONT_SIZE,
+                            color: colors.text,
+                        },
+                    },
+                    TextSection {
+                        value: "".to_string(),
+                        style: TextStyle {
+                            font: font.0.clone(),
+                            font_size: SCOREBOARD_FONT_SIZE,
+                            color: colors.score,
+                        },
+                    },
+                ],
+                ..default()
+            },
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(SCOREBOARD_TEXT_PADDING + safe_area.top),
+                left: Val::Px(SCOREBOARD_TEXT_PADDING + safe_area.left),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(ScoreText::default())
+        .insert(LocalizedText("hud-score"))
+        .insert(StateScoped(InRun))
+        .id();
+    commands.add(ApplyPrefab {
+        entity: score_text,
+        prefab: Prefab::Hud,
+    });
+
+    if player_count.0 < 2 {
+        return;
+    }
+
+    // Player one's marker takes the tint of their skin, if it has one.
+    let sections = (0..player_count.0)
+        .map(|index| TextSection {
+            value: format!(" P{}", index + 1),
+            style: TextStyle {
+                font: font.0.clone(),
+                font_size: SCOREBOARD_FONT_SIZE,
+                color: match wardrobe.skin().color {
+                    Some(color) if index == 0 => color,
+                    _ => colors.text,
+                },
+            },
+        })
+        .collect();
+
+    commands
+        .spawn(TextBundle {
+            text: Text {
+                sections,
+                ..default()
+            },
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(SCOREBOARD_TEXT_PADDING + safe_area.top),
+                right: Val::Px(SCOREBOARD_TEXT_PADDING + safe_area.right),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(PlayerStatusText)
+        .insert(StateScoped(InRun));
+}
+
+fn update_player_status(
+    players: Query<&Player>,
+    mut query: Query<&mut Text, With<PlayerStatusText>>,
+) {
+    for mut text in query.iter_mut() {
+        for (index, section) in text.sections.iter_mut().enumerate() {
+            let alive = players.iter().any(|player| player.index == index);
+            section
+                .style
+                .color
+                .set_alpha(if alive { 1.0 } else { DOWN_PLAYER_ALPHA });
+        }
+    }
+}
+
+/// Runs that used assists, upgrades or a slower speed, or were resumed, are
+/// flagged under the score.
+#[allow(clippy::too_many_arguments)]
+fn show_summary(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    scoreboard: Res<Scoreboard>,
+    settings: Res<Settings>,
+    upgrades: Res<Upgrades>,
+    player_count: Res<PlayerCount>,
+    ranked: Res<Ranked>,
+    resumed: Option<Res<ResumedRun>>,
+    locale: Res<Locale>,
+) {
+    let label = if player_count.0 > 1 {
+        locale.text("summary-co-op-score")
+    } else {
+        locale.text("summary-score")
+    };
+    let unranked = if ranked.0 {
+        String::new()
+    } else {
+        let mut assists = settings.assists(&locale);
+        assists.extend(
+            upgrades
+                .active
+                .iter()
+                .map(|upgrade| locale.text(upgrade.name_id())),
+        );
+        if resumed.is_some() {
+            assists.push(locale.text("unranked-resumed"));
+        }
+        if assists.is_empty() {
+            format!("\n{}", locale.text("summary-unranked"))
+        } else {
+            let reasons = assists.join(", ");
+            let unranked =
+                locale.format("summary-unranked-because", &[("reasons", reasons.into())]);
+            format!("\n{}", unranked)
+        }
+    };
+
+    let colors = settings.palette.colors();
+    commands
+        .spawn(TextBundle {
+            text: Text {
+                sections: vec![
+                    TextSection {
+                        value: label,
+                        style: TextStyle {
+                            font: font.0.clone(),
+                            font_size: SUMMARY_FONT_SIZE,
+                            color: colors.text,
+                        },
+                    },
+                    TextSection {
+                        value: format!("{}", scoreboard.score as i16),
+                        style: TextStyle {
+                            font: font.0.clone(),
+                            font_size: SUMMARY_FONT_SIZE,
+                            color: colors.score,
+                        },
+                    },
+                    TextSection {
+                        value: unranked,
+                        style: TextStyle {
+                            font: font.0.clone(),
+                            font_size: HINT_FONT_SIZE,
+                            color: colors.text,
+                        },
+                    },
+                ],
+                justify: JustifyText::Center,
+                ..default()
+            },
+            style: Style {
+                align_self: AlignSelf::Center,
+                justify_content: JustifyContent::Center,
+                position_type: PositionType::Absolute,
+                left: Val::Px(320.0 - SUMMARY_FONT_SIZE),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(StateScoped(GameState::GameOver));
+}
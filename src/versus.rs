@@ -0,0 +1,306 @@
+use bevy::{input::mouse::MouseWheel, prelude::*, window::PrimaryWindow};
+
+use crate::{
+    bounds::PlayArea,
+    enemy::{AutoSpawn, EnemyDropEvent, EnemyTableHandle},
+    enemy_table::EnemyTable,
+    locale::Locale,
+    player::PlayerCount,
+    settings::Settings,
+    states::{self, GameState, InRun, MenuSelectEvent, RunState},
+    time_scale::TimeScale,
+    ui::{TextFont, SCOREBOARD_FONT_SIZE},
+    ACTOR_Z,
+};
+
+const START_BUDGET: f32 = 3.0;
+const MAX_BUDGET: f32 = 10.0;
+const BUDGET_PER_SECOND: f32 = 1.0;
+const DROP_COOLDOWN: f32 = 0.5;
+const DROPPER_FONT_SIZE: f32 = 16.0;
+const CURSOR_SIZE: Vec2 = Vec2::new(16.0, 4.0);
+const CURSOR_UNAFFORDABLE_ALPHA: f32 = 0.3;
+
+/// A two round match for two players. While one dodges, the other picks what
+/// falls and where with the mouse, from a budget that refills over time.
+/// Then they swap, and whoever survived longer wins. There are no automatic
+/// spawns during a round.
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Versus>()
+            .add_systems(Update, start_versus.run_if(in_state(GameState::Title)))
+            .add_systems(Update, start_versus.run_if(in_state(GameState::GameOver)))
+            .add_systems(OnEnter(GameState::Playing), setup_round)
+            .add_systems(
+                Update,
+                (refill_budget, drop_enemies.after(refill_budget))
+                    .run_if(in_state(RunState::Running)),
+            )
+            .add_systems(OnEnter(GameState::Dying), end_round)
+            .add_systems(OnEnter(GameState::GameOver), show_result);
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Versus {
+    pub active: bool,
+    /// How long the dodger survived each round played so far. Player one
+    /// dodges the first round and player two the second.
+    pub times: Vec<f32>,
+    elapsed: f32,
+    budget: f32,
+    cooldown: f32,
+    /// Index of the enemy kind the dropper has picked.
+    kind: usize,
+}
+
+impl Versus {
+    fn finished(&self) -> bool {
+        self.times.len() == 2
+    }
+}
+
+#[derive(Component)]
+struct DropCursor;
+
+#[derive(Component)]
+struct DropperText;
+
+/// Whether a run is one player on their own, rather than co-op or versus.
+pub fn is_solo_run(player_count: &PlayerCount, versus: &Versus) -> bool {
+    player_count.0 == 1 && !versus.active
+}
+
+/// 3 starts a match, or its second round once the first is over. Any other
+/// way of starting a run leaves versus mode.
+fn start_versus(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    mut versus: ResMut<Versus>,
+    mut player_count: ResMut<PlayerCount>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_menu: EventWriter<MenuSelectEvent>,
+) {
+    let gamepad_start = gamepads.iter().any(|gamepad| {
+        gamepad_input.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
+    });
+    if keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Digit2]) || gamepad_start {
+        versus.active = false;
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Digit3)
+        && states::try_change(&mut next_state, GameState::Playing)
+    {
+        if !versus.active || versus.finished() {
+            *versus = Versus {
+                active: true,
+                ..default()
+            };
+        }
+        player_count.0 = 1;
+        ev_menu.send(MenuSelectEvent);
+    }
+}
+
+fn setup_round(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    play_area: Res<PlayArea>,
+    mut versus: ResMut<Versus>,
+    mut auto_spawn: ResMut<AutoSpawn>,
+) {
+    auto_spawn.0 = !versus.active;
+    if !versus.active {
+        return;
+    }
+
+    versus.elapsed = 0.0;
+    versus.budget = START_BUDGET;
+    versus.cooldown = 0.0;
+
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: settings.palette.colors().enemy,
+                custom_size: Some(CURSOR_SIZE),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, play_area.y.end, ACTOR_Z),
+            ..default()
+        })
+        .insert(DropCursor)
+        .insert(StateScoped(InRun));
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: DROPPER_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(DROPPER_FONT_SIZE),
+                right: Val::Px(DROPPER_FONT_SIZE),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(DropperText)
+        .insert(StateScoped(InRun));
+}
+
+fn refill_budget(time: Res<Time>, time_scale: Res<TimeScale>, mut versus: ResMut<Versus>) {
+    if !versus.active {
+        return;
+    }
+
+    let delta_time = time_scale.delta_seconds(&time);
+    versus.elapsed += delta_time;
+    versus.budget = (versus.budget + BUDGET_PER_SECOND * delta_time).min(MAX_BUDGET);
+    versus.cooldown = (versus.cooldown - delta_time).max(0.0);
+}
+
+/// The mouse wheel or right button picks a kind, and the left button drops
+/// one above the cursor.
+#[allow(clippy::too_many_arguments)]
+fn drop_enemies(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut ev_wheel: EventReader<MouseWheel>,
+    tables: Res<Assets<EnemyTable>>,
+    table_handle: Res<EnemyTableHandle>,
+    play_area: Res<PlayArea>,
+    mut versus: ResMut<Versus>,
+    locale: Res<Locale>,
+    mut ev_drop: EventWriter<EnemyDropEvent>,
+    mut cursors: Query<(&mut Transform, &mut Sprite), With<DropCursor>>,
+    mut texts: Query<&mut Text, With<DropperText>>,
+) {
+    let scrolled = ev_wheel.read().count() > 0;
+    if !versus.active {
+        return;
+    }
+
+    let kinds = match tables.get(&table_handle.0) {
+        Some(table) if !table.kinds.is_empty() => &table.kinds,
+        _ => return,
+    };
+
+    if scrolled || mouse_input.just_pressed(MouseButton::Right) {
+        versus.kind = (versus.kind + 1) % kinds.len();
+    }
+    let index = versus.kind.min(kinds.len() - 1);
+    let kind = &kinds[index];
+    let affordable = versus.budget >= kind.cost && versus.cooldown == 0.0;
+
+    let cursor_x = windows
+        .get_single()
+        .ok()
+        .and_then(|window| Some(window.cursor_position()?.x - window.width() / 2.0))
+        .map(|x| x.clamp(play_area.x.start, play_area.x.end));
+
+    if let Some(x) = cursor_x {
+        for (mut transform, mut sprite) in cursors.iter_mut() {
+            transform.translation.x = x;
+            sprite.color.set_alpha(if affordable {
+                1.0
+            } else {
+                CURSOR_UNAFFORDABLE_ALPHA
+            });
+        }
+
+        if affordable && mouse_input.just_pressed(MouseButton::Left) {
+            ev_drop.send(EnemyDropEvent { kind: index, x });
+            versus.budget -= kind.cost;
+            versus.cooldown = DROP_COOLDOWN;
+        }
+    }
+
+    let dropper = if versus.times.is_empty() { 2 } else { 1 };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = locale.format(
+            "versus-dropper",
+            &[
+                ("player", dropper.into()),
+                ("kind", kind.name.clone().into()),
+                ("cost", kind.cost.round().into()),
+                ("budget", tenths(versus.budget).into()),
+            ],
+        );
+    }
+}
+
+fn end_round(mut versus: ResMut<Versus>) {
+    if versus.active && !versus.finished() {
+        let elapsed = versus.elapsed;
+        versus.times.push(elapsed);
+    }
+}
+
+fn show_result(
+    mut commands: Commands,
+    font: Res<TextFont>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    versus: Res<Versus>,
+) {
+    if !versus.active {
+        return;
+    }
+
+    let value = match versus.times.as_slice() {
+        [first] => locale.format("versus-first-round", &[("seconds", tenths(*first).into())]),
+        [first, second] => {
+            let winner = if first > second {
+                locale.text("versus-p1-wins")
+            } else if second > first {
+                locale.text("versus-p2-wins")
+            } else {
+                locale.text("versus-draw")
+            };
+            locale.format(
+                "versus-result",
+                &[
+                    ("first", tenths(*first).into()),
+                    ("second", tenths(*second).into()),
+                    ("winner", winner.into()),
+                ],
+            )
+        }
+        _ => return,
+    };
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                value,
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: DROPPER_FONT_SIZE,
+                    color: settings.palette.colors().text,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(SCOREBOARD_FONT_SIZE * 2.0),
+                left: Val::Px(SCOREBOARD_FONT_SIZE),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(StateScoped(GameState::GameOver));
+}
+
+/// Rounds a time the way it is shown, so its plural form matches too.
+fn tenths(seconds: f32) -> f64 {
+    (seconds as f64 * 10.0).round() / 10.0
+}
@@ -0,0 +1,215 @@
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+use bevy::{
+    prelude::*,
+    window::{PresentMode, PrimaryWindow},
+    winit::WinitWindows,
+};
+use serde::{Deserialize, Serialize};
+use winit::{
+    dpi::PhysicalPosition,
+    monitor::MonitorHandle,
+    window::{Fullscreen, Window as WinitWindow},
+};
+
+use crate::{launch::LaunchOptions, settings::Settings};
+
+/// Applies the video settings to the primary window whenever they change.
+pub struct VideoPlugin;
+
+impl Plugin for VideoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Monitors>()
+            .add_systems(Startup, apply_launch_fullscreen)
+            .add_systems(Update, apply_video_settings)
+            .add_systems(Update, apply_vsync);
+
+        // Browsers already pace frames, and can't sleep the main thread.
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Last, limit_frame_rate);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum FullscreenMode {
+    #[default]
+    Windowed,
+    Borderless,
+    /// Switches the monitor to its largest video mode.
+    Exclusive,
+}
+
+impl FullscreenMode {
+    /// The message id of its name.
+    pub fn name_id(&self) -> &'static str {
+        match self {
+            FullscreenMode::Windowed => "setting-off",
+            FullscreenMode::Borderless => "fullscreen-borderless",
+            FullscreenMode::Exclusive => "fullscreen-exclusive",
+        }
+    }
+
+    pub fn next(&self) -> FullscreenMode {
+        match self {
+            FullscreenMode::Windowed => FullscreenMode::Borderless,
+            FullscreenMode::Borderless => FullscreenMode::Exclusive,
+            FullscreenMode::Exclusive => FullscreenMode::Windowed,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum FpsCap {
+    Fps30,
+    Fps60,
+    Fps120,
+    #[default]
+    Uncapped,
+}
+
+impl FpsCap {
+    pub fn fps(&self) -> Option<u32> {
+        match self {
+            FpsCap::Fps30 => Some(30),
+            FpsCap::Fps60 => Some(60),
+            FpsCap::Fps120 => Some(120),
+            FpsCap::Uncapped => None,
+        }
+    }
+
+    pub fn next(&self) -> FpsCap {
+        match self {
+            FpsCap::Fps30 => FpsCap::Fps60,
+            FpsCap::Fps60 => FpsCap::Fps120,
+            FpsCap::Fps120 => FpsCap::Uncapped,
+            FpsCap::Uncapped => FpsCap::Fps30,
+        }
+    }
+
+    /// The shortest time a frame may take, if there is a cap.
+    pub fn frame_time(&self) -> Option<Duration> {
+        self.fps()
+            .map(|fps| Duration::from_secs_f64(1.0 / fps as f64))
+    }
+}
+
+/// How many monitors were connected when the video settings were last
+/// applied, so menus know when to wrap around.
+#[derive(Resource, Default)]
+pub struct Monitors(pub usize);
+
+/// `--fullscreen` picks borderless fullscreen for players who haven't chosen
+/// a mode in the settings yet.
+fn apply_launch_fullscreen(options: Res<LaunchOptions>, mut settings: ResMut<Settings>) {
+    if options.fullscreen && settings.fullscreen == FullscreenMode::Windowed {
+        settings.fullscreen = FullscreenMode::Borderless;
+    }
+}
+
+/// Bevy's window API can't choose a monitor, so this goes through winit.
+/// Settings that no longer exist, such as an unplugged monitor, fall back to
+/// the monitor the window is on.
+fn apply_video_settings(
+    settings: Res<Settings>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    winit_windows: NonSend<WinitWindows>,
+    mut monitors: ResMut<Monitors>,
+    mut applied: Local<Option<(FullscreenMode, usize)>>,
+) {
+    let wanted = (settings.fullscreen, settings.monitor);
+    if *applied == Some(wanted) {
+        return;
+    }
+
+    let window = match windows
+        .get_single()
+        .ok()
+        .and_then(|window| winit_windows.get_window(window))
+    {
+        Some(window) => window,
+        None => return,
+    };
+
+    let available: Vec<MonitorHandle> = window.available_monitors().collect();
+    monitors.0 = available.len();
+    let monitor = match available
+        .get(settings.monitor)
+        .cloned()
+        .or_else(|| window.current_monitor())
+    {
+        Some(monitor) => monitor,
+        None => return,
+    };
+
+    match settings.fullscreen {
+        FullscreenMode::Windowed => {
+            window.set_fullscreen(None);
+            if applied.is_some() || window.current_monitor().as_ref() != Some(&monitor) {
+                center_on(window, &monitor);
+            }
+        }
+        FullscreenMode::Borderless => {
+            window.set_fullscreen(Some(Fullscreen::Borderless(Some(monitor))));
+        }
+        FullscreenMode::Exclusive => {
+            let fullscreen = match monitor.video_modes().max_by_key(|mode| {
+                (
+                    mode.size().width * mode.size().height,
+                    mode.refresh_rate_millihertz(),
+                )
+            }) {
+                Some(mode) => Fullscreen::Exclusive(mode),
+                None => Fullscreen::Borderless(Some(monitor)),
+            };
+            window.set_fullscreen(Some(fullscreen));
+        }
+    }
+
+    *applied = Some(wanted);
+}
+
+fn apply_vsync(
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut applied: Local<Option<bool>>,
+) {
+    if *applied == Some(settings.vsync) {
+        return;
+    }
+
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.present_mode = if settings.vsync {
+            PresentMode::Fifo
+        } else {
+            PresentMode::Immediate
+        };
+        *applied = Some(settings.vsync);
+    }
+}
+
+/// Sleeps off whatever is left of the frame budget. Runs last so that every
+/// other system, and the previous frame's rendering, count towards it.
+#[cfg(not(target_arch = "wasm32"))]
+fn limit_frame_rate(settings: Res<Settings>, mut frame_start: Local<Option<Instant>>) {
+    if let (Some(frame_time), Some(start)) = (settings.fps_cap.frame_time(), *frame_start) {
+        let elapsed = start.elapsed();
+        if elapsed < frame_time {
+            std::thread::sleep(frame_time - elapsed);
+        }
+    }
+
+    *frame_start = Some(Instant::now());
+}
+
+fn center_on(window: &WinitWindow, monitor: &MonitorHandle) {
+    let origin = monitor.position();
+    let monitor_size = monitor.size();
+    let window_size = window.outer_size();
+
+    window.set_outer_position(PhysicalPosition::new(
+        origin.x + (monitor_size.width as i32 - window_size.width as i32) / 2,
+        origin.y + (monitor_size.height as i32 - window_size.height as i32) / 2,
+    ));
+}
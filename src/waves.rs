@@ -0,0 +1,262 @@
+use std::time::Duration;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bounds::PlayArea,
+    console::AddConsoleCommand,
+    enemy::{AutoSpawn, EnemyDropEvent, EnemyTableHandle},
+    enemy_table::EnemyTable,
+    loading::LoadingAssets,
+    states::{GameState, RunState},
+    time_scale::TimeScale,
+};
+
+pub const WAVE_TABLE_PATH: &str = "default.waves.ron";
+
+/// Drops a formation of enemies every so often on top of the regular spawns,
+/// going through the patterns in [`WaveTable`] in order. Each wave reads the
+/// table as it starts, so with the asset folder watched, a pattern edited and
+/// saved during a run is used by the next wave. `wave` in the console starts
+/// the next one straight away.
+///
+/// Waves only come while the spawner drops enemies on its own, so not in
+/// versus rounds.
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<WaveTable>()
+            .init_asset_loader::<WaveTableLoader>()
+            .add_systems(Startup, load_wave_table)
+            .add_cheat_command("wave", wave_command)
+            .add_systems(OnEnter(GameState::Playing), setup_waves)
+            .add_systems(Update, run_waves.run_if(in_state(RunState::Running)));
+    }
+}
+
+/// Formations and how often they come. Loaded from any file ending in
+/// `.waves.ron`, by default `assets/default.waves.ron`.
+#[derive(Debug, Deserialize, Asset, TypePath)]
+pub struct WaveTable {
+    /// Seconds between the start of one wave and the next.
+    pub interval: f32,
+    pub patterns: Vec<Pattern>,
+}
+
+impl WaveTable {
+    /// Turns down values the timers would panic on, so a bad save of the
+    /// file is logged and the last good table kept.
+    fn check(&self) -> Result<(), anyhow::Error> {
+        if !self.interval.is_finite() || self.interval <= 0.0 {
+            anyhow::bail!("interval is {}, it has to be more than 0", self.interval);
+        }
+        for pattern in &self.patterns {
+            for enemy in &pattern.enemies {
+                if !enemy.x.is_finite() || !enemy.delay.is_finite() || enemy.delay < 0.0 {
+                    anyhow::bail!(
+                        "'{}' has a {} at x {} after {}s, both have to be finite and the delay can't be negative",
+                        pattern.name,
+                        enemy.kind,
+                        enemy.x,
+                        enemy.delay
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Pattern {
+    pub name: String,
+    pub enemies: Vec<PatternEnemy>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PatternEnemy {
+    /// The name of an [`EnemyTable`] kind.
+    pub kind: String,
+    /// Across the play area, from -1.0 at the left edge to 1.0 at the right.
+    pub x: f32,
+    /// Seconds after the wave starts.
+    #[serde(default)]
+    pub delay: f32,
+}
+
+#[derive(Default)]
+pub struct WaveTableLoader;
+
+impl AssetLoader for WaveTableLoader {
+    type Asset = WaveTable;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<WaveTable, anyhow::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let table: WaveTable = ron::de::from_bytes(&bytes)?;
+        table.check()?;
+        Ok(table)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["waves.ron"]
+    }
+}
+
+#[derive(Resource)]
+pub struct WaveTableHandle(pub Handle<WaveTable>);
+
+#[derive(Resource)]
+struct Waves {
+    timer: Timer,
+    /// Index of the next pattern to drop.
+    next: usize,
+    /// Enemies of waves already started, as the kind's index, the x position
+    /// and the seconds left until they drop.
+    queued: Vec<(usize, f32, f32)>,
+}
+
+/// Where the waves of a run had got to, see [`crate::snapshot`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct WavesSnapshot {
+    /// Seconds into the current interval.
+    pub elapsed: f32,
+    pub next: usize,
+    pub queued: Vec<(usize, f32, f32)>,
+}
+
+pub fn snapshot_waves(world: &World) -> WavesSnapshot {
+    match world.get_resource::<Waves>() {
+        Some(waves) => WavesSnapshot {
+            elapsed: waves.timer.elapsed_secs(),
+            next: waves.next,
+            queued: waves.queued.clone(),
+        },
+        None => WavesSnapshot::default(),
+    }
+}
+
+pub fn restore_waves(world: &mut World, snapshot: &WavesSnapshot) {
+    if let Some(mut waves) = world.get_resource_mut::<Waves>() {
+        waves
+            .timer
+            .set_elapsed(Duration::from_secs_f32(snapshot.elapsed));
+        waves.next = snapshot.next;
+        waves.queued = snapshot.queued.clone();
+    }
+}
+
+fn load_wave_table(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    let table = asset_server.load(WAVE_TABLE_PATH);
+    loading.add(&table);
+    commands.insert_resource(WaveTableHandle(table));
+}
+
+fn setup_waves(
+    mut commands: Commands,
+    tables: Res<Assets<WaveTable>>,
+    handle: Res<WaveTableHandle>,
+) {
+    let interval = tables.get(&handle.0).map_or(0.0, |table| table.interval);
+    commands.insert_resource(Waves {
+        timer: Timer::from_seconds(interval, TimerMode::Repeating),
+        next: 0,
+        queued: Vec::new(),
+    });
+}
+
+/// Like the spawner, a run without a wave table carries on without waves.
+#[allow(clippy::too_many_arguments)]
+fn run_waves(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    wave_tables: Res<Assets<WaveTable>>,
+    wave_handle: Res<WaveTableHandle>,
+    enemy_tables: Res<Assets<EnemyTable>>,
+    enemy_handle: Res<EnemyTableHandle>,
+    play_area: Res<PlayArea>,
+    auto_spawn: Res<AutoSpawn>,
+    waves: Option<ResMut<Waves>>,
+    mut ev_drop: EventWriter<EnemyDropEvent>,
+) {
+    let (wave_table, enemy_table, mut waves) = match (
+        wave_tables.get(&wave_handle.0),
+        enemy_tables.get(&enemy_handle.0),
+        waves,
+    ) {
+        (Some(wave_table), Some(enemy_table), Some(waves)) => (wave_table, enemy_table, waves),
+        _ => return,
+    };
+    if !auto_spawn.0 || wave_table.patterns.is_empty() || wave_table.interval <= 0.0 {
+        return;
+    }
+
+    let delta = time_scale.delta(&time);
+    let interval = Duration::from_secs_f32(wave_table.interval);
+    if waves.timer.duration() != interval {
+        waves.timer.set_duration(interval);
+    }
+
+    if waves.timer.tick(delta).just_finished() {
+        let pattern = &wave_table.patterns[waves.next % wave_table.patterns.len()];
+        waves.next += 1;
+        debug!("starting wave '{}'", pattern.name);
+
+        let center = (play_area.x.start + play_area.x.end) / 2.0;
+        let half_width = (play_area.x.end - play_area.x.start) / 2.0;
+        for enemy in &pattern.enemies {
+            match enemy_table
+                .kinds
+                .iter()
+                .position(|kind| kind.name == enemy.kind)
+            {
+                Some(kind) => {
+                    let x = center + enemy.x.clamp(-1.0, 1.0) * half_width;
+                    waves.queued.push((kind, x, enemy.delay));
+                }
+                None => warn!(
+                    "wave '{}' has an unknown enemy kind '{}'",
+                    pattern.name, enemy.kind
+                ),
+            }
+        }
+    }
+
+    let delta_seconds = delta.as_secs_f32();
+    waves.queued.retain_mut(|(kind, x, delay)| {
+        *delay -= delta_seconds;
+        if *delay > 0.0 {
+            return true;
+        }
+        ev_drop.send(EnemyDropEvent { kind: *kind, x: *x });
+        false
+    });
+}
+
+/// `wave` starts the next wave now, rather than at the end of the interval.
+fn wave_command(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    match world.get_resource_mut::<Waves>() {
+        Some(mut waves) => {
+            let duration = waves.timer.duration();
+            waves.timer.set_elapsed(duration);
+            Ok("starting the next wave".to_string())
+        }
+        None => Err("no run in progress".to_string()),
+    }
+}
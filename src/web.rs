@@ -0,0 +1,27 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+/// Browser glue for the `web` build. Bevy sizes the canvas once from the
+/// window's resolution, so this keeps it matched to the browser window.
+pub struct WebPlugin;
+
+impl Plugin for WebPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, fit_canvas_to_browser);
+    }
+}
+
+fn fit_canvas_to_browser(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    let browser = match web_sys::window() {
+        Some(browser) => browser,
+        None => return,
+    };
+    let width = browser.inner_width().ok().and_then(|value| value.as_f64());
+    let height = browser.inner_height().ok().and_then(|value| value.as_f64());
+
+    if let (Some(width), Some(height), Ok(mut window)) = (width, height, windows.get_single_mut()) {
+        let (width, height) = (width as f32, height as f32);
+        if window.width() != width || window.height() != height {
+            window.resolution.set(width, height);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Properties of the collision math that must hold for any boxes, not only
+//! the ones that come up in play.
+
+use dodger::collision::Aabb;
+use proptest::prelude::*;
+
+/// Wider than the play area, so offscreen enemies are covered too.
+const COORD: f32 = 1_000.0;
+/// From smaller than the smallest enemy to bigger than the biggest.
+const SIZE: std::ops::Range<f32> = 0.1..100.0;
+
+fn point() -> impl Strategy<Value = [f32; 2]> {
+    [-COORD..COORD, -COORD..COORD]
+}
+
+fn size() -> impl Strategy<Value = [f32; 2]> {
+    [SIZE, SIZE]
+}
+
+fn aabb() -> impl Strategy<Value = Aabb> {
+    (point(), size()).prop_map(|(center, size)| Aabb::new(center, size))
+}
+
+proptest! {
+    #[test]
+    fn overlap_is_symmetric(a in aabb(), b in aabb()) {
+        prop_assert_eq!(a.overlaps(&b), b.overlaps(&a));
+    }
+
+    #[test]
+    fn boxes_overlap_themselves(a in aabb()) {
+        prop_assert!(a.overlaps(&a));
+        prop_assert!(a.contains(&a));
+    }
+
+    /// A box shrunk by any amount, anywhere inside another, is contained by
+    /// it and overlaps it.
+    #[test]
+    fn contained_boxes_overlap(
+        outer in aabb(),
+        scale in [0.01f32..1.0, 0.01f32..1.0],
+        along in [0.0f32..1.0, 0.0f32..1.0],
+    ) {
+        let mut min = [0.0; 2];
+        let mut max = [0.0; 2];
+        for axis in 0..2 {
+            let extent = outer.max[axis] - outer.min[axis];
+            let inner = extent * scale[axis];
+            min[axis] = outer.min[axis] + (extent - inner) * along[axis];
+            max[axis] = (min[axis] + inner).min(outer.max[axis]);
+        }
+        let inner = Aabb { min, max };
+        prop_assume!(inner.min[0] < inner.max[0] && inner.min[1] < inner.max[1]);
+
+        prop_assert!(outer.contains(&inner));
+        prop_assert!(outer.overlaps(&inner));
+        prop_assert!(inner.overlaps(&outer));
+    }
+
+    /// Boxes whose centers are closer than half their combined sizes on both
+    /// axes always overlap. Offsets stop a little short of touching so
+    /// rounding can't decide the result.
+    #[test]
+    fn no_false_negatives(
+        center in point(),
+        a_size in size(),
+        b_size in size(),
+        offset in [-0.95f32..0.95, -0.95f32..0.95],
+    ) {
+        let b_center = [
+            center[0] + offset[0] * (a_size[0] + b_size[0]) / 2.0,
+            center[1] + offset[1] * (a_size[1] + b_size[1]) / 2.0,
+        ];
+        let a = Aabb::new(center, a_size);
+        let b = Aabb::new(b_center, b_size);
+
+        prop_assert!(a.overlaps(&b), "{:?} and {:?} should overlap", a, b);
+    }
+
+    /// Boxes apart on either axis never overlap, however close they are on
+    /// the other.
+    #[test]
+    fn no_false_positives(
+        center in point(),
+        a_size in size(),
+        b_size in size(),
+        gap in 1.05f32..10.0,
+        along in -0.95f32..0.95,
+        axis in 0usize..2,
+        side in prop::bool::ANY,
+    ) {
+        let other = 1 - axis;
+        let mut b_center = center;
+        let apart = gap * (a_size[axis] + b_size[axis]) / 2.0;
+        b_center[axis] += if side { apart } else { -apart };
+        b_center[other] += along * (a_size[other] + b_size[other]) / 2.0;
+        let a = Aabb::new(center, a_size);
+        let b = Aabb::new(b_center, b_size);
+
+        prop_assert!(!a.overlaps(&b), "{:?} and {:?} shouldn't overlap", a, b);
+    }
+}
@@ -0,0 +1,97 @@
+//! Enemy tables the loader has to turn down, rather than let the spawner
+//! panic on the first enemy.
+
+use dodger::enemy_table::EnemyTable;
+
+/// A table with one kind, `rock`, where `field` is set to `value`.
+fn table(field: &str, value: &str) -> Vec<u8> {
+    let mut fields = vec![
+        ("sprite", "1069"),
+        ("size", "(start: 0.5, end: 5.0)"),
+        ("speed", "(start: 50.0, end: 125.0)"),
+        ("spin", "(start: -3.0, end: 3.0)"),
+        ("weight", "4.0"),
+    ];
+    for (name, old) in &mut fields {
+        if *name == field {
+            *old = value;
+        }
+    }
+    let fields: String = fields
+        .iter()
+        .map(|(name, value)| format!("{}: {}, ", name, value))
+        .collect();
+    format!("(kinds: [(name: \"rock\", {})])", fields).into_bytes()
+}
+
+fn assert_rejected(field: &str, value: &str) {
+    let err = EnemyTable::from_bytes(&table(field, value))
+        .err()
+        .unwrap_or_else(|| panic!("{} of {} was accepted", field, value));
+    assert!(
+        err.to_string().contains(field),
+        "error for {} doesn't name it: {}",
+        field,
+        err
+    );
+}
+
+#[test]
+fn default_table_loads() {
+    let table = EnemyTable::from_bytes(include_bytes!("../assets/default.enemies.ron")).unwrap();
+    assert!(!table.kinds.is_empty());
+}
+
+#[test]
+fn valid_kind_loads() {
+    let table = EnemyTable::from_bytes(&table("weight", "1.0")).unwrap();
+    assert_eq!(table.kinds[0].name, "rock");
+}
+
+#[test]
+fn empty_ranges_are_rejected() {
+    assert_rejected("size", "(start: 1.0, end: 1.0)");
+    assert_rejected("speed", "(start: 100.0, end: 100.0)");
+    assert_rejected("spin", "(start: 0.0, end: 0.0)");
+}
+
+#[test]
+fn inverted_ranges_are_rejected() {
+    assert_rejected("size", "(start: 5.0, end: 0.5)");
+    assert_rejected("speed", "(start: 125.0, end: 50.0)");
+    assert_rejected("spin", "(start: 3.0, end: -3.0)");
+}
+
+#[test]
+fn ranges_that_are_not_finite_are_rejected() {
+    assert_rejected("size", "(start: 0.5, end: inf)");
+    assert_rejected("speed", "(start: NaN, end: 125.0)");
+    assert_rejected("spin", "(start: -inf, end: 3.0)");
+}
+
+#[test]
+fn negative_weights_are_rejected() {
+    assert_rejected("weight", "-1.0");
+    assert_rejected("weight", "NaN");
+}
+
+#[test]
+fn variants_are_checked_too() {
+    let bytes = br#"(kinds: [
+        (name: "rock", sprite: 1069, size: (start: 0.5, end: 5.0),
+         speed: (start: 50.0, end: 125.0), spin: (start: -3.0, end: 3.0), weight: 4.0),
+        (name: "stuck rock", base: "rock", speed: (start: 0.0, end: 0.0)),
+    ])"#;
+    let err = EnemyTable::from_bytes(bytes).unwrap_err();
+    assert!(err.to_string().contains("stuck rock"), "{}", err);
+}
+
+#[test]
+fn sprites_off_the_sheet_are_rejected() {
+    assert_rejected("sprite", "1078");
+}
+
+#[test]
+fn tables_where_nothing_can_spawn_are_rejected() {
+    assert!(EnemyTable::from_bytes(&table("weight", "0.0")).is_err());
+}
@@ -0,0 +1,340 @@
+//! Plays short runs in the headless simulation and checks what must hold
+//! for every run, whatever the seed.
+
+use bevy::{
+    ecs::event::{Events, ManualEventReader},
+    prelude::*,
+};
+use dodger::{
+    bounds::PlayArea,
+    enemy::{Collider, EnemyBurstEvent, EnemyDropEvent, EnemySpawnEvent},
+    headless::{self, InputScript, ScriptedInput},
+    movement::FIXED_STEP,
+    player::{Player, PlayerDeathEvent},
+    replay::{Replay, ReplayFrame},
+    save::{MemoryBackend, Save},
+    score::Scoreboard,
+    snapshot::RunSnapshot,
+    states::GameState,
+};
+
+const SEED: u64 = 7;
+/// Long enough for loading to finish, which takes a varying number of frames.
+const MAX_LOADING_FRAMES: u32 = 600;
+/// Long enough for an enemy dropped at the top to reach the player at the
+/// slowest speed, and for the death sequence to play out.
+const MAX_RUN_FRAMES: u32 = 1_200;
+
+/// Presses space on the given frames, each starting a run from the title or
+/// the game over screen.
+fn press_space(frames: &[u32]) -> InputScript {
+    let steps = frames
+        .iter()
+        .flat_map(|&frame| {
+            [
+                ScriptedInput {
+                    frame,
+                    key: KeyCode::Space,
+                    pressed: true,
+                },
+                ScriptedInput {
+                    frame: frame + 1,
+                    key: KeyCode::Space,
+                    pressed: false,
+                },
+            ]
+        })
+        .collect();
+    InputScript::new(steps)
+}
+
+/// Runs with default settings, whatever is saved on this machine.
+fn build_app(script: InputScript) -> App {
+    headless::build_app_with_save(SEED, script, Save(Box::new(MemoryBackend::default())))
+}
+
+fn state(app: &App) -> GameState {
+    app.world()
+        .get_resource::<State<GameState>>()
+        .unwrap()
+        .get()
+        .clone()
+}
+
+/// Updates until `state` is reached, and returns how many frames it took.
+fn run_until(app: &mut App, state: GameState, max_frames: u32) -> u32 {
+    for frame in 0..max_frames {
+        if self::state(app) == state {
+            return frame;
+        }
+        app.update();
+    }
+    panic!("{:?} not reached in {} frames", state, max_frames);
+}
+
+/// Drops an enemy straight above the first player, who stands still.
+fn drop_on_player(app: &mut App) {
+    let x = app
+        .world_mut()
+        .query_filtered::<&Transform, With<Player>>()
+        .iter(app.world())
+        .next()
+        .expect("the run has a player")
+        .translation
+        .x;
+    app.world_mut()
+        .get_resource_mut::<Events<EnemyDropEvent>>()
+        .unwrap()
+        .send(EnemyDropEvent { kind: 0, x });
+}
+
+fn entity_count(app: &App) -> u32 {
+    app.world().entities().len()
+}
+
+#[test]
+fn score_accrues_while_playing() {
+    let mut app = build_app(press_space(&[0]));
+    run_until(&mut app, GameState::Playing, MAX_LOADING_FRAMES);
+
+    for _ in 0..120 {
+        app.update();
+    }
+
+    assert_eq!(state(&app), GameState::Playing);
+    let score = app.world().get_resource::<Scoreboard>().unwrap().score;
+    assert!(score >= 1.0, "score was {} after two seconds", score);
+}
+
+#[test]
+fn collisions_end_the_run() {
+    let mut app = build_app(press_space(&[0]));
+    run_until(&mut app, GameState::Playing, MAX_LOADING_FRAMES);
+    drop_on_player(&mut app);
+
+    let mut reader = ManualEventReader::<PlayerDeathEvent>::default();
+    let mut deaths = 0;
+    for _ in 0..MAX_RUN_FRAMES {
+        if state(&app) == GameState::GameOver {
+            break;
+        }
+        app.update();
+        let events = app
+            .world()
+            .get_resource::<Events<PlayerDeathEvent>>()
+            .unwrap();
+        deaths += reader.read(events).count();
+    }
+
+    assert_eq!(state(&app), GameState::GameOver);
+    assert_eq!(deaths, 1);
+}
+
+#[test]
+fn finished_runs_leave_no_entities_behind() {
+    let mut app = build_app(InputScript::default());
+    run_until(&mut app, GameState::Title, MAX_LOADING_FRAMES);
+    // Let the title's enter systems run.
+    app.update();
+    let before = entity_count(&app);
+
+    // The second run starts from the game over screen of the first.
+    for _ in 0..2 {
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        run_until(&mut app, GameState::Playing, 10);
+
+        drop_on_player(&mut app);
+        app.world_mut()
+            .get_resource_mut::<Events<EnemyBurstEvent>>()
+            .unwrap()
+            .send(EnemyBurstEvent(20));
+        run_until(&mut app, GameState::GameOver, MAX_RUN_FRAMES);
+
+        assert_eq!(entity_count(&app), before);
+        let players = app
+            .world_mut()
+            .query_filtered::<(), With<Player>>()
+            .iter(app.world())
+            .count();
+        let enemies = app
+            .world_mut()
+            .query_filtered::<(), With<Collider>>()
+            .iter(app.world())
+            .count();
+        assert_eq!((players, enemies), (0, 0));
+    }
+}
+
+/// Suspending a run or jumping to the title with the `state` command leaves
+/// it without going through `Dying` or the game over screen.
+#[test]
+fn abandoned_runs_leave_no_entities_behind() {
+    let mut app = build_app(InputScript::default());
+    run_until(&mut app, GameState::Title, MAX_LOADING_FRAMES);
+    app.update();
+    let before = entity_count(&app);
+
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    run_until(&mut app, GameState::Playing, 10);
+    app.world_mut()
+        .get_resource_mut::<Events<EnemyBurstEvent>>()
+        .unwrap()
+        .send(EnemyBurstEvent(20));
+    for _ in 0..30 {
+        app.update();
+    }
+
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Title);
+    run_until(&mut app, GameState::Title, 10);
+    app.update();
+
+    assert_eq!(entity_count(&app), before);
+}
+
+#[test]
+fn spawner_drops_at_most_one_enemy_per_step() {
+    const BURST: u32 = 30;
+    const FRAMES: usize = 60;
+
+    let mut app = build_app(press_space(&[0]));
+    run_until(&mut app, GameState::Playing, MAX_LOADING_FRAMES);
+    app.world_mut()
+        .get_resource_mut::<Events<EnemyBurstEvent>>()
+        .unwrap()
+        .send(EnemyBurstEvent(BURST));
+
+    let mut reader = ManualEventReader::<EnemySpawnEvent>::default();
+    let mut spawned = 0;
+    for _ in 0..FRAMES {
+        app.update();
+        let events = app
+            .world()
+            .get_resource::<Events<EnemySpawnEvent>>()
+            .unwrap();
+        let count = reader.read(events).count();
+        // Each headless frame is exactly one fixed step.
+        assert!(count <= 1, "{} enemies spawned in one frame", count);
+        spawned += count;
+    }
+
+    assert!(
+        spawned <= FRAMES,
+        "{} spawned in {} frames",
+        spawned,
+        FRAMES
+    );
+    assert!(
+        spawned >= BURST as usize,
+        "only {} of the burst of {} spawned",
+        spawned,
+        BURST
+    );
+}
+
+/// A full queue of enemies, topped up by bursts and by timed spawns held
+/// back for drops, must not overflow.
+#[test]
+fn queued_enemies_saturate_instead_of_overflowing() {
+    const FRAMES: usize = 60;
+
+    let mut app = build_app(press_space(&[0]));
+    run_until(&mut app, GameState::Playing, MAX_LOADING_FRAMES);
+    for _ in 0..2 {
+        app.world_mut()
+            .get_resource_mut::<Events<EnemyBurstEvent>>()
+            .unwrap()
+            .send(EnemyBurstEvent(u32::MAX));
+    }
+
+    // Dropped at the edge, away from the player, so the run keeps going.
+    let x = app.world().resource::<PlayArea>().x.start;
+    let mut reader = ManualEventReader::<EnemySpawnEvent>::default();
+    let mut spawned = 0;
+    for _ in 0..FRAMES {
+        app.world_mut()
+            .get_resource_mut::<Events<EnemyDropEvent>>()
+            .unwrap()
+            .send(EnemyDropEvent { kind: 0, x });
+        app.update();
+        let events = app
+            .world()
+            .get_resource::<Events<EnemySpawnEvent>>()
+            .unwrap();
+        let count = reader.read(events).count();
+        assert!(count <= 1, "{} enemies spawned in one frame", count);
+        spawned += count;
+    }
+
+    // However many are queued, they still come out one step at a time. The
+    // first frame's step runs before anything is queued.
+    assert!(
+        spawned >= FRAMES - 1,
+        "only {} spawned in {} frames",
+        spawned,
+        FRAMES
+    );
+}
+
+fn enemy_positions(app: &mut App) -> Vec<Vec2> {
+    app.world_mut()
+        .query_filtered::<&Transform, With<Collider>>()
+        .iter(app.world())
+        .map(|transform| transform.translation.truncate())
+        .collect()
+}
+
+/// A saved replay of a long run only keeps its end, and starts by putting
+/// the run back the way it was there. It must then play out the same.
+#[test]
+fn replays_starting_mid_run_play_out_the_same() {
+    const BEFORE: usize = 300;
+    const AFTER: usize = 300;
+
+    let mut app = build_app(press_space(&[0]));
+    run_until(&mut app, GameState::Playing, MAX_LOADING_FRAMES);
+    for _ in 0..BEFORE {
+        app.update();
+    }
+    let start = RunSnapshot::capture(app.world_mut());
+    for _ in 0..AFTER {
+        app.update();
+    }
+
+    let frame = ReplayFrame {
+        delta_nanos: Time::<Fixed>::from_seconds(FIXED_STEP as f64)
+            .timestep()
+            .as_nanos() as u64,
+        left: false,
+        right: false,
+        left_two: false,
+        right_two: false,
+        x: 0.0,
+    };
+    let mut replay = headless::build_replay_app(Replay {
+        seed: SEED,
+        start: Some(start),
+        frames: vec![frame; AFTER],
+        player_count: 1,
+        upgrades: Vec::new(),
+        assist_hitbox: false,
+        assist_lives: false,
+        game_speed: 100,
+    });
+    run_until(&mut replay, GameState::Playing, MAX_LOADING_FRAMES);
+    for _ in 0..AFTER {
+        replay.update();
+    }
+
+    assert_eq!(state(&replay), state(&app));
+    let score = |app: &App| app.world().resource::<Scoreboard>().score;
+    assert_eq!(score(&replay), score(&app));
+    let enemies = enemy_positions(&mut app);
+    assert!(!enemies.is_empty());
+    assert_eq!(enemy_positions(&mut replay), enemies);
+}